@@ -0,0 +1,296 @@
+//! Command-line vote account tool.
+//!
+//! ```text
+//! vote_cli decode <ACCOUNT_DATA_FILE>
+//! vote_cli build <INSTRUCTION> <ARGS>...
+//! vote_cli predict <ACCOUNT_DATA_FILE> <LAMPORTS> <SLOT> <EPOCH> <INSTRUCTION> <ARGS>...
+//! ```
+//!
+//! Account data files hold the raw bytes of a vote account. Instructions
+//! are printed as program id, account metas and hex data, ready to be
+//! signed and sent with any client; this tool does not send them.
+//! `predict` applies the instruction as if it landed at `SLOT` in `EPOCH`,
+//! under the default epoch schedule; it only predicts administrative
+//! instructions.
+//!
+//! Instructions and their arguments, all keys and hashes base-58:
+//!
+//! ```text
+//! initialize <VOTE> <NODE> <VOTER> <WITHDRAWER> <COMMISSION>
+//! authorize <VOTE> <AUTHORITY> <NEW_AUTHORITY> voter|withdrawer
+//! authorize-checked <VOTE> <AUTHORITY> <NEW_AUTHORITY> voter|withdrawer
+//! authorize-with-seed <VOTE> <BASE> <OWNER> <SEED> <NEW_AUTHORITY> voter|withdrawer
+//! authorize-checked-with-seed <VOTE> <BASE> <OWNER> <SEED> <NEW_AUTHORITY> voter|withdrawer
+//! update-validator-identity <VOTE> <NODE> <WITHDRAWER>
+//! update-commission <VOTE> <WITHDRAWER> <COMMISSION>
+//! withdraw <VOTE> <WITHDRAWER> <LAMPORTS> <TO>
+//! vote <VOTE> <VOTER> <SLOT,...> <HASH>
+//! vote-switch <VOTE> <VOTER> <SLOT,...> <HASH> <PROOF_HASH>
+//! update-vote-state <VOTE> <VOTER> <SLOT:CONFIRMATIONS,...> <ROOT|none> <HASH>
+//! update-vote-state-switch <VOTE> <VOTER> <SLOT:CONFIRMATIONS,...> <ROOT|none> <HASH> <PROOF_HASH>
+//! compact-update-vote-state <VOTE> <VOTER> <SLOT:CONFIRMATIONS,...> <ROOT|none> <HASH>
+//! compact-update-vote-state-switch <VOTE> <VOTER> <SLOT:CONFIRMATIONS,...> <ROOT|none> <HASH> <PROOF_HASH>
+//! ```
+
+use {
+    solana_program::{
+        clock::{Clock, Slot},
+        epoch_schedule::EpochSchedule,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        rent::Rent,
+        sysvar,
+        vote::{
+            self,
+            instruction::{self as vote_instruction, VoteInstruction},
+            state::{Lockout, Vote, VoteAuthorize, VoteInit, VoteStateUpdate},
+        },
+    },
+    solana_vote_program::{
+        display::DisplayVoteState,
+        predict::{predict, PreAccount},
+        sanitize::sanitize_vote_account_data,
+    },
+    std::{env, error::Error, fs, process, str::FromStr},
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> CliResult<()> {
+    match args {
+        [command, path] if command == "decode" => {
+            let vote_state = sanitize_vote_account_data(&fs::read(path)?)?;
+            print!("{}", DisplayVoteState(&vote_state));
+        }
+        [command, instruction_args @ ..] if command == "build" => {
+            print_instruction(&build_instruction(instruction_args)?);
+        }
+        [command, path, lamports, slot, epoch, instruction_args @ ..] if command == "predict" => {
+            let data = fs::read(path)?;
+            let vote_state = sanitize_vote_account_data(&data)?;
+            let instruction = build_instruction(instruction_args)?;
+            let pre_account = PreAccount {
+                lamports: lamports.parse()?,
+                data_len: data.len(),
+            };
+            let epoch_schedule = EpochSchedule::default();
+            let slot: Slot = slot.parse()?;
+            let clock = Clock {
+                slot,
+                epoch: epoch.parse()?,
+                leader_schedule_epoch: epoch_schedule.get_leader_schedule_epoch(slot),
+                ..Clock::default()
+            };
+            let effects = predict(
+                &instruction,
+                &vote_state,
+                pre_account,
+                &clock,
+                &Rent::default(),
+                &epoch_schedule,
+            )?;
+            println!("Lamports: {}", effects.lamports);
+            match effects.vote_state {
+                Some(vote_state) => print!("{}", DisplayVoteState(&vote_state)),
+                None => println!("Account closed"),
+            }
+        }
+        _ => return Err("usage: vote_cli decode|build|predict ...".into()),
+    }
+    Ok(())
+}
+
+fn build_instruction(args: &[String]) -> CliResult<Instruction> {
+    let pubkey = |arg: &String| Pubkey::from_str(arg);
+    let vote_authorize = |arg: &String| match arg.as_str() {
+        "voter" => Ok(VoteAuthorize::Voter),
+        "withdrawer" => Ok(VoteAuthorize::Withdrawer),
+        _ => Err(format!("unknown authority kind {arg}")),
+    };
+    let hash = |arg: &String| Hash::from_str(arg);
+    Ok(match args {
+        [name, vote, node, voter, withdrawer, commission] if name == "initialize" => {
+            initialize_account(
+                &pubkey(vote)?,
+                &VoteInit {
+                    node_pubkey: pubkey(node)?,
+                    authorized_voter: pubkey(voter)?,
+                    authorized_withdrawer: pubkey(withdrawer)?,
+                    commission: commission.parse()?,
+                },
+            )
+        }
+        [name, vote, authority, new_authority, kind] if name == "authorize" => {
+            vote_instruction::authorize(
+                &pubkey(vote)?,
+                &pubkey(authority)?,
+                &pubkey(new_authority)?,
+                vote_authorize(kind)?,
+            )
+        }
+        [name, vote, authority, new_authority, kind] if name == "authorize-checked" => {
+            vote_instruction::authorize_checked(
+                &pubkey(vote)?,
+                &pubkey(authority)?,
+                &pubkey(new_authority)?,
+                vote_authorize(kind)?,
+            )
+        }
+        [name, vote, base, owner, seed, new_authority, kind] if name == "authorize-with-seed" => {
+            vote_instruction::authorize_with_seed(
+                &pubkey(vote)?,
+                &pubkey(base)?,
+                &pubkey(owner)?,
+                seed,
+                &pubkey(new_authority)?,
+                vote_authorize(kind)?,
+            )
+        }
+        [name, vote, base, owner, seed, new_authority, kind]
+            if name == "authorize-checked-with-seed" =>
+        {
+            vote_instruction::authorize_checked_with_seed(
+                &pubkey(vote)?,
+                &pubkey(base)?,
+                &pubkey(owner)?,
+                seed,
+                &pubkey(new_authority)?,
+                vote_authorize(kind)?,
+            )
+        }
+        [name, vote, node, withdrawer] if name == "update-validator-identity" => {
+            vote_instruction::update_validator_identity(
+                &pubkey(vote)?,
+                &pubkey(withdrawer)?,
+                &pubkey(node)?,
+            )
+        }
+        [name, vote, withdrawer, commission] if name == "update-commission" => {
+            vote_instruction::update_commission(
+                &pubkey(vote)?,
+                &pubkey(withdrawer)?,
+                commission.parse()?,
+            )
+        }
+        [name, vote, withdrawer, lamports, to] if name == "withdraw" => vote_instruction::withdraw(
+            &pubkey(vote)?,
+            &pubkey(withdrawer)?,
+            lamports.parse()?,
+            &pubkey(to)?,
+        ),
+        [name, vote, voter, slots, bank_hash] if name == "vote" => vote_instruction::vote(
+            &pubkey(vote)?,
+            &pubkey(voter)?,
+            Vote::new(parse_slots(slots)?, hash(bank_hash)?),
+        ),
+        [name, vote, voter, slots, bank_hash, proof_hash] if name == "vote-switch" => {
+            vote_instruction::vote_switch(
+                &pubkey(vote)?,
+                &pubkey(voter)?,
+                Vote::new(parse_slots(slots)?, hash(bank_hash)?),
+                hash(proof_hash)?,
+            )
+        }
+        [name, vote, voter, lockouts, root, bank_hash] if name == "update-vote-state" => {
+            vote_instruction::update_vote_state(
+                &pubkey(vote)?,
+                &pubkey(voter)?,
+                parse_update(lockouts, root, hash(bank_hash)?)?,
+            )
+        }
+        [name, vote, voter, lockouts, root, bank_hash, proof_hash]
+            if name == "update-vote-state-switch" =>
+        {
+            vote_instruction::update_vote_state_switch(
+                &pubkey(vote)?,
+                &pubkey(voter)?,
+                parse_update(lockouts, root, hash(bank_hash)?)?,
+                hash(proof_hash)?,
+            )
+        }
+        [name, vote, voter, lockouts, root, bank_hash] if name == "compact-update-vote-state" => {
+            vote_instruction::compact_update_vote_state(
+                &pubkey(vote)?,
+                &pubkey(voter)?,
+                parse_update(lockouts, root, hash(bank_hash)?)?,
+            )
+        }
+        [name, vote, voter, lockouts, root, bank_hash, proof_hash]
+            if name == "compact-update-vote-state-switch" =>
+        {
+            vote_instruction::compact_update_vote_state_switch(
+                &pubkey(vote)?,
+                &pubkey(voter)?,
+                parse_update(lockouts, root, hash(bank_hash)?)?,
+                hash(proof_hash)?,
+            )
+        }
+        _ => return Err("unknown instruction or wrong arguments".into()),
+    })
+}
+
+/// `InitializeAccount` for an already created account, which the SDK only
+/// builds together with the account creation.
+fn initialize_account(vote_pubkey: &Pubkey, vote_init: &VoteInit) -> Instruction {
+    Instruction::new_with_bincode(
+        vote::program::id(),
+        &VoteInstruction::InitializeAccount(*vote_init),
+        vec![
+            AccountMeta::new(*vote_pubkey, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(vote_init.node_pubkey, true),
+        ],
+    )
+}
+
+/// Parses comma-separated slots.
+fn parse_slots(arg: &str) -> CliResult<Vec<Slot>> {
+    Ok(arg.split(',').map(str::parse).collect::<Result<_, _>>()?)
+}
+
+/// Parses comma-separated `slot:confirmations` lockouts and a root.
+fn parse_update(lockouts: &str, root: &str, hash: Hash) -> CliResult<VoteStateUpdate> {
+    let lockouts = lockouts
+        .split(',')
+        .map(|lockout| {
+            let (slot, confirmation_count) = lockout
+                .split_once(':')
+                .ok_or_else(|| format!("lockout {lockout} is not SLOT:CONFIRMATIONS"))?;
+            Ok(Lockout::new_with_confirmation_count(
+                slot.parse()?,
+                confirmation_count.parse()?,
+            ))
+        })
+        .collect::<CliResult<_>>()?;
+    let root = match root {
+        "none" => None,
+        root => Some(root.parse()?),
+    };
+    Ok(VoteStateUpdate::new(lockouts, root, hash))
+}
+
+fn print_instruction(instruction: &Instruction) {
+    println!("Program: {}", instruction.program_id);
+    for account_meta in &instruction.accounts {
+        println!(
+            "Account: {} signer={} writable={}",
+            account_meta.pubkey, account_meta.is_signer, account_meta.is_writable
+        );
+    }
+    let data: String = instruction
+        .data
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    println!("Data: {data}");
+}
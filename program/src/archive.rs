@@ -0,0 +1,387 @@
+//! rkyv archives of vote states and vote state updates, for off-chain
+//! caches that keep many snapshots and read them without decoding.
+//!
+//! The record types mirror the SDK types with plain integers and byte
+//! arrays, which rkyv can archive. Archived records are validated when
+//! accessed, so bytes from disk or the network are safe to read, and
+//! restored vote states pass [`sanitize_vote_account_data`].
+//!
+//! This SDK has no `TowerSync`, so vote state updates are archived as
+//! `VoteStateUpdate`.
+
+use {
+    crate::{
+        layout::{PriorVotersRing, PRIOR_VOTERS_LEN},
+        sanitize::{sanitize_vote_account_data, SanitizeError},
+        versioned::Versioned,
+        wire,
+    },
+    rkyv::{AlignedVec, Archive, Deserialize, Infallible, Serialize},
+    serde::Serialize as SerdeSerialize,
+    solana_program::{
+        clock::{Epoch, Slot, UnixTimestamp},
+        hash::Hash,
+        pubkey::Pubkey,
+        vote::state::{
+            BlockTimestamp, LandedVote, Lockout, VoteState, VoteStateUpdate, VoteStateVersions,
+        },
+    },
+    std::collections::BTreeMap,
+    thiserror::Error,
+};
+
+/// Scratch space reserved while archiving; a full vote state fits.
+const SCRATCH_BYTES: usize = 4096;
+
+/// Reasons an archive cannot be written or read.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ArchiveError {
+    #[error("record could not be archived")]
+    Serialize,
+    #[error("bytes are not a valid archive")]
+    Invalid,
+    #[error("archived vote state could not be restored")]
+    InvalidVoteState,
+    #[error("archived vote state is invalid: {0}")]
+    Sanitize(SanitizeError),
+}
+
+#[derive(Archive, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[archive(check_bytes)]
+pub struct LockoutRecord {
+    pub slot: Slot,
+    pub confirmation_count: u32,
+    /// Vote latency; always 0 in vote state updates.
+    pub latency: u8,
+}
+
+#[derive(Archive, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[archive(check_bytes)]
+pub struct PriorVoterRecord {
+    pub pubkey: [u8; 32],
+    pub start_epoch: Epoch,
+    pub end_epoch: Epoch,
+}
+
+/// A `VoteState`, field for field.
+#[derive(Archive, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[archive(check_bytes)]
+pub struct VoteStateRecord {
+    pub node_pubkey: [u8; 32],
+    pub authorized_withdrawer: [u8; 32],
+    pub commission: u8,
+    pub votes: Vec<LockoutRecord>,
+    pub root_slot: Option<Slot>,
+    pub authorized_voters: Vec<(Epoch, [u8; 32])>,
+    /// The prior voters ring buffer in storage order.
+    pub prior_voters: Vec<PriorVoterRecord>,
+    pub prior_voters_idx: u64,
+    pub prior_voters_is_empty: bool,
+    pub epoch_credits: Vec<(Epoch, u64, u64)>,
+    pub last_timestamp_slot: Slot,
+    pub last_timestamp: UnixTimestamp,
+}
+
+/// A `VoteStateUpdate`, field for field.
+#[derive(Archive, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[archive(check_bytes)]
+pub struct VoteStateUpdateRecord {
+    pub lockouts: Vec<LockoutRecord>,
+    pub root: Option<Slot>,
+    pub hash: [u8; 32],
+    pub timestamp: Option<UnixTimestamp>,
+}
+
+impl VoteStateRecord {
+    /// Records `vote_state`, which is borrowed mutably only to read
+    /// `prior_voters`; it is left unchanged.
+    pub fn new(vote_state: &mut VoteState) -> Self {
+        let prior_voters = PriorVotersRing::of(vote_state);
+        Self {
+            node_pubkey: vote_state.node_pubkey.to_bytes(),
+            authorized_withdrawer: vote_state.authorized_withdrawer.to_bytes(),
+            commission: vote_state.commission,
+            votes: vote_state
+                .votes
+                .iter()
+                .map(|landed_vote| LockoutRecord {
+                    slot: landed_vote.slot(),
+                    confirmation_count: landed_vote.confirmation_count(),
+                    latency: landed_vote.latency,
+                })
+                .collect(),
+            root_slot: vote_state.root_slot,
+            authorized_voters: vote_state
+                .authorized_voters()
+                .iter()
+                .map(|(epoch, authorized_voter)| (*epoch, authorized_voter.to_bytes()))
+                .collect(),
+            prior_voters: prior_voters
+                .buf
+                .iter()
+                .map(|(pubkey, start_epoch, end_epoch)| PriorVoterRecord {
+                    pubkey: pubkey.to_bytes(),
+                    start_epoch: *start_epoch,
+                    end_epoch: *end_epoch,
+                })
+                .collect(),
+            prior_voters_idx: prior_voters.idx as u64,
+            prior_voters_is_empty: prior_voters.is_empty,
+            epoch_credits: vote_state.epoch_credits.clone(),
+            last_timestamp_slot: vote_state.last_timestamp.slot,
+            last_timestamp: vote_state.last_timestamp.timestamp,
+        }
+    }
+}
+
+/// `VoteState` fields in serialization order, for restoring the private
+/// authorized and prior voters.
+#[derive(SerdeSerialize)]
+struct VoteStateFields {
+    node_pubkey: Pubkey,
+    authorized_withdrawer: Pubkey,
+    commission: u8,
+    votes: Vec<LandedVote>,
+    root_slot: Option<Slot>,
+    authorized_voters: BTreeMap<Epoch, Pubkey>,
+    prior_voters: PriorVotersRing,
+    epoch_credits: Vec<(Epoch, u64, u64)>,
+    last_timestamp: BlockTimestamp,
+}
+
+impl TryFrom<&VoteStateRecord> for VoteState {
+    type Error = ArchiveError;
+
+    /// Fails if the prior voters ring does not have its fixed length, or if
+    /// the state does not pass [`sanitize_vote_account_data`].
+    fn try_from(record: &VoteStateRecord) -> Result<Self, ArchiveError> {
+        let mut buf = [(Pubkey::default(), 0, 0); PRIOR_VOTERS_LEN];
+        if record.prior_voters.len() != buf.len() {
+            return Err(ArchiveError::InvalidVoteState);
+        }
+        for (entry, prior_voter) in buf.iter_mut().zip(&record.prior_voters) {
+            *entry = (
+                Pubkey::new_from_array(prior_voter.pubkey),
+                prior_voter.start_epoch,
+                prior_voter.end_epoch,
+            );
+        }
+        let fields = VoteStateFields {
+            node_pubkey: Pubkey::new_from_array(record.node_pubkey),
+            authorized_withdrawer: Pubkey::new_from_array(record.authorized_withdrawer),
+            commission: record.commission,
+            votes: record
+                .votes
+                .iter()
+                .map(|lockout| LandedVote {
+                    latency: lockout.latency,
+                    lockout: Lockout::new_with_confirmation_count(
+                        lockout.slot,
+                        lockout.confirmation_count,
+                    ),
+                })
+                .collect(),
+            root_slot: record.root_slot,
+            authorized_voters: record
+                .authorized_voters
+                .iter()
+                .map(|(epoch, authorized_voter)| {
+                    (*epoch, Pubkey::new_from_array(*authorized_voter))
+                })
+                .collect(),
+            prior_voters: PriorVotersRing {
+                buf,
+                idx: record.prior_voters_idx as usize,
+                is_empty: record.prior_voters_is_empty,
+            },
+            epoch_credits: record.epoch_credits.clone(),
+            last_timestamp: BlockTimestamp {
+                slot: record.last_timestamp_slot,
+                timestamp: record.last_timestamp,
+            },
+        };
+        let data = wire::serialize(
+            &(VoteStateVersions::CURRENT_TAG, fields),
+            VoteState::size_of() as u64,
+        )
+        .map_err(|_| ArchiveError::InvalidVoteState)?;
+        sanitize_vote_account_data(&data)
+            .map(|vote_state| vote_state.into_inner())
+            .map_err(ArchiveError::Sanitize)
+    }
+}
+
+impl From<&VoteStateUpdate> for VoteStateUpdateRecord {
+    fn from(vote_state_update: &VoteStateUpdate) -> Self {
+        Self {
+            lockouts: vote_state_update
+                .lockouts
+                .iter()
+                .map(|lockout| LockoutRecord {
+                    slot: lockout.slot(),
+                    confirmation_count: lockout.confirmation_count(),
+                    latency: 0,
+                })
+                .collect(),
+            root: vote_state_update.root,
+            hash: vote_state_update.hash.to_bytes(),
+            timestamp: vote_state_update.timestamp,
+        }
+    }
+}
+
+impl From<&VoteStateUpdateRecord> for VoteStateUpdate {
+    fn from(record: &VoteStateUpdateRecord) -> Self {
+        Self {
+            lockouts: record
+                .lockouts
+                .iter()
+                .map(|lockout| {
+                    Lockout::new_with_confirmation_count(lockout.slot, lockout.confirmation_count)
+                })
+                .collect(),
+            root: record.root,
+            hash: Hash::new_from_array(record.hash),
+            timestamp: record.timestamp,
+        }
+    }
+}
+
+/// Archives `vote_state`, as recorded by [`VoteStateRecord::new`].
+pub fn archive_vote_state(vote_state: &mut VoteState) -> Result<AlignedVec, ArchiveError> {
+    rkyv::to_bytes::<_, SCRATCH_BYTES>(&VoteStateRecord::new(vote_state))
+        .map_err(|_| ArchiveError::Serialize)
+}
+
+/// Validates `bytes` and returns the archived vote state they hold, without
+/// copying.
+pub fn access_vote_state(bytes: &[u8]) -> Result<&ArchivedVoteStateRecord, ArchiveError> {
+    rkyv::check_archived_root::<VoteStateRecord>(bytes).map_err(|_| ArchiveError::Invalid)
+}
+
+/// Validates `bytes` and restores the vote state they hold.
+pub fn restore_vote_state(bytes: &[u8]) -> Result<VoteState, ArchiveError> {
+    let record: VoteStateRecord = access_vote_state(bytes)?
+        .deserialize(&mut Infallible)
+        .map_err(|_| ArchiveError::Invalid)?;
+    VoteState::try_from(&record)
+}
+
+/// Archives `vote_state_update`.
+pub fn archive_vote_state_update(
+    vote_state_update: &VoteStateUpdate,
+) -> Result<AlignedVec, ArchiveError> {
+    rkyv::to_bytes::<_, SCRATCH_BYTES>(&VoteStateUpdateRecord::from(vote_state_update))
+        .map_err(|_| ArchiveError::Serialize)
+}
+
+/// Validates `bytes` and returns the archived vote state update they hold,
+/// without copying.
+pub fn access_vote_state_update(
+    bytes: &[u8],
+) -> Result<&ArchivedVoteStateUpdateRecord, ArchiveError> {
+    rkyv::check_archived_root::<VoteStateUpdateRecord>(bytes).map_err(|_| ArchiveError::Invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{initialized_vote_state, populated_vote_state},
+        solana_program::vote::state::MAX_LOCKOUT_HISTORY,
+    };
+
+    fn restore_record(record: &VoteStateRecord) -> Result<VoteState, ArchiveError> {
+        let bytes = rkyv::to_bytes::<_, SCRATCH_BYTES>(record).unwrap();
+        restore_vote_state(&bytes)
+    }
+
+    fn populated_record() -> VoteStateRecord {
+        VoteStateRecord::new(&mut populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 40))
+    }
+
+    #[test]
+    fn test_vote_state_round_trip() {
+        for mut vote_state in [
+            initialized_vote_state(),
+            populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 40),
+        ] {
+            let bytes = archive_vote_state(&mut vote_state).unwrap();
+            assert!(access_vote_state(&bytes).is_ok());
+            assert_eq!(restore_vote_state(&bytes).unwrap(), vote_state);
+        }
+    }
+
+    #[test]
+    fn test_vote_state_update_round_trip() {
+        let vote_state_update = VoteStateUpdate {
+            lockouts: [(10, 3), (11, 2), (12, 1)]
+                .into_iter()
+                .map(|(slot, confirmation_count)| {
+                    Lockout::new_with_confirmation_count(slot, confirmation_count)
+                })
+                .collect(),
+            root: Some(9),
+            hash: Hash::new_unique(),
+            timestamp: Some(1_700_000_000),
+        };
+        let bytes = archive_vote_state_update(&vote_state_update).unwrap();
+        let record = access_vote_state_update(&bytes).unwrap();
+        let record: VoteStateUpdateRecord = record.deserialize(&mut Infallible).unwrap();
+        assert_eq!(VoteStateUpdate::from(&record), vote_state_update);
+    }
+
+    #[test]
+    fn test_restore_rejects_prior_voters_index() {
+        let mut record = populated_record();
+        record.prior_voters_idx = PRIOR_VOTERS_LEN as u64;
+        assert_eq!(
+            restore_record(&record),
+            Err(ArchiveError::Sanitize(SanitizeError::PriorVotersIndex(
+                PRIOR_VOTERS_LEN as u64
+            )))
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_too_many_votes() {
+        let mut record = populated_record();
+        let vote = record.votes[0];
+        record.votes.push(vote);
+        assert_eq!(
+            restore_record(&record),
+            Err(ArchiveError::Sanitize(SanitizeError::TooManyVotes(
+                MAX_LOCKOUT_HISTORY as u64 + 1
+            )))
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_confirmation_count() {
+        let mut record = populated_record();
+        record.votes[0].confirmation_count = MAX_LOCKOUT_HISTORY as u32 + 1;
+        assert_eq!(
+            restore_record(&record),
+            Err(ArchiveError::Sanitize(SanitizeError::ConfirmationTooLarge(
+                MAX_LOCKOUT_HISTORY as u32 + 1
+            )))
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_no_authorized_voters() {
+        let mut record = populated_record();
+        record.authorized_voters.clear();
+        assert_eq!(
+            restore_record(&record),
+            Err(ArchiveError::Sanitize(SanitizeError::Uninitialized))
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_short_prior_voters() {
+        let mut record = populated_record();
+        record.prior_voters.pop();
+        assert_eq!(restore_record(&record), Err(ArchiveError::InvalidVoteState));
+    }
+}
@@ -0,0 +1,70 @@
+//! The authorities a vote account delegates, and how each one changes.
+//!
+//! Handlers match on [`AuthorityKind`] rather than `VoteAuthorize`, so a
+//! new kind of authority is added here and in the instruction metadata
+//! instead of in every handler.
+
+use solana_program::{
+    clock::Clock,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    vote::state::{VoteAuthorize, VoteState},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AuthorityKind {
+    Voter,
+    Withdrawer,
+}
+
+impl From<VoteAuthorize> for AuthorityKind {
+    fn from(vote_authorize: VoteAuthorize) -> Self {
+        match vote_authorize {
+            VoteAuthorize::Voter => Self::Voter,
+            VoteAuthorize::Withdrawer => Self::Withdrawer,
+        }
+    }
+}
+
+impl AuthorityKind {
+    /// Sets `new_authority` as the program does at `clock`.
+    ///
+    /// `verify_signer` is asked about the keys that may approve the change:
+    /// the withdrawer, or for the voter the withdrawer and then the current
+    /// epoch's voter. A new voter takes effect after the leader schedule
+    /// epoch; a new withdrawer immediately.
+    pub(crate) fn set(
+        self,
+        vote_state: &mut VoteState,
+        new_authority: &Pubkey,
+        clock: &Clock,
+        verify_signer: impl Fn(&Pubkey) -> Result<(), InstructionError>,
+    ) -> Result<(), InstructionError> {
+        match self {
+            Self::Voter => {
+                let withdrawer_signed = verify_signer(&vote_state.authorized_withdrawer).is_ok();
+                let target_epoch = clock
+                    .leader_schedule_epoch
+                    .checked_add(1)
+                    .ok_or(InstructionError::InvalidAccountData)?;
+                vote_state.set_new_authorized_voter(
+                    new_authority,
+                    clock.epoch,
+                    target_epoch,
+                    |epoch_authorized_voter| {
+                        if withdrawer_signed {
+                            Ok(())
+                        } else {
+                            verify_signer(&epoch_authorized_voter)
+                        }
+                    },
+                )
+            }
+            Self::Withdrawer => {
+                verify_signer(&vote_state.authorized_withdrawer)?;
+                vote_state.authorized_withdrawer = *new_authority;
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Authorized voter lookups.
+
+use solana_program::{
+    clock::Epoch,
+    pubkey::Pubkey,
+    vote::{authorized_voters::AuthorizedVoters, state::VoteState},
+};
+
+/// Epoch-aware lookup of the key allowed to sign votes.
+pub trait AuthorizedVoterLookup {
+    /// Returns the key that may sign votes in `epoch`.
+    ///
+    /// An epoch without its own entry inherits the latest voter set before
+    /// it. Epochs older than the retained entries are answered from the
+    /// prior voters history when it still covers them.
+    fn authorized_voter_for(&self, epoch: Epoch) -> Option<Pubkey>;
+}
+
+impl AuthorizedVoterLookup for VoteState {
+    fn authorized_voter_for(&self, epoch: Epoch) -> Option<Pubkey> {
+        self.get_authorized_voter(epoch).or_else(|| {
+            // `prior_voters` is only reachable through a mutable borrow.
+            self.clone()
+                .prior_voters()
+                .buf()
+                .iter()
+                .find(|(_, start_epoch, end_epoch)| (*start_epoch..*end_epoch).contains(&epoch))
+                .map(|(pubkey, _, _)| *pubkey)
+        })
+    }
+}
+
+/// The authorized voters as they will be after the program first touches the
+/// account in a later epoch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthorizedVotersRollover {
+    /// The key allowed to sign votes in the rollover epoch.
+    pub authorized_voter: Pubkey,
+    /// The remaining entries, with the rollover epoch's voter cached.
+    pub authorized_voters: AuthorizedVoters,
+    /// Entries purged because they precede the rollover epoch.
+    pub evicted: Vec<(Epoch, Pubkey)>,
+}
+
+/// Previews the purge the program performs when a vote account is first
+/// used in `epoch`, without modifying `authorized_voters`.
+///
+/// Returns `None` if no voter is authorized for `epoch`, in which case the
+/// program rejects the instruction instead.
+pub fn preview_rollover(
+    authorized_voters: &AuthorizedVoters,
+    epoch: Epoch,
+) -> Option<AuthorizedVotersRollover> {
+    let mut authorized_voters = authorized_voters.clone();
+    let authorized_voter = authorized_voters.get_and_cache_authorized_voter_for_epoch(epoch)?;
+    let evicted = authorized_voters
+        .iter()
+        .take_while(|(authorized_epoch, _)| **authorized_epoch < epoch)
+        .map(|(authorized_epoch, pubkey)| (*authorized_epoch, *pubkey))
+        .collect();
+    authorized_voters.purge_authorized_voters(epoch);
+    Some(AuthorizedVotersRollover {
+        authorized_voter,
+        authorized_voters,
+        evicted,
+    })
+}
@@ -0,0 +1,269 @@
+//! Standalone byte codec for the compact vote state update encoding.
+//!
+//! This is the wire format of `CompactUpdateVoteState` payloads, without the
+//! instruction tag. `TowerSync` is not available in the solana-program
+//! version this crate builds against.
+
+use {
+    crate::varint::{self, VarintError},
+    solana_program::{
+        clock::{Slot, UnixTimestamp},
+        hash::{Hash, HASH_BYTES},
+        vote::state::{Lockout, VoteStateUpdate, MAX_LOCKOUT_HISTORY},
+    },
+    std::collections::VecDeque,
+    thiserror::Error,
+};
+
+/// Reasons compact encoding or decoding fails.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum CodecError {
+    #[error("data truncated at byte {0}")]
+    Truncated(usize),
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("invalid short_vec length")]
+    InvalidLength,
+    #[error("invalid varint")]
+    InvalidVarint,
+    #[error("invalid option tag {0}")]
+    InvalidOption(u8),
+    #[error("{0} lockouts, at most {MAX_LOCKOUT_HISTORY} allowed")]
+    TooManyLockouts(usize),
+    #[error("lockout offset overflows the slot range")]
+    InvalidLockoutOffset,
+    #[error("lockout slots are not in increasing order")]
+    SlotsNotOrdered,
+    #[error("confirmation count {0} does not fit in a byte")]
+    ConfirmationCountTooLarge(u32),
+}
+
+/// Encodes `vote_state_update` in the compact format.
+pub fn encode_compact_vote_state_update(
+    vote_state_update: &VoteStateUpdate,
+) -> Result<Vec<u8>, CodecError> {
+    let lockouts = &vote_state_update.lockouts;
+    if lockouts.len() > MAX_LOCKOUT_HISTORY {
+        return Err(CodecError::TooManyLockouts(lockouts.len()));
+    }
+    let mut data = Vec::with_capacity(8 + 1 + lockouts.len() * 11 + HASH_BYTES + 9);
+    data.extend_from_slice(&vote_state_update.root.unwrap_or(Slot::MAX).to_le_bytes());
+    varint::encode_short_u16_len(lockouts.len(), &mut data);
+    let mut slot = vote_state_update.root.unwrap_or_default();
+    for lockout in lockouts {
+        let offset = lockout
+            .slot()
+            .checked_sub(slot)
+            .ok_or(CodecError::SlotsNotOrdered)?;
+        let confirmation_count = u8::try_from(lockout.confirmation_count())
+            .map_err(|_| CodecError::ConfirmationCountTooLarge(lockout.confirmation_count()))?;
+        varint::encode_u64(offset, &mut data);
+        data.push(confirmation_count);
+        slot = lockout.slot();
+    }
+    data.extend_from_slice(vote_state_update.hash.as_ref());
+    match vote_state_update.timestamp {
+        Some(timestamp) => {
+            data.push(1);
+            data.extend_from_slice(&timestamp.to_le_bytes());
+        }
+        None => data.push(0),
+    }
+    Ok(data)
+}
+
+/// Decodes a compact vote state update occupying all of `data`.
+pub fn decode_compact_vote_state_update(data: &[u8]) -> Result<VoteStateUpdate, CodecError> {
+    let mut cursor = Cursor { data, offset: 0 };
+    let root = Slot::from_le_bytes(cursor.take_array()?);
+    let root = (root != Slot::MAX).then_some(root);
+
+    let len = cursor.read_short_u16_len(MAX_LOCKOUT_HISTORY)?;
+    let mut lockouts = VecDeque::with_capacity(len);
+    let mut slot = root.unwrap_or_default();
+    for _ in 0..len {
+        slot = slot
+            .checked_add(cursor.read_varint_u64()?)
+            .ok_or(CodecError::InvalidLockoutOffset)?;
+        let [confirmation_count] = cursor.take_array()?;
+        lockouts.push_back(Lockout::new_with_confirmation_count(
+            slot,
+            u32::from(confirmation_count),
+        ));
+    }
+
+    let hash = Hash::new_from_array(cursor.take_array()?);
+    let timestamp = match cursor.take_array()? {
+        [0] => None,
+        [1] => Some(UnixTimestamp::from_le_bytes(cursor.take_array()?)),
+        [tag] => return Err(CodecError::InvalidOption(tag)),
+    };
+    let trailing = data.len() - cursor.offset;
+    if trailing != 0 {
+        return Err(CodecError::TrailingBytes(trailing));
+    }
+    Ok(VoteStateUpdate {
+        lockouts,
+        root,
+        hash,
+        timestamp,
+    })
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Cursor<'_> {
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], CodecError> {
+        let bytes = self
+            .data
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..N))
+            .ok_or(CodecError::Truncated(self.offset))?;
+        self.offset += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn read_short_u16_len(&mut self, max: usize) -> Result<usize, CodecError> {
+        let rest = self.data.get(self.offset..).unwrap_or_default();
+        let (len, bytes) = varint::decode_short_u16_len(rest, max).map_err(|err| match err {
+            VarintError::Truncated => CodecError::Truncated(self.offset),
+            VarintError::Invalid => CodecError::InvalidLength,
+            VarintError::LengthExceeded(len) => CodecError::TooManyLockouts(len),
+        })?;
+        self.offset += bytes;
+        Ok(len)
+    }
+
+    fn read_varint_u64(&mut self) -> Result<u64, CodecError> {
+        let rest = self.data.get(self.offset..).unwrap_or_default();
+        let (value, bytes) = varint::decode_u64(rest).map_err(|err| match err {
+            VarintError::Truncated => CodecError::Truncated(self.offset),
+            VarintError::Invalid | VarintError::LengthExceeded(_) => CodecError::InvalidVarint,
+        })?;
+        self.offset += bytes;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_program::{hash::hash, vote::instruction::VoteInstruction},
+    };
+
+    fn update(
+        lockouts: &[(Slot, u32)],
+        root: Option<Slot>,
+        timestamp: Option<UnixTimestamp>,
+    ) -> VoteStateUpdate {
+        let mut vote_state_update = VoteStateUpdate::from(lockouts.to_vec());
+        vote_state_update.root = root;
+        vote_state_update.hash = hash(b"bank");
+        vote_state_update.timestamp = timestamp;
+        vote_state_update
+    }
+
+    fn cases() -> Vec<VoteStateUpdate> {
+        vec![
+            update(&[], Some(5), None),
+            update(&[], None, None),
+            update(&[(3, 2), (4, 1)], None, None),
+            update(&[(10, 3), (11, 2), (12, 1)], Some(9), None),
+            // Offsets of one, two, three and nine varint bytes.
+            update(
+                &[
+                    (100, 5),
+                    (300, 4),
+                    (70_000, 3),
+                    (Slot::MAX / 2, 2),
+                    (Slot::MAX - 1, 1),
+                ],
+                Some(1),
+                None,
+            ),
+            update(&[(7, 1)], Some(6), Some(1_700_000_000)),
+            update(&[(7, 1)], None, Some(-1)),
+        ]
+    }
+
+    /// The SDK's encoding, without the instruction tag.
+    fn sdk_encode(vote_state_update: &VoteStateUpdate) -> Vec<u8> {
+        let instruction = VoteInstruction::CompactUpdateVoteState(vote_state_update.clone());
+        bincode::serialize(&instruction).unwrap()[4..].to_vec()
+    }
+
+    #[test]
+    fn test_matches_sdk_encoding() {
+        for vote_state_update in cases() {
+            let data = encode_compact_vote_state_update(&vote_state_update).unwrap();
+            assert_eq!(
+                data,
+                sdk_encode(&vote_state_update),
+                "{vote_state_update:?}"
+            );
+            assert_eq!(
+                decode_compact_vote_state_update(&data).unwrap(),
+                vote_state_update
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_root_sentinel() {
+        let data = encode_compact_vote_state_update(&update(&[(3, 1)], None, None)).unwrap();
+        assert_eq!(data[..8], Slot::MAX.to_le_bytes());
+        // The first offset counts from slot 0.
+        assert_eq!(data[9], 3);
+    }
+
+    #[test]
+    fn test_rejects_trailing_bytes() {
+        for vote_state_update in cases() {
+            let mut data = encode_compact_vote_state_update(&vote_state_update).unwrap();
+            data.push(0);
+            assert_eq!(
+                decode_compact_vote_state_update(&data),
+                Err(CodecError::TrailingBytes(1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_truncated() {
+        for vote_state_update in cases() {
+            let data = encode_compact_vote_state_update(&vote_state_update).unwrap();
+            for len in 0..data.len() {
+                assert!(
+                    matches!(
+                        decode_compact_vote_state_update(&data[..len]),
+                        Err(CodecError::Truncated(offset)) if offset <= len
+                    ),
+                    "{vote_state_update:?} truncated to {len} bytes"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        assert_eq!(
+            encode_compact_vote_state_update(&update(&[(3, 1)], Some(5), None)),
+            Err(CodecError::SlotsNotOrdered)
+        );
+        assert_eq!(
+            encode_compact_vote_state_update(&update(&[(3, 256)], None, None)),
+            Err(CodecError::ConfirmationCountTooLarge(256))
+        );
+        let lockouts: Vec<_> = (1..=MAX_LOCKOUT_HISTORY as u64 + 1)
+            .map(|slot| (slot, 1))
+            .collect();
+        assert_eq!(
+            encode_compact_vote_state_update(&update(&lockouts, None, None)),
+            Err(CodecError::TooManyLockouts(MAX_LOCKOUT_HISTORY + 1))
+        );
+    }
+}
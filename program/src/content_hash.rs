@@ -0,0 +1,173 @@
+//! Stable content hashes of vote payloads.
+//!
+//! Each payload type hashes under its own domain prefix, so a `Vote` and a
+//! `VoteStateUpdate` never collide. `TowerSync` is not available in the
+//! solana-program version this crate builds against.
+
+use solana_program::{
+    clock::UnixTimestamp,
+    hash::{Hash, Hasher},
+    vote::state::{Vote, VoteStateUpdate},
+};
+
+const VOTE_DOMAIN: &[u8] = b"solana-vote-program:vote";
+const VOTE_STATE_UPDATE_DOMAIN: &[u8] = b"solana-vote-program:vote-state-update";
+
+/// Hash identifying a vote payload by content.
+pub trait ContentHash {
+    /// Returns a hash that is equal for equal payloads and independent of
+    /// the instruction or encoding the payload arrived in.
+    fn content_hash(&self) -> Hash;
+}
+
+impl ContentHash for Vote {
+    fn content_hash(&self) -> Hash {
+        let mut hasher = Hasher::default();
+        hasher.hash(VOTE_DOMAIN);
+        hasher.hash(&(self.slots.len() as u64).to_le_bytes());
+        for slot in &self.slots {
+            hasher.hash(&slot.to_le_bytes());
+        }
+        hasher.hash(self.hash.as_ref());
+        hash_timestamp(&mut hasher, self.timestamp);
+        hasher.result()
+    }
+}
+
+impl ContentHash for VoteStateUpdate {
+    fn content_hash(&self) -> Hash {
+        let mut hasher = Hasher::default();
+        hasher.hash(VOTE_STATE_UPDATE_DOMAIN);
+        hasher.hash(&(self.lockouts.len() as u64).to_le_bytes());
+        for lockout in &self.lockouts {
+            hasher.hash(&lockout.slot().to_le_bytes());
+            hasher.hash(&lockout.confirmation_count().to_le_bytes());
+        }
+        match self.root {
+            Some(root) => {
+                hasher.hash(&[1]);
+                hasher.hash(&root.to_le_bytes());
+            }
+            None => hasher.hash(&[0]),
+        }
+        hasher.hash(self.hash.as_ref());
+        hash_timestamp(&mut hasher, self.timestamp);
+        hasher.result()
+    }
+}
+
+fn hash_timestamp(hasher: &mut Hasher, timestamp: Option<UnixTimestamp>) {
+    match timestamp {
+        Some(timestamp) => {
+            hasher.hash(&[1]);
+            hasher.hash(&timestamp.to_le_bytes());
+        }
+        None => hasher.hash(&[0]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test_support::fixture_bank_hash, std::collections::HashSet};
+
+    fn vote() -> Vote {
+        Vote {
+            slots: vec![3, 4],
+            hash: fixture_bank_hash(4),
+            timestamp: Some(1_700_000_000),
+        }
+    }
+
+    fn update() -> VoteStateUpdate {
+        VoteStateUpdate {
+            root: Some(1),
+            hash: fixture_bank_hash(4),
+            timestamp: Some(1_700_000_000),
+            ..VoteStateUpdate::from(vec![(3, 2), (4, 1)])
+        }
+    }
+
+    #[test]
+    fn test_equal_payloads_hash_equal() {
+        assert_eq!(vote().content_hash(), vote().content_hash());
+        assert_eq!(update().content_hash(), update().content_hash());
+    }
+
+    #[test]
+    fn test_vote_fields_change_hash() {
+        let variants = [
+            vote(),
+            Vote {
+                slots: vec![3, 5],
+                ..vote()
+            },
+            Vote {
+                slots: vec![3],
+                ..vote()
+            },
+            Vote {
+                hash: fixture_bank_hash(5),
+                ..vote()
+            },
+            Vote {
+                timestamp: None,
+                ..vote()
+            },
+            Vote {
+                timestamp: Some(0),
+                ..vote()
+            },
+        ];
+        let hashes: HashSet<_> = variants.iter().map(ContentHash::content_hash).collect();
+        assert_eq!(hashes.len(), variants.len());
+    }
+
+    #[test]
+    fn test_update_fields_change_hash() {
+        let variants = [
+            update(),
+            VoteStateUpdate {
+                root: None,
+                ..update()
+            },
+            VoteStateUpdate {
+                root: Some(0),
+                ..update()
+            },
+            VoteStateUpdate {
+                lockouts: VoteStateUpdate::from(vec![(3, 3), (4, 1)]).lockouts,
+                ..update()
+            },
+            VoteStateUpdate {
+                lockouts: VoteStateUpdate::from(vec![(4, 1)]).lockouts,
+                ..update()
+            },
+            VoteStateUpdate {
+                hash: fixture_bank_hash(5),
+                ..update()
+            },
+            VoteStateUpdate {
+                timestamp: None,
+                ..update()
+            },
+        ];
+        let hashes: HashSet<_> = variants.iter().map(ContentHash::content_hash).collect();
+        assert_eq!(hashes.len(), variants.len());
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let vote = Vote {
+            slots: vec![],
+            hash: Hash::default(),
+            timestamp: None,
+        };
+        let update = VoteStateUpdate {
+            root: None,
+            hash: Hash::default(),
+            timestamp: None,
+            ..VoteStateUpdate::from(vec![])
+        };
+        assert_ne!(vote.content_hash(), update.content_hash());
+    }
+}
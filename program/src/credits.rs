@@ -0,0 +1,264 @@
+//! Vote credit economics and epoch credits history checks.
+
+use {
+    serde::{Deserialize, Serialize},
+    solana_program::{
+        clock::Epoch,
+        vote::state::{VoteState, VOTE_CREDITS_GRACE_SLOTS, VOTE_CREDITS_MAXIMUM_PER_SLOT},
+    },
+};
+
+/// One epoch credits history entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EpochCredit {
+    pub epoch: Epoch,
+    /// Total credits at the end of the epoch, or now for the current epoch.
+    pub credits: u64,
+    /// Total credits when the epoch began.
+    pub prev_credits: u64,
+}
+
+impl EpochCredit {
+    /// Credits earned during the epoch.
+    pub fn earned(&self) -> u64 {
+        self.credits.saturating_sub(self.prev_credits)
+    }
+}
+
+impl From<(Epoch, u64, u64)> for EpochCredit {
+    fn from((epoch, credits, prev_credits): (Epoch, u64, u64)) -> Self {
+        Self {
+            epoch,
+            credits,
+            prev_credits,
+        }
+    }
+}
+
+impl From<EpochCredit> for (Epoch, u64, u64) {
+    fn from(epoch_credit: EpochCredit) -> Self {
+        (
+            epoch_credit.epoch,
+            epoch_credit.credits,
+            epoch_credit.prev_credits,
+        )
+    }
+}
+
+/// The epoch credits history of `vote_state`, oldest first.
+pub fn epoch_credit_history(vote_state: &VoteState) -> Vec<EpochCredit> {
+    vote_state
+        .epoch_credits()
+        .iter()
+        .copied()
+        .map(EpochCredit::from)
+        .collect()
+}
+
+/// Credits a vote earns when it roots, given the slots between the voted
+/// slot and the slot the vote landed in.
+///
+/// Follows the current cluster rules (timely vote credits active). A
+/// latency of zero marks a vote recorded before latencies were tracked and
+/// earns one credit.
+pub fn credits_for_latency(latency: u8) -> u64 {
+    if latency == 0 {
+        return 1;
+    }
+    let late_by = latency.saturating_sub(VOTE_CREDITS_GRACE_SLOTS);
+    u64::from(VOTE_CREDITS_MAXIMUM_PER_SLOT.saturating_sub(late_by).max(1))
+}
+
+/// Credits given up by landing at `latency` rather than within the grace
+/// period.
+pub fn credits_lost_to_latency(latency: u8) -> u64 {
+    u64::from(VOTE_CREDITS_MAXIMUM_PER_SLOT).saturating_sub(credits_for_latency(latency))
+}
+
+/// Credits the votes currently in the tower will earn as they root.
+pub fn expected_tower_credits(vote_state: &VoteState) -> u64 {
+    vote_state
+        .votes
+        .iter()
+        .map(|landed_vote| credits_for_latency(landed_vote.latency))
+        .sum()
+}
+
+/// Most credits the votes in a tower of the same depth could earn.
+pub fn max_tower_credits(vote_state: &VoteState) -> u64 {
+    (vote_state.votes.len() as u64).saturating_mul(u64::from(VOTE_CREDITS_MAXIMUM_PER_SLOT))
+}
+
+/// Something unexpected in an epoch credits history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreditAnomaly {
+    /// No credits were earned in epochs `first_epoch..=last_epoch`; the
+    /// validator was likely delinquent throughout.
+    ///
+    /// The runtime keeps no entry for a past epoch that earned nothing, so
+    /// these are the epochs missing between two entries, plus the newest
+    /// entry while it has earned nothing.
+    ZeroCredits {
+        first_epoch: Epoch,
+        last_epoch: Epoch,
+    },
+    /// Credits at the end of the epoch are below credits at its start.
+    Regressed {
+        epoch: Epoch,
+        prev_credits: u64,
+        credits: u64,
+    },
+    /// The entry does not continue from the previous entry's credits.
+    Discontinuity {
+        epoch: Epoch,
+        expected_prev_credits: u64,
+        prev_credits: u64,
+    },
+    /// The entry's epoch is not after the previous entry's.
+    EpochNotIncreasing { epoch: Epoch, previous_epoch: Epoch },
+}
+
+/// Flags anomalies in `(epoch, credits, prev_credits)` entries, oldest
+/// first, in the order they appear.
+pub fn detect_credit_anomalies(epoch_credits: &[(Epoch, u64, u64)]) -> Vec<CreditAnomaly> {
+    let mut anomalies = vec![];
+    let mut previous: Option<&(Epoch, u64, u64)> = None;
+    for entry @ &(epoch, credits, prev_credits) in epoch_credits {
+        if let Some(&(previous_epoch, previous_credits, _)) = previous {
+            if epoch <= previous_epoch {
+                anomalies.push(CreditAnomaly::EpochNotIncreasing {
+                    epoch,
+                    previous_epoch,
+                });
+            } else if epoch > previous_epoch.saturating_add(1) {
+                anomalies.push(CreditAnomaly::ZeroCredits {
+                    first_epoch: previous_epoch.saturating_add(1),
+                    last_epoch: epoch.saturating_sub(1),
+                });
+            }
+            if prev_credits != previous_credits {
+                anomalies.push(CreditAnomaly::Discontinuity {
+                    epoch,
+                    expected_prev_credits: previous_credits,
+                    prev_credits,
+                });
+            }
+        }
+        if credits < prev_credits {
+            anomalies.push(CreditAnomaly::Regressed {
+                epoch,
+                prev_credits,
+                credits,
+            });
+        }
+        previous = Some(entry);
+    }
+    if let Some(&(epoch, credits, prev_credits)) = previous {
+        if credits == prev_credits {
+            match anomalies.last_mut() {
+                // Extend the missing epochs right before the newest entry.
+                Some(CreditAnomaly::ZeroCredits { last_epoch, .. })
+                    if last_epoch.saturating_add(1) == epoch =>
+                {
+                    *last_epoch = epoch;
+                }
+                _ => anomalies.push(CreditAnomaly::ZeroCredits {
+                    first_epoch: epoch,
+                    last_epoch: epoch,
+                }),
+            }
+        }
+    }
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Credits `credits[n]` in epoch `n`, skipping epochs that earn
+    /// nothing, as the runtime does.
+    fn history(credits: &[u64]) -> Vec<(Epoch, u64, u64)> {
+        let mut vote_state = VoteState::default();
+        for (epoch, &credits) in (0..).zip(credits) {
+            if credits > 0 {
+                vote_state.increment_credits(epoch, credits);
+            }
+        }
+        vote_state.epoch_credits().clone()
+    }
+
+    #[test]
+    fn test_voting_every_epoch() {
+        assert_eq!(detect_credit_anomalies(&history(&[10, 20, 30])), []);
+    }
+
+    #[test]
+    fn test_missing_epochs() {
+        let epoch_credits = history(&[10, 0, 0, 20, 0, 5]);
+        assert_eq!(epoch_credits, [(0, 10, 0), (3, 30, 10), (5, 35, 30)]);
+        assert_eq!(
+            detect_credit_anomalies(&epoch_credits),
+            [
+                CreditAnomaly::ZeroCredits {
+                    first_epoch: 1,
+                    last_epoch: 2,
+                },
+                CreditAnomaly::ZeroCredits {
+                    first_epoch: 4,
+                    last_epoch: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_newest_epoch_without_credits() {
+        let mut vote_state = VoteState::default();
+        vote_state.increment_credits(0, 10);
+        vote_state.increment_credits(1, 0);
+        assert_eq!(
+            detect_credit_anomalies(vote_state.epoch_credits()),
+            [CreditAnomaly::ZeroCredits {
+                first_epoch: 1,
+                last_epoch: 1,
+            }]
+        );
+
+        // The runtime moves the empty entry forward rather than keeping it.
+        vote_state.increment_credits(3, 0);
+        assert_eq!(vote_state.epoch_credits(), &[(0, 10, 0), (3, 10, 10)]);
+        assert_eq!(
+            detect_credit_anomalies(vote_state.epoch_credits()),
+            [CreditAnomaly::ZeroCredits {
+                first_epoch: 1,
+                last_epoch: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_corrupt_history() {
+        assert_eq!(
+            detect_credit_anomalies(&[(2, 10, 0), (2, 15, 10), (3, 12, 20)]),
+            [
+                CreditAnomaly::EpochNotIncreasing {
+                    epoch: 2,
+                    previous_epoch: 2,
+                },
+                CreditAnomaly::Discontinuity {
+                    epoch: 3,
+                    expected_prev_credits: 15,
+                    prev_credits: 20,
+                },
+                CreditAnomaly::Regressed {
+                    epoch: 3,
+                    prev_credits: 20,
+                    credits: 12,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Batch decoding of vote account data.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use {
+    crate::versioned::decode_current,
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        vote::state::{VoteState, VoteStateVersions},
+    },
+};
+
+/// Decodes one vote account's data into the current `VoteState`, rejecting
+/// uninitialized accounts.
+pub fn decode_vote_account(data: &[u8]) -> Result<VoteState, InstructionError> {
+    decode_current::<VoteStateVersions>(data)
+        .map_err(|_| InstructionError::InvalidAccountData)?
+        .ok_or(InstructionError::UninitializedAccount)
+}
+
+/// Decodes many vote accounts, such as a `getProgramAccounts` response,
+/// keeping results in input order.
+pub fn decode_vote_accounts<'a>(
+    accounts: impl Iterator<Item = (&'a Pubkey, &'a [u8])>,
+) -> Vec<Result<(Pubkey, VoteState), InstructionError>> {
+    accounts
+        .map(|(pubkey, data)| decode_vote_account(data).map(|vote_state| (*pubkey, vote_state)))
+        .collect()
+}
+
+/// Like [`decode_vote_accounts`], decoding on the rayon thread pool.
+#[cfg(feature = "rayon")]
+pub fn par_decode_vote_accounts<'a>(
+    accounts: &[(&'a Pubkey, &'a [u8])],
+) -> Vec<Result<(Pubkey, VoteState), InstructionError>> {
+    accounts
+        .par_iter()
+        .map(|(pubkey, data)| decode_vote_account(data).map(|vote_state| (**pubkey, vote_state)))
+        .collect()
+}
@@ -0,0 +1,398 @@
+//! Incremental vote state maintenance from landed vote transactions.
+//!
+//! These functions replay a vote payload that has already been accepted by
+//! the cluster, so they skip signer verification and the `SlotHashes` checks
+//! the program performs. Vote credits follow the current cluster rules
+//! (timely vote credits active).
+
+use {
+    solana_program::{
+        clock::{Epoch, Slot},
+        instruction::InstructionError,
+        vote::{
+            error::VoteError,
+            state::{
+                BlockTimestamp, LandedVote, Vote, VoteState, VoteStateUpdate, MAX_LOCKOUT_HISTORY,
+            },
+        },
+    },
+    std::{cmp::Ordering, collections::VecDeque},
+};
+
+pub(crate) const TIMELY_VOTE_CREDITS: bool = true;
+pub(crate) const DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING: bool = true;
+
+/// Summary of what a single vote payload changed in a vote state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VoteStateDelta {
+    /// Slots that became rooted, oldest first.
+    pub rooted_slots: Vec<Slot>,
+    /// Credits awarded for the rooted slots.
+    pub credits_earned: u64,
+    /// The root after the update, if it changed.
+    pub new_root: Option<Slot>,
+    /// The last voted slot after the update.
+    pub last_voted_slot: Option<Slot>,
+    /// The timestamp recorded by the update, if any.
+    pub timestamp: Option<BlockTimestamp>,
+}
+
+impl VoteStateDelta {
+    fn new(pre_credits: u64, pre_root: Option<Slot>, vote_state: &VoteState) -> Self {
+        Self {
+            credits_earned: vote_state.credits().saturating_sub(pre_credits),
+            new_root: vote_state
+                .root_slot
+                .filter(|_| vote_state.root_slot != pre_root),
+            last_voted_slot: vote_state.last_voted_slot(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Applies a legacy `Vote` that landed in `current_slot` during `epoch`.
+///
+/// On error `vote_state` is left unchanged.
+pub fn apply_vote(
+    vote_state: &mut VoteState,
+    vote: &Vote,
+    epoch: Epoch,
+    current_slot: Slot,
+) -> Result<VoteStateDelta, InstructionError> {
+    if vote.slots.is_empty() {
+        return Err(VoteError::EmptySlots.into());
+    }
+    check_authorized_voter(vote_state, epoch)?;
+    // The timestamp does not depend on the tower, so it is checked before
+    // anything else changes.
+    let mut timestamp = None;
+    if let Some(unix_timestamp) = vote.timestamp {
+        let slot = vote.slots.iter().max().copied().unwrap_or_default();
+        vote_state.process_timestamp(slot, unix_timestamp)?;
+        timestamp = Some(vote_state.last_timestamp.clone());
+    }
+    vote_state.get_and_update_authorized_voter(epoch)?;
+
+    let pre_credits = vote_state.credits();
+    let pre_root = vote_state.root_slot;
+    let mut rooted_slots = vec![];
+    for slot in &vote.slots {
+        let root_slot = vote_state.root_slot;
+        vote_state.process_next_vote_slot(
+            *slot,
+            epoch,
+            current_slot,
+            TIMELY_VOTE_CREDITS,
+            DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+        );
+        if vote_state.root_slot != root_slot {
+            rooted_slots.extend(vote_state.root_slot);
+        }
+    }
+
+    Ok(VoteStateDelta {
+        rooted_slots,
+        timestamp,
+        ..VoteStateDelta::new(pre_credits, pre_root, vote_state)
+    })
+}
+
+/// Applies a `VoteStateUpdate` that landed in `current_slot` during `epoch`.
+///
+/// On error `vote_state` is left unchanged.
+pub fn apply_update(
+    vote_state: &mut VoteState,
+    vote_state_update: &VoteStateUpdate,
+    epoch: Epoch,
+    current_slot: Slot,
+) -> Result<VoteStateDelta, InstructionError> {
+    if vote_state_update.lockouts.is_empty() {
+        return Err(VoteError::EmptySlots.into());
+    }
+    check_authorized_voter(vote_state, epoch)?;
+
+    let pre_credits = vote_state.credits();
+    let pre_root = vote_state.root_slot;
+    let rooted_slots = match vote_state_update.root {
+        Some(new_root) if pre_root != Some(new_root) => vote_state
+            .votes
+            .iter()
+            .map(LandedVote::slot)
+            .take_while(|slot| *slot <= new_root)
+            .collect(),
+        _ => vec![],
+    };
+
+    process_new_vote_state(
+        vote_state,
+        vote_state_update
+            .lockouts
+            .iter()
+            .copied()
+            .map(LandedVote::from)
+            .collect(),
+        vote_state_update.root,
+        vote_state_update.timestamp,
+        epoch,
+        current_slot,
+    )?;
+    vote_state.get_and_update_authorized_voter(epoch)?;
+
+    Ok(VoteStateDelta {
+        rooted_slots,
+        timestamp: vote_state_update
+            .timestamp
+            .map(|_| vote_state.last_timestamp.clone()),
+        ..VoteStateDelta::new(pre_credits, pre_root, vote_state)
+    })
+}
+
+/// Fails as `get_and_update_authorized_voter` would, without purging.
+fn check_authorized_voter(vote_state: &VoteState, epoch: Epoch) -> Result<(), InstructionError> {
+    vote_state
+        .authorized_voters()
+        .get_authorized_voter(epoch)
+        .map(|_| ())
+        .ok_or(InstructionError::InvalidAccountData)
+}
+
+/// Replaces the tower in `vote_state` with `new_state`, checking that the
+/// transition is consistent with the existing lockouts and awarding credits
+/// for newly rooted slots. Mirrors the runtime's handling of a proposed tower
+/// after it has been filtered against `SlotHashes`.
+///
+/// Every check, including the timestamp's, runs before `vote_state` is
+/// changed, so on error it is left as it was.
+pub(crate) fn process_new_vote_state(
+    vote_state: &mut VoteState,
+    mut new_state: VecDeque<LandedVote>,
+    new_root: Option<Slot>,
+    timestamp: Option<i64>,
+    epoch: Epoch,
+    current_slot: Slot,
+) -> Result<(), VoteError> {
+    if new_state.is_empty() {
+        return Err(VoteError::EmptySlots);
+    }
+    if new_state.len() > MAX_LOCKOUT_HISTORY {
+        return Err(VoteError::TooManyVotes);
+    }
+
+    match (new_root, vote_state.root_slot) {
+        (Some(new_root), Some(current_root)) if new_root < current_root => {
+            return Err(VoteError::RootRollBack);
+        }
+        (None, Some(_)) => return Err(VoteError::RootRollBack),
+        _ => (),
+    }
+
+    let mut previous_vote: Option<&LandedVote> = None;
+    for vote in &new_state {
+        if vote.confirmation_count() == 0 {
+            return Err(VoteError::ZeroConfirmations);
+        } else if vote.confirmation_count() > MAX_LOCKOUT_HISTORY as u32 {
+            return Err(VoteError::ConfirmationTooLarge);
+        } else if let Some(new_root) = new_root {
+            // Towers always carry a root, even when empty, so a zero root is
+            // not treated as a real root here.
+            if vote.slot() <= new_root && new_root != Slot::default() {
+                return Err(VoteError::SlotSmallerThanRoot);
+            }
+        }
+
+        if let Some(previous_vote) = previous_vote {
+            if previous_vote.slot() >= vote.slot() {
+                return Err(VoteError::SlotsNotOrdered);
+            } else if previous_vote.confirmation_count() <= vote.confirmation_count() {
+                return Err(VoteError::ConfirmationsNotOrdered);
+            } else if vote.slot() > previous_vote.lockout.last_locked_out_slot() {
+                return Err(VoteError::NewVoteStateLockoutMismatch);
+            }
+        }
+        previous_vote = Some(vote);
+    }
+
+    // Credit every current vote that the new root finalizes.
+    let mut current_vote_state_index = 0;
+    let mut earned_credits = 0_u64;
+    if let Some(new_root) = new_root {
+        for current_vote in &vote_state.votes {
+            if current_vote.slot() > new_root {
+                break;
+            }
+            earned_credits = earned_credits.saturating_add(vote_state.credits_for_vote_at_index(
+                current_vote_state_index,
+                TIMELY_VOTE_CREDITS,
+                DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+            ));
+            current_vote_state_index += 1;
+        }
+    }
+
+    // Votes missing from the new state must have expired; votes present in
+    // both keep their recorded latency.
+    let mut new_vote_state_index = 0;
+    while current_vote_state_index < vote_state.votes.len()
+        && new_vote_state_index < new_state.len()
+    {
+        let current_vote = &vote_state.votes[current_vote_state_index];
+        let new_vote = &mut new_state[new_vote_state_index];
+
+        match current_vote.slot().cmp(&new_vote.slot()) {
+            Ordering::Less => {
+                if current_vote.lockout.last_locked_out_slot() >= new_vote.slot() {
+                    return Err(VoteError::LockoutConflict);
+                }
+                current_vote_state_index += 1;
+            }
+            Ordering::Equal => {
+                if new_vote.confirmation_count() < current_vote.confirmation_count() {
+                    return Err(VoteError::ConfirmationRollBack);
+                }
+                new_vote.latency = current_vote.latency;
+                current_vote_state_index += 1;
+                new_vote_state_index += 1;
+            }
+            Ordering::Greater => new_vote_state_index += 1,
+        }
+    }
+
+    // Newly voted slots record the latency at which this vote landed.
+    for new_vote in new_state.iter_mut().filter(|vote| vote.latency == 0) {
+        new_vote.latency = VoteState::compute_vote_latency(new_vote.slot(), current_slot);
+    }
+
+    if let Some(timestamp) = timestamp {
+        let last_slot = new_state.back().map(LandedVote::slot).unwrap_or_default();
+        vote_state.process_timestamp(last_slot, timestamp)?;
+    }
+    if vote_state.root_slot != new_root {
+        vote_state.increment_credits(epoch, earned_credits);
+    }
+    vote_state.root_slot = new_root;
+    vote_state.votes = new_state;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{populated_vote_state, VoteStateBuilder},
+        solana_program::{hash::Hash, vote::state::Lockout},
+    };
+
+    fn timestamped_vote_state() -> VoteState {
+        let mut vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 4, 3);
+        let last_voted_slot = vote_state.last_voted_slot().unwrap();
+        vote_state.last_timestamp = BlockTimestamp {
+            slot: last_voted_slot,
+            timestamp: 1_000,
+        };
+        vote_state
+    }
+
+    fn next_update(vote_state: &VoteState, timestamp: i64) -> VoteStateUpdate {
+        let mut shadow = vote_state.clone();
+        let slot = shadow.last_voted_slot().unwrap() + 1;
+        shadow.process_next_vote_slot(slot, 0, slot + 1, true, true);
+        let mut vote_state_update = VoteStateUpdate::new(
+            shadow.votes.iter().map(|vote| vote.lockout).collect(),
+            shadow.root_slot,
+            Hash::default(),
+        );
+        vote_state_update.timestamp = Some(timestamp);
+        vote_state_update
+    }
+
+    #[test]
+    fn test_apply_vote_timestamp_too_old_leaves_state() {
+        let vote_state = timestamped_vote_state();
+        let epoch = vote_state
+            .authorized_voters()
+            .last()
+            .map(|(e, _)| *e)
+            .unwrap()
+            + 2;
+        let slot = vote_state.last_voted_slot().unwrap() + 1;
+        let vote = Vote {
+            slots: vec![slot],
+            hash: Hash::default(),
+            timestamp: Some(999),
+        };
+        let mut updated = vote_state.clone();
+        assert_eq!(
+            apply_vote(&mut updated, &vote, epoch, slot + 1),
+            Err(VoteError::TimestampTooOld.into())
+        );
+        assert_eq!(updated, vote_state);
+    }
+
+    #[test]
+    fn test_apply_update_timestamp_too_old_leaves_state() {
+        let vote_state = timestamped_vote_state();
+        let epoch = vote_state
+            .authorized_voters()
+            .last()
+            .map(|(e, _)| *e)
+            .unwrap()
+            + 2;
+        let vote_state_update = next_update(&vote_state, 999);
+        let mut updated = vote_state.clone();
+        assert_eq!(
+            apply_update(&mut updated, &vote_state_update, epoch, 10_000),
+            Err(VoteError::TimestampTooOld.into())
+        );
+        assert_eq!(updated, vote_state);
+    }
+
+    #[test]
+    fn test_apply_update_with_valid_timestamp() {
+        let vote_state = timestamped_vote_state();
+        let epoch = vote_state
+            .authorized_voters()
+            .last()
+            .map(|(e, _)| *e)
+            .unwrap()
+            + 2;
+        let vote_state_update = next_update(&vote_state, 1_001);
+        let mut updated = vote_state.clone();
+        let delta = apply_update(&mut updated, &vote_state_update, epoch, 10_000).unwrap();
+        assert_eq!(delta.timestamp.unwrap().timestamp, 1_001);
+        assert_eq!(delta.rooted_slots.len(), 1);
+        assert!(delta.credits_earned > 0);
+        assert_eq!(updated.authorized_voters().len(), 1);
+    }
+
+    #[test]
+    fn test_apply_vote_without_authorized_voter_leaves_state() {
+        let vote_state = VoteState::default();
+        let vote = Vote {
+            slots: vec![1],
+            hash: Hash::default(),
+            timestamp: Some(1),
+        };
+        let mut updated = vote_state.clone();
+        assert_eq!(
+            apply_vote(&mut updated, &vote, 0, 2),
+            Err(InstructionError::InvalidAccountData)
+        );
+        assert_eq!(updated, vote_state);
+    }
+
+    #[test]
+    fn test_process_new_vote_state_rejects_rollback_without_change() {
+        let vote_state = VoteStateBuilder::new().vote(1, 1).vote(2, 1).build();
+        let mut updated = vote_state.clone();
+        let new_state = [Lockout::new(1)]
+            .into_iter()
+            .map(LandedVote::from)
+            .collect();
+        assert_eq!(
+            process_new_vote_state(&mut updated, new_state, None, Some(5), 0, 3),
+            Err(VoteError::ConfirmationRollBack)
+        );
+        assert_eq!(updated, vote_state);
+    }
+}
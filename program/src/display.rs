@@ -0,0 +1,118 @@
+//! Human-readable rendering of vote accounts for operator CLIs.
+//!
+//! The vote types are defined upstream, so these are wrappers:
+//! `println!("{}", DisplayVoteState(&vote_state))`. `TowerSync` is not
+//! available in the solana-program version this crate builds against;
+//! `VoteStateUpdate` carries the same tower.
+
+use {
+    solana_program::{
+        clock::{Epoch, Slot},
+        vote::state::{Lockout, VoteState, VoteStateUpdate},
+    },
+    std::fmt,
+};
+
+/// Renders a [`VoteState`] as labelled lines.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayVoteState<'a>(pub &'a VoteState);
+
+/// Renders a [`VoteStateUpdate`] as labelled lines.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayVoteStateUpdate<'a>(pub &'a VoteStateUpdate);
+
+/// Renders `(epoch, credits, prev_credits)` entries as a table.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayEpochCredits<'a>(pub &'a [(Epoch, u64, u64)]);
+
+impl fmt::Display for DisplayVoteState<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let vote_state = self.0;
+        writeln!(f, "Validator identity: {}", vote_state.node_pubkey)?;
+        writeln!(
+            f,
+            "Authorized withdrawer: {}",
+            vote_state.authorized_withdrawer
+        )?;
+        for (epoch, authorized_voter) in vote_state.authorized_voters().iter() {
+            writeln!(
+                f,
+                "Authorized voter: {authorized_voter} (from epoch {epoch})"
+            )?;
+        }
+        writeln!(f, "Commission: {}%", vote_state.commission)?;
+        writeln!(f, "Root slot: {}", OptionalSlot(vote_state.root_slot))?;
+        writeln!(
+            f,
+            "Last timestamp: {} at slot {}",
+            vote_state.last_timestamp.timestamp, vote_state.last_timestamp.slot
+        )?;
+        writeln!(f, "Recent votes: {}", vote_state.votes.len())?;
+        for landed_vote in vote_state.votes.iter().rev() {
+            write_lockout(f, &landed_vote.lockout)?;
+            writeln!(f, ", latency {}", landed_vote.latency)?;
+        }
+        writeln!(f, "Epoch credits:")?;
+        write!(f, "{}", DisplayEpochCredits(vote_state.epoch_credits()))
+    }
+}
+
+impl fmt::Display for DisplayVoteStateUpdate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let vote_state_update = self.0;
+        writeln!(f, "Root slot: {}", OptionalSlot(vote_state_update.root))?;
+        writeln!(f, "Bank hash: {}", vote_state_update.hash)?;
+        match vote_state_update.timestamp {
+            Some(timestamp) => writeln!(f, "Timestamp: {timestamp}")?,
+            None => writeln!(f, "Timestamp: ~")?,
+        }
+        writeln!(f, "Lockouts: {}", vote_state_update.lockouts.len())?;
+        for lockout in vote_state_update.lockouts.iter().rev() {
+            write_lockout(f, lockout)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DisplayEpochCredits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>8}  {:>16}  {:>16}  {:>10}",
+            "Epoch", "Credits", "Prev credits", "Earned"
+        )?;
+        for (epoch, credits, prev_credits) in self.0.iter().rev() {
+            writeln!(
+                f,
+                "{:>8}  {:>16}  {:>16}  {:>10}",
+                epoch,
+                credits,
+                prev_credits,
+                credits.saturating_sub(*prev_credits)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct OptionalSlot(Option<Slot>);
+
+impl fmt::Display for OptionalSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(slot) => write!(f, "{slot}"),
+            None => write!(f, "~"),
+        }
+    }
+}
+
+fn write_lockout(f: &mut fmt::Formatter, lockout: &Lockout) -> fmt::Result {
+    write!(
+        f,
+        "  slot {}, confirmation count {}, locked out until {}",
+        lockout.slot(),
+        lockout.confirmation_count(),
+        lockout.last_locked_out_slot()
+    )
+}
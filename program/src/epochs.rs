@@ -0,0 +1,112 @@
+//! Epoch boundaries as the vote program sees them.
+
+use solana_program::{
+    clock::{Epoch, Slot},
+    epoch_schedule::EpochSchedule,
+};
+
+/// Slot ranges of the two halves of an epoch, as used by the commission
+/// update window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochHalves {
+    pub first_slot: Slot,
+    /// First slot of the second half, where commission increases stop
+    /// being allowed.
+    pub second_half_first_slot: Slot,
+    pub last_slot: Slot,
+}
+
+/// Returns whether commission increases are allowed at `slot`: in the
+/// first half of its epoch, midpoint included.
+///
+/// Matches the program, which measures from `first_normal_slot` with
+/// `slots_per_epoch`. Every slot before `first_normal_slot` measures as the
+/// start of an epoch, so updates are always allowed during warmup.
+pub fn is_commission_update_allowed(slot: Slot, epoch_schedule: &EpochSchedule) -> bool {
+    match slot
+        .saturating_sub(epoch_schedule.first_normal_slot)
+        .checked_rem(epoch_schedule.slots_per_epoch)
+    {
+        Some(relative_slot) => relative_slot.saturating_mul(2) <= epoch_schedule.slots_per_epoch,
+        None => true,
+    }
+}
+
+/// Halves of `epoch`, split where [`is_commission_update_allowed`] flips
+/// for normal epochs.
+pub fn epoch_halves(epoch: Epoch, epoch_schedule: &EpochSchedule) -> EpochHalves {
+    let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+    let slots_in_epoch = epoch_schedule.get_slots_in_epoch(epoch);
+    EpochHalves {
+        first_slot,
+        second_half_first_slot: first_slot.saturating_add(slots_in_epoch / 2 + 1),
+        last_slot: epoch_schedule.get_last_slot_in_epoch(epoch),
+    }
+}
+
+/// Epoch whose leader schedule is computed at `slot`. An `Authorize(Voter)`
+/// landing at `slot` takes effect in the epoch after it.
+pub fn leader_schedule_epoch(slot: Slot, epoch_schedule: &EpochSchedule) -> Epoch {
+    epoch_schedule.get_leader_schedule_epoch(slot)
+}
+
+/// Slots after `slot` until the end of its epoch.
+pub fn slots_remaining_in_epoch(slot: Slot, epoch_schedule: &EpochSchedule) -> u64 {
+    let epoch = epoch_schedule.get_epoch(slot);
+    epoch_schedule
+        .get_last_slot_in_epoch(epoch)
+        .saturating_sub(slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_commission_update_allowed() {
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let slots_per_epoch = epoch_schedule.slots_per_epoch;
+        let first_slot = slots_per_epoch * 3;
+        assert!(is_commission_update_allowed(first_slot, &epoch_schedule));
+        assert!(is_commission_update_allowed(
+            first_slot + slots_per_epoch / 2,
+            &epoch_schedule
+        ));
+        assert!(!is_commission_update_allowed(
+            first_slot + slots_per_epoch / 2 + 1,
+            &epoch_schedule
+        ));
+        assert!(!is_commission_update_allowed(
+            first_slot + slots_per_epoch - 1,
+            &epoch_schedule
+        ));
+    }
+
+    #[test]
+    fn test_is_commission_update_allowed_during_warmup() {
+        let epoch_schedule = EpochSchedule::new(8192);
+        let first_normal_slot = epoch_schedule.first_normal_slot;
+        assert!(epoch_schedule.warmup && first_normal_slot > 0);
+        // Including the second half of every warmup epoch.
+        assert!(
+            (0..first_normal_slot).all(|slot| is_commission_update_allowed(slot, &epoch_schedule))
+        );
+        assert!(is_commission_update_allowed(
+            first_normal_slot + 4096,
+            &epoch_schedule
+        ));
+        assert!(!is_commission_update_allowed(
+            first_normal_slot + 4097,
+            &epoch_schedule
+        ));
+    }
+
+    #[test]
+    fn test_is_commission_update_allowed_without_epoch_length() {
+        let epoch_schedule = EpochSchedule {
+            slots_per_epoch: 0,
+            ..EpochSchedule::without_warmup()
+        };
+        assert!(is_commission_update_allowed(12_345, &epoch_schedule));
+    }
+}
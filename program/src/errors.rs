@@ -0,0 +1,113 @@
+//! Catalog of the custom errors the vote program can return.
+
+use solana_program::vote::error::VoteError;
+
+/// One custom error, as it appears in `InstructionError::Custom(code)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorCatalogEntry {
+    pub code: u32,
+    /// `VoteError` variant name.
+    pub name: String,
+    pub description: String,
+}
+
+impl From<VoteError> for ErrorCatalogEntry {
+    fn from(err: VoteError) -> Self {
+        Self {
+            name: format!("{err:?}"),
+            description: err.to_string(),
+            code: err as u32,
+        }
+    }
+}
+
+/// Every `VoteError`, in code order.
+pub fn catalog() -> Vec<ErrorCatalogEntry> {
+    [
+        VoteError::VoteTooOld,
+        VoteError::SlotsMismatch,
+        VoteError::SlotHashMismatch,
+        VoteError::EmptySlots,
+        VoteError::TimestampTooOld,
+        VoteError::TooSoonToReauthorize,
+        VoteError::LockoutConflict,
+        VoteError::NewVoteStateLockoutMismatch,
+        VoteError::SlotsNotOrdered,
+        VoteError::ConfirmationsNotOrdered,
+        VoteError::ZeroConfirmations,
+        VoteError::ConfirmationTooLarge,
+        VoteError::RootRollBack,
+        VoteError::ConfirmationRollBack,
+        VoteError::SlotSmallerThanRoot,
+        VoteError::TooManyVotes,
+        VoteError::VotesTooOldAllFiltered,
+        VoteError::RootOnDifferentFork,
+        VoteError::ActiveVoteAccountClose,
+        VoteError::CommissionUpdateTooLate,
+    ]
+    .into_iter()
+    .map(ErrorCatalogEntry::from)
+    .collect()
+}
+
+/// Looks up the catalog entry for a custom error code.
+pub fn lookup(code: u32) -> Option<ErrorCatalogEntry> {
+    catalog().into_iter().find(|entry| entry.code == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::HashSet};
+
+    #[test]
+    fn test_catalog_has_every_code() {
+        let catalog = catalog();
+        let codes: Vec<_> = catalog.iter().map(|entry| entry.code).collect();
+        assert_eq!(codes, (0..=19).collect::<Vec<_>>());
+        let names: HashSet<_> = catalog.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names.len(), catalog.len());
+        assert!(catalog.iter().all(|entry| !entry.description.is_empty()));
+    }
+
+    #[test]
+    fn test_catalog_is_exhaustive() {
+        // Fails to compile when the SDK adds a variant the catalog lacks.
+        fn listed(err: &VoteError) -> bool {
+            match err {
+                VoteError::VoteTooOld
+                | VoteError::SlotsMismatch
+                | VoteError::SlotHashMismatch
+                | VoteError::EmptySlots
+                | VoteError::TimestampTooOld
+                | VoteError::TooSoonToReauthorize
+                | VoteError::LockoutConflict
+                | VoteError::NewVoteStateLockoutMismatch
+                | VoteError::SlotsNotOrdered
+                | VoteError::ConfirmationsNotOrdered
+                | VoteError::ZeroConfirmations
+                | VoteError::ConfirmationTooLarge
+                | VoteError::RootRollBack
+                | VoteError::ConfirmationRollBack
+                | VoteError::SlotSmallerThanRoot
+                | VoteError::TooManyVotes
+                | VoteError::VotesTooOldAllFiltered
+                | VoteError::RootOnDifferentFork
+                | VoteError::ActiveVoteAccountClose
+                | VoteError::CommissionUpdateTooLate => true,
+            }
+        }
+        assert!(listed(&VoteError::VoteTooOld));
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(
+            lookup(VoteError::CommissionUpdateTooLate as u32),
+            Some(ErrorCatalogEntry::from(VoteError::CommissionUpdateTooLate))
+        );
+        let entry = lookup(0).unwrap();
+        assert_eq!(entry.name, "VoteTooOld");
+        assert_eq!(entry.description, VoteError::VoteTooOld.to_string());
+        assert_eq!(lookup(20), None);
+    }
+}
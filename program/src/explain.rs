@@ -0,0 +1,337 @@
+//! Why a proposed vote state update was rejected.
+//!
+//! Reruns the checks of [`simulate_vote`](crate::simulate::simulate_vote)
+//! and attaches the slots and hashes involved, so operators do not have to
+//! reconstruct them from runtime logs.
+
+use {
+    crate::{delta::process_new_vote_state, simulate::check_and_filter_proposed_vote_state},
+    solana_program::{
+        clock::{Clock, Slot},
+        hash::Hash,
+        instruction::InstructionError,
+        vote::{
+            error::VoteError,
+            state::{LandedVote, VoteState, VoteStateUpdate},
+        },
+    },
+    thiserror::Error,
+};
+
+/// Structured reason a vote state update is rejected.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum VoteRejection {
+    #[error("no authorized voter for the current epoch: {0}")]
+    AuthorizedVoter(InstructionError),
+    #[error("update proposes no lockouts")]
+    EmptyLockouts,
+    #[error("slot {last_proposed_slot} is not newer than the last vote {last_voted_slot}")]
+    NotNewerThanLastVote {
+        last_proposed_slot: Slot,
+        last_voted_slot: Slot,
+    },
+    #[error("slot {last_proposed_slot} is older than the oldest slot hash, {earliest_slot_hash}")]
+    OlderThanSlotHashes {
+        last_proposed_slot: Slot,
+        earliest_slot_hash: Slot,
+    },
+    #[error("SlotHashes is empty")]
+    NoSlotHashes,
+    #[error("root {root} is not an ancestor in SlotHashes")]
+    RootOnDifferentFork { root: Slot },
+    #[error("slot {slot} is not in SlotHashes")]
+    SlotNotInSlotHashes { slot: Slot },
+    #[error("proposed slots are not in increasing order")]
+    SlotsNotOrdered,
+    #[error("hash {proposed} for slot {slot} does not match {expected} in SlotHashes")]
+    SlotHashMismatch {
+        slot: Slot,
+        expected: Hash,
+        proposed: Hash,
+    },
+    #[error("update conflicts with the stored tower: {0}")]
+    Tower(VoteError),
+}
+
+/// Returns why `vote_state_update` would be rejected when landing at
+/// `clock.slot`, or `None` if it would be accepted.
+///
+/// `slot_hashes` must be ordered newest first, as in the `SlotHashes` sysvar.
+pub fn explain_vote_rejection(
+    vote_state: &VoteState,
+    vote_state_update: &VoteStateUpdate,
+    slot_hashes: &[(Slot, Hash)],
+    clock: &Clock,
+) -> Option<VoteRejection> {
+    let mut vote_state = vote_state.clone();
+    if let Err(err) = vote_state.get_and_update_authorized_voter(clock.epoch) {
+        return Some(VoteRejection::AuthorizedVoter(err));
+    }
+    let mut lockouts = vote_state_update.lockouts.clone();
+    let mut root = vote_state_update.root;
+    if let Err(err) = check_and_filter_proposed_vote_state(
+        &vote_state,
+        &mut lockouts,
+        &mut root,
+        vote_state_update.hash,
+        slot_hashes,
+    ) {
+        return Some(explain_slot_hashes_error(
+            err,
+            &vote_state,
+            vote_state_update,
+            root,
+            slot_hashes,
+        ));
+    }
+    process_new_vote_state(
+        &mut vote_state,
+        lockouts.into_iter().map(LandedVote::from).collect(),
+        root,
+        vote_state_update.timestamp,
+        clock.epoch,
+        clock.slot,
+    )
+    .err()
+    .map(VoteRejection::Tower)
+}
+
+fn explain_slot_hashes_error(
+    err: VoteError,
+    vote_state: &VoteState,
+    vote_state_update: &VoteStateUpdate,
+    filtered_root: Option<Slot>,
+    slot_hashes: &[(Slot, Hash)],
+) -> VoteRejection {
+    let last_proposed_slot = vote_state_update
+        .lockouts
+        .back()
+        .map(|lockout| lockout.slot());
+    let earliest_slot_hash = slot_hashes.last().map(|(slot, _)| *slot);
+    let in_slot_hashes = |slot: Slot| slot_hashes.iter().any(|(hash_slot, _)| *hash_slot == slot);
+    match (err, last_proposed_slot, earliest_slot_hash) {
+        (VoteError::EmptySlots, ..) | (_, None, _) => VoteRejection::EmptyLockouts,
+        (VoteError::VoteTooOld, Some(last_proposed_slot), earliest_slot_hash) => {
+            match (vote_state.last_voted_slot(), earliest_slot_hash) {
+                (Some(last_voted_slot), _) if last_proposed_slot <= last_voted_slot => {
+                    VoteRejection::NotNewerThanLastVote {
+                        last_proposed_slot,
+                        last_voted_slot,
+                    }
+                }
+                (_, Some(earliest_slot_hash)) => VoteRejection::OlderThanSlotHashes {
+                    last_proposed_slot,
+                    earliest_slot_hash,
+                },
+                (_, None) => VoteRejection::NoSlotHashes,
+            }
+        }
+        (_, _, None) => VoteRejection::NoSlotHashes,
+        (VoteError::RootOnDifferentFork, ..) => VoteRejection::RootOnDifferentFork {
+            root: filtered_root.unwrap_or_default(),
+        },
+        (VoteError::SlotsMismatch, _, Some(earliest_slot_hash)) => vote_state_update
+            .lockouts
+            .iter()
+            .map(|lockout| lockout.slot())
+            .find(|slot| *slot >= earliest_slot_hash && !in_slot_hashes(*slot))
+            .map(|slot| VoteRejection::SlotNotInSlotHashes { slot })
+            .unwrap_or(VoteRejection::Tower(VoteError::SlotsMismatch)),
+        (VoteError::SlotHashMismatch, Some(slot), _) => VoteRejection::SlotHashMismatch {
+            slot,
+            expected: slot_hashes
+                .iter()
+                .find(|(hash_slot, _)| *hash_slot == slot)
+                .map(|(_, hash)| *hash)
+                .unwrap_or_default(),
+            proposed: vote_state_update.hash,
+        },
+        (VoteError::SlotsNotOrdered, ..) => VoteRejection::SlotsNotOrdered,
+        (err, ..) => VoteRejection::Tower(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_bank_hash, fixture_slot_hashes, VoteStateBuilder},
+    };
+
+    /// A tower that voted on slots 1, 2 and 3.
+    fn vote_state() -> VoteState {
+        VoteStateBuilder::new()
+            .vote(1, 1)
+            .vote(2, 1)
+            .vote(3, 1)
+            .build()
+    }
+
+    /// An update proposing `lockouts` with the bank hash of its newest slot.
+    fn update(lockouts: &[(Slot, u32)], root: Option<Slot>) -> VoteStateUpdate {
+        let mut vote_state_update = VoteStateUpdate::from(lockouts.to_vec());
+        vote_state_update.root = root;
+        vote_state_update.hash = lockouts
+            .last()
+            .map(|(slot, _)| fixture_bank_hash(*slot))
+            .unwrap_or_default();
+        vote_state_update
+    }
+
+    /// Votes on slot 4 on top of [`vote_state`].
+    fn next_update() -> VoteStateUpdate {
+        update(&[(1, 4), (2, 3), (3, 2), (4, 1)], None)
+    }
+
+    fn explain(
+        vote_state: &VoteState,
+        vote_state_update: &VoteStateUpdate,
+        slot_hashes: &[(Slot, Hash)],
+    ) -> Option<VoteRejection> {
+        let clock = Clock {
+            slot: 5,
+            ..Clock::default()
+        };
+        let rejection = explain_vote_rejection(vote_state, vote_state_update, slot_hashes, &clock);
+        // Every explained rejection is one the program would raise.
+        assert_eq!(
+            rejection.is_some(),
+            crate::simulate::simulate_vote(vote_state, vote_state_update, slot_hashes, &clock)
+                .is_err()
+        );
+        rejection
+    }
+
+    fn without(skipped: Slot) -> Vec<(Slot, Hash)> {
+        fixture_slot_hashes((0..=10).filter(|slot| *slot != skipped))
+    }
+
+    #[test]
+    fn test_accepted() {
+        assert_eq!(
+            explain(&vote_state(), &next_update(), &fixture_slot_hashes(0..=10)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_authorized_voter() {
+        let vote_state = VoteState::default();
+        let err = vote_state
+            .clone()
+            .get_and_update_authorized_voter(0)
+            .unwrap_err();
+        assert_eq!(
+            explain(&vote_state, &next_update(), &fixture_slot_hashes(0..=10)),
+            Some(VoteRejection::AuthorizedVoter(err))
+        );
+    }
+
+    #[test]
+    fn test_empty_lockouts() {
+        assert_eq!(
+            explain(
+                &vote_state(),
+                &update(&[], None),
+                &fixture_slot_hashes(0..=10)
+            ),
+            Some(VoteRejection::EmptyLockouts)
+        );
+    }
+
+    #[test]
+    fn test_not_newer_than_last_vote() {
+        assert_eq!(
+            explain(
+                &vote_state(),
+                &update(&[(3, 1)], None),
+                &fixture_slot_hashes(0..=10)
+            ),
+            Some(VoteRejection::NotNewerThanLastVote {
+                last_proposed_slot: 3,
+                last_voted_slot: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_older_than_slot_hashes() {
+        assert_eq!(
+            explain(&vote_state(), &next_update(), &fixture_slot_hashes(5..=10)),
+            Some(VoteRejection::OlderThanSlotHashes {
+                last_proposed_slot: 4,
+                earliest_slot_hash: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_slot_hashes() {
+        assert_eq!(
+            explain(&vote_state(), &next_update(), &[]),
+            Some(VoteRejection::NoSlotHashes)
+        );
+    }
+
+    #[test]
+    fn test_root_on_different_fork() {
+        assert_eq!(
+            explain(
+                &vote_state(),
+                &update(&[(2, 3), (3, 2), (4, 1)], Some(1)),
+                &without(1)
+            ),
+            Some(VoteRejection::RootOnDifferentFork { root: 1 })
+        );
+    }
+
+    #[test]
+    fn test_slot_not_in_slot_hashes() {
+        assert_eq!(
+            explain(&vote_state(), &next_update(), &without(3)),
+            Some(VoteRejection::SlotNotInSlotHashes { slot: 3 })
+        );
+    }
+
+    #[test]
+    fn test_slots_not_ordered() {
+        assert_eq!(
+            explain(
+                &vote_state(),
+                &update(&[(1, 4), (3, 3), (2, 2), (4, 1)], None),
+                &fixture_slot_hashes(0..=10)
+            ),
+            Some(VoteRejection::SlotsNotOrdered)
+        );
+    }
+
+    #[test]
+    fn test_slot_hash_mismatch() {
+        let mut vote_state_update = next_update();
+        vote_state_update.hash = fixture_bank_hash(0);
+        assert_eq!(
+            explain(
+                &vote_state(),
+                &vote_state_update,
+                &fixture_slot_hashes(0..=10)
+            ),
+            Some(VoteRejection::SlotHashMismatch {
+                slot: 4,
+                expected: fixture_bank_hash(4),
+                proposed: fixture_bank_hash(0),
+            })
+        );
+    }
+
+    #[test]
+    fn test_tower_conflict() {
+        assert_eq!(
+            explain(
+                &vote_state(),
+                &update(&[(1, 4), (2, 3), (3, 2), (4, 2)], None),
+                &fixture_slot_hashes(0..=10)
+            ),
+            Some(VoteRejection::Tower(VoteError::ConfirmationsNotOrdered))
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Byte offsets and `memcmp` filters for RPC account queries.
+//!
+//! Offsets are into the serialized `VoteStateVersions`, and hold for both the
+//! current and the 1.14.11 layouts.
+
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// Length of the `VoteStateVersions` enum tag.
+pub const VERSION_TAG_LEN: usize = 4;
+/// Offset of `node_pubkey`.
+pub const NODE_PUBKEY_OFFSET: usize = VERSION_TAG_LEN;
+/// Offset of `authorized_withdrawer`.
+pub const AUTHORIZED_WITHDRAWER_OFFSET: usize = NODE_PUBKEY_OFFSET + PUBKEY_BYTES;
+/// Offset of `commission`.
+pub const COMMISSION_OFFSET: usize = AUTHORIZED_WITHDRAWER_OFFSET + PUBKEY_BYTES;
+
+/// A `memcmp` filter: matches accounts whose data at `offset` starts with
+/// `bytes`. Maps directly onto the RPC `Memcmp` filter with raw bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemcmpFilter {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl MemcmpFilter {
+    /// Matches vote accounts voting for validator identity `node_pubkey`.
+    pub fn node_pubkey(node_pubkey: &Pubkey) -> Self {
+        Self {
+            offset: NODE_PUBKEY_OFFSET,
+            bytes: node_pubkey.to_bytes().to_vec(),
+        }
+    }
+
+    /// Matches vote accounts withdrawable by `authorized_withdrawer`.
+    pub fn authorized_withdrawer(authorized_withdrawer: &Pubkey) -> Self {
+        Self {
+            offset: AUTHORIZED_WITHDRAWER_OFFSET,
+            bytes: authorized_withdrawer.to_bytes().to_vec(),
+        }
+    }
+
+    /// Matches vote accounts charging exactly `commission` percent.
+    pub fn commission(commission: u8) -> Self {
+        Self {
+            offset: COMMISSION_OFFSET,
+            bytes: vec![commission],
+        }
+    }
+
+    /// Returns whether `data` passes this filter.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.get(self.offset..)
+            .is_some_and(|data| data.starts_with(&self.bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, serialize_1_14_11, VoteStateBuilder},
+        solana_program::vote::state::{VoteState, VoteStateVersions},
+    };
+
+    fn vote_state() -> VoteState {
+        VoteStateBuilder::new()
+            .node_pubkey(fixture_pubkey(11))
+            .authorized_withdrawer(fixture_pubkey(12))
+            .commission(42)
+            .vote(1, 1)
+            .build()
+    }
+
+    #[test]
+    fn test_offsets_match_current_layout() {
+        let data = bincode::serialize(&VoteStateVersions::new_current(vote_state())).unwrap();
+        assert_eq!(
+            data[NODE_PUBKEY_OFFSET..NODE_PUBKEY_OFFSET + PUBKEY_BYTES],
+            fixture_pubkey(11).to_bytes()
+        );
+        assert_eq!(
+            data[AUTHORIZED_WITHDRAWER_OFFSET..AUTHORIZED_WITHDRAWER_OFFSET + PUBKEY_BYTES],
+            fixture_pubkey(12).to_bytes()
+        );
+        assert_eq!(data[COMMISSION_OFFSET], 42);
+    }
+
+    #[test]
+    fn test_filters_match_both_layouts() {
+        let current = bincode::serialize(&VoteStateVersions::new_current(vote_state())).unwrap();
+        let legacy = serialize_1_14_11(&vote_state()).unwrap();
+        for data in [&current, &legacy] {
+            assert!(MemcmpFilter::node_pubkey(&fixture_pubkey(11)).matches(data));
+            assert!(MemcmpFilter::authorized_withdrawer(&fixture_pubkey(12)).matches(data));
+            assert!(MemcmpFilter::commission(42).matches(data));
+            assert!(!MemcmpFilter::node_pubkey(&fixture_pubkey(12)).matches(data));
+            assert!(!MemcmpFilter::authorized_withdrawer(&fixture_pubkey(11)).matches(data));
+            assert!(!MemcmpFilter::commission(43).matches(data));
+        }
+    }
+
+    #[test]
+    fn test_short_data() {
+        let filter = MemcmpFilter::commission(42);
+        assert!(!filter.matches(&[]));
+        assert!(!filter.matches(&[0; COMMISSION_OFFSET]));
+        let mut data = vec![0; COMMISSION_OFFSET + 1];
+        data[COMMISSION_OFFSET] = 42;
+        assert!(filter.matches(&data));
+    }
+}
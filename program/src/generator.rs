@@ -0,0 +1,197 @@
+//! Deterministic vote traffic for load tests and benchmarks.
+//!
+//! [`VoteTrafficGenerator`] follows one validator's tower: it votes on
+//! mostly consecutive slots, occasionally skips slots, now and then
+//! switches forks far enough ahead that recent lockouts expire, and
+//! attaches a timestamp at a fixed interval. The same seed always yields
+//! the same stream.
+
+use {
+    crate::delta::{DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING, TIMELY_VOTE_CREDITS},
+    solana_program::{
+        clock::{Slot, UnixTimestamp},
+        hash::{hashv, Hash},
+        vote::{
+            instruction::VoteInstruction,
+            state::{VoteState, VoteStateUpdate},
+        },
+    },
+};
+
+/// Slot duration used to derive timestamps.
+const MS_PER_SLOT: u64 = 400;
+
+/// Shape of the generated traffic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoteTrafficConfig {
+    pub seed: u64,
+    /// Slot the first vote is cast on.
+    pub start_slot: Slot,
+    /// Timestamp of slot 0.
+    pub genesis_timestamp: UnixTimestamp,
+    /// Chance, in percent, that a vote skips one or more slots.
+    pub skip_percent: u8,
+    /// Chance, in percent, that a vote switches forks.
+    pub switch_percent: u8,
+    /// Every this many votes carry a timestamp; 0 for none.
+    pub timestamp_interval: u64,
+    /// Most slots a vote lands after the slot it votes on.
+    pub max_latency: u8,
+}
+
+impl Default for VoteTrafficConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            start_slot: 1,
+            genesis_timestamp: 0,
+            skip_percent: 5,
+            switch_percent: 1,
+            timestamp_interval: 1,
+            max_latency: 2,
+        }
+    }
+}
+
+/// Infinite stream of `CompactUpdateVoteState` and
+/// `CompactUpdateVoteStateSwitch` instructions from one validator.
+#[derive(Clone, Debug)]
+pub struct VoteTrafficGenerator {
+    config: VoteTrafficConfig,
+    rng: SplitMix64,
+    vote_state: VoteState,
+    next_slot: Slot,
+    votes: u64,
+}
+
+impl VoteTrafficGenerator {
+    pub fn new(config: VoteTrafficConfig) -> Self {
+        Self {
+            config,
+            rng: SplitMix64(config.seed),
+            vote_state: VoteState::default(),
+            next_slot: config.start_slot,
+            votes: 0,
+        }
+    }
+
+    /// The validator's tower after the votes generated so far.
+    pub fn vote_state(&self) -> &VoteState {
+        &self.vote_state
+    }
+
+    fn percent(&mut self, percent: u8) -> bool {
+        self.rng.next_u64() % 100 < u64::from(percent)
+    }
+}
+
+impl Iterator for VoteTrafficGenerator {
+    type Item = VoteInstruction;
+
+    fn next(&mut self) -> Option<VoteInstruction> {
+        let switch = self.percent(self.config.switch_percent);
+        let mut slot = self.next_slot;
+        if switch {
+            // Vote past the newest lockout so the tower pops on the new fork.
+            let lockout = self
+                .vote_state
+                .votes
+                .back()
+                .map(|landed_vote| landed_vote.lockout.lockout())
+                .unwrap_or_default();
+            slot = slot.saturating_add(lockout);
+        } else if self.percent(self.config.skip_percent) {
+            slot = slot.saturating_add(1 + self.rng.next_u64() % 4);
+        }
+        let latency = 1 + self.rng.next_u64() % u64::from(self.config.max_latency.max(1));
+        self.vote_state.process_next_vote_slot(
+            slot,
+            0,
+            slot.saturating_add(latency),
+            TIMELY_VOTE_CREDITS,
+            DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+        );
+        self.next_slot = slot.saturating_add(1);
+        self.votes = self.votes.saturating_add(1);
+
+        // A zero interval never timestamps.
+        let interval = self.config.timestamp_interval;
+        let timestamp = (self.votes.checked_rem(interval) == Some(0)).then(|| {
+            let elapsed_secs = slot.saturating_mul(MS_PER_SLOT) / 1_000;
+            self.config
+                .genesis_timestamp
+                .saturating_add(elapsed_secs as UnixTimestamp)
+        });
+        let mut vote_state_update = VoteStateUpdate::new(
+            self.vote_state
+                .votes
+                .iter()
+                .map(|landed_vote| landed_vote.lockout)
+                .collect(),
+            self.vote_state.root_slot,
+            bank_hash(self.config.seed, slot),
+        );
+        vote_state_update.timestamp = timestamp;
+        Some(if switch {
+            let proof_hash = hashv(&[b"switch", &self.rng.next_u64().to_le_bytes()]);
+            VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, proof_hash)
+        } else {
+            VoteInstruction::CompactUpdateVoteState(vote_state_update)
+        })
+    }
+}
+
+fn bank_hash(seed: u64, slot: Slot) -> Hash {
+    hashv(&[&seed.to_le_bytes(), &slot.to_le_bytes()])
+}
+
+/// SplitMix64, for a seeded stream without a `rand` dependency.
+#[derive(Clone, Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamps(timestamp_interval: u64) -> Vec<bool> {
+        let config = VoteTrafficConfig {
+            timestamp_interval,
+            ..VoteTrafficConfig::default()
+        };
+        VoteTrafficGenerator::new(config)
+            .take(6)
+            .map(|instruction| match instruction {
+                VoteInstruction::CompactUpdateVoteState(update)
+                | VoteInstruction::CompactUpdateVoteStateSwitch(update, _) => {
+                    update.timestamp.is_some()
+                }
+                _ => panic!("unexpected instruction"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_timestamp_interval() {
+        assert_eq!(timestamps(0), [false; 6]);
+        assert_eq!(timestamps(1), [true; 6]);
+        assert_eq!(timestamps(3), [false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn test_same_seed_same_stream() {
+        let config = VoteTrafficConfig::default();
+        assert!(VoteTrafficGenerator::new(config)
+            .take(64)
+            .eq(VoteTrafficGenerator::new(config).take(64)));
+    }
+}
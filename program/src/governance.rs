@@ -0,0 +1,98 @@
+//! Helpers for vote accounts whose withdrawer is held by a governance or
+//! multisig program.
+//!
+//! The withdrawer is first handed to the program's authority address with
+//! [`grant_withdrawer`]. The program then acts on the vote account by
+//! invoking the vote program with that address as a signer.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    vote::{instruction as vote_instruction, state::VoteAuthorize},
+};
+
+/// Hands the withdraw authority of `vote_pubkey` to `governance_authority`,
+/// signed by the current withdrawer.
+///
+/// Uses the unchecked `Authorize`, since a program derived address cannot
+/// sign a transaction.
+pub fn grant_withdrawer(
+    vote_pubkey: &Pubkey,
+    authorized_withdrawer: &Pubkey,
+    governance_authority: &Pubkey,
+) -> Instruction {
+    vote_instruction::authorize(
+        vote_pubkey,
+        authorized_withdrawer,
+        governance_authority,
+        VoteAuthorize::Withdrawer,
+    )
+}
+
+/// Invokes `Authorize` signed by `authority` through `signer_seeds`.
+pub fn authorize_signed<'a>(
+    vote_account: &AccountInfo<'a>,
+    clock: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    new_authority: &Pubkey,
+    vote_authorize: VoteAuthorize,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &vote_instruction::authorize(
+            vote_account.key,
+            authority.key,
+            new_authority,
+            vote_authorize,
+        ),
+        &[vote_account.clone(), clock.clone(), authority.clone()],
+        signer_seeds,
+    )
+}
+
+/// Invokes `Withdraw` signed by `authorized_withdrawer` through
+/// `signer_seeds`.
+pub fn withdraw_signed<'a>(
+    vote_account: &AccountInfo<'a>,
+    recipient: &AccountInfo<'a>,
+    authorized_withdrawer: &AccountInfo<'a>,
+    lamports: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &vote_instruction::withdraw(
+            vote_account.key,
+            authorized_withdrawer.key,
+            lamports,
+            recipient.key,
+        ),
+        &[
+            vote_account.clone(),
+            recipient.clone(),
+            authorized_withdrawer.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Invokes `UpdateCommission` signed by `authorized_withdrawer` through
+/// `signer_seeds`.
+pub fn update_commission_signed<'a>(
+    vote_account: &AccountInfo<'a>,
+    authorized_withdrawer: &AccountInfo<'a>,
+    commission: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &vote_instruction::update_commission(
+            vote_account.key,
+            authorized_withdrawer.key,
+            commission,
+        ),
+        &[vote_account.clone(), authorized_withdrawer.clone()],
+        signer_seeds,
+    )
+}
@@ -0,0 +1,46 @@
+//! Hot/cold key separation checks for vote account authorities.
+//!
+//! The withdrawer controls the account's funds and should stay in cold
+//! storage; the voter and the node identity sign continuously and are hot.
+
+use {
+    solana_program::{pubkey::Pubkey, vote::state::VoteState},
+    thiserror::Error,
+};
+
+/// A risky authority configuration.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum KeyHygieneWarning {
+    #[error("authorized withdrawer {0} is also an authorized voter")]
+    VoterIsWithdrawer(Pubkey),
+    #[error("authorized withdrawer {0} is also the validator identity")]
+    WithdrawerIsNodeIdentity(Pubkey),
+    #[error("authorized withdrawer {0} is also the fee payer")]
+    WithdrawerIsFeePayer(Pubkey),
+}
+
+/// Flags authority keys of `vote_state` that should be kept apart.
+///
+/// `fee_payer` is the key the validator pays transaction fees with, usually
+/// the node identity; pass `None` if it is unknown.
+pub fn audit_key_hygiene(
+    vote_state: &VoteState,
+    fee_payer: Option<&Pubkey>,
+) -> Vec<KeyHygieneWarning> {
+    let withdrawer = vote_state.authorized_withdrawer;
+    let mut warnings = vec![];
+    if vote_state
+        .authorized_voters()
+        .iter()
+        .any(|(_, authorized_voter)| *authorized_voter == withdrawer)
+    {
+        warnings.push(KeyHygieneWarning::VoterIsWithdrawer(withdrawer));
+    }
+    if vote_state.node_pubkey == withdrawer {
+        warnings.push(KeyHygieneWarning::WithdrawerIsNodeIdentity(withdrawer));
+    }
+    if fee_payer == Some(&withdrawer) {
+        warnings.push(KeyHygieneWarning::WithdrawerIsFeePayer(withdrawer));
+    }
+    warnings
+}
@@ -0,0 +1,66 @@
+//! Client helpers for `UpdateValidatorIdentity`.
+
+use {
+    crate::signers::is_signer,
+    solana_program::{
+        instruction::Instruction,
+        pubkey::Pubkey,
+        vote::{instruction as vote_instruction, state::VoteState},
+    },
+    thiserror::Error,
+};
+
+/// Reasons an identity update would fail.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum IdentityUpdateError {
+    #[error("new validator identity {0} must sign the update")]
+    MissingIdentitySignature(Pubkey),
+    #[error("authorized withdrawer {0} must sign the update")]
+    MissingWithdrawerSignature(Pubkey),
+    #[error("vote account withdrawer is {actual}, not {expected}")]
+    WithdrawerMismatch { expected: Pubkey, actual: Pubkey },
+}
+
+/// Builds `UpdateValidatorIdentity` after checking that both the new
+/// identity and the withdrawer are among the transaction's `signers`.
+///
+/// The program requires the new identity to sign, which is easy to miss
+/// when the identity keypair lives on the validator host.
+pub fn update_validator_identity(
+    vote_pubkey: &Pubkey,
+    authorized_withdrawer: &Pubkey,
+    node_pubkey: &Pubkey,
+    signers: &[Pubkey],
+) -> Result<Instruction, IdentityUpdateError> {
+    if !is_signer(node_pubkey, signers) {
+        return Err(IdentityUpdateError::MissingIdentitySignature(*node_pubkey));
+    }
+    if !is_signer(authorized_withdrawer, signers) {
+        return Err(IdentityUpdateError::MissingWithdrawerSignature(
+            *authorized_withdrawer,
+        ));
+    }
+    Ok(vote_instruction::update_validator_identity(
+        vote_pubkey,
+        authorized_withdrawer,
+        node_pubkey,
+    ))
+}
+
+/// Like [`update_validator_identity`], also checking `authorized_withdrawer`
+/// against the account's current `vote_state`.
+pub fn update_validator_identity_checked(
+    vote_pubkey: &Pubkey,
+    vote_state: &VoteState,
+    authorized_withdrawer: &Pubkey,
+    node_pubkey: &Pubkey,
+    signers: &[Pubkey],
+) -> Result<Instruction, IdentityUpdateError> {
+    if vote_state.authorized_withdrawer != *authorized_withdrawer {
+        return Err(IdentityUpdateError::WithdrawerMismatch {
+            expected: *authorized_withdrawer,
+            actual: vote_state.authorized_withdrawer,
+        });
+    }
+    update_validator_identity(vote_pubkey, authorized_withdrawer, node_pubkey, signers)
+}
@@ -0,0 +1,230 @@
+//! Per-instruction metadata for `VoteInstruction`.
+
+use solana_program::{pubkey::Pubkey, sysvar, vote::instruction::VoteInstruction};
+
+/// Authority whose signature an instruction checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequiredAuthority {
+    /// The validator identity being set.
+    NodePubkey,
+    /// The authorized voter for the current epoch.
+    Voter,
+    /// The authorized withdrawer.
+    Withdrawer,
+    /// The authorized voter or the withdrawer when changing the voter, the
+    /// withdrawer when changing the withdrawer.
+    VoterOrWithdrawer,
+}
+
+/// Sysvar read by an instruction handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequiredSysvar {
+    Clock,
+    Rent,
+    SlotHashes,
+    EpochSchedule,
+}
+
+impl RequiredSysvar {
+    pub fn id(self) -> Pubkey {
+        match self {
+            Self::Clock => sysvar::clock::id(),
+            Self::Rent => sysvar::rent::id(),
+            Self::SlotHashes => sysvar::slot_hashes::id(),
+            Self::EpochSchedule => sysvar::epoch_schedule::id(),
+        }
+    }
+}
+
+/// Static facts about one `VoteInstruction` variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstructionMeta {
+    /// Serialized variant index.
+    pub tag: u32,
+    pub name: &'static str,
+    /// Accounts listed in the variant's documentation.
+    pub min_accounts: usize,
+    pub authority: RequiredAuthority,
+    /// Runtime feature the instruction was introduced behind, if any.
+    pub feature_gate: Option<&'static str>,
+}
+
+impl InstructionMeta {
+    const fn new(
+        tag: u32,
+        name: &'static str,
+        min_accounts: usize,
+        authority: RequiredAuthority,
+        feature_gate: Option<&'static str>,
+    ) -> Self {
+        Self {
+            tag,
+            name,
+            min_accounts,
+            authority,
+            feature_gate,
+        }
+    }
+
+    /// Metadata for serialized variant index `tag`.
+    pub fn from_tag(tag: u32) -> Option<&'static Self> {
+        INSTRUCTIONS.get(usize::try_from(tag).ok()?)
+    }
+
+    /// Metadata for serialized instruction data, read from its tag without
+    /// decoding the payload.
+    pub fn from_data(instruction_data: &[u8]) -> Option<&'static Self> {
+        let tag = instruction_data
+            .get(..4)
+            .and_then(|tag| tag.try_into().ok())
+            .map(u32::from_le_bytes)?;
+        Self::from_tag(tag)
+    }
+
+    pub fn of(instruction: &VoteInstruction) -> &'static Self {
+        let tag = match instruction {
+            VoteInstruction::InitializeAccount(_) => 0,
+            VoteInstruction::Authorize(..) => 1,
+            VoteInstruction::Vote(_) => 2,
+            VoteInstruction::Withdraw(_) => 3,
+            VoteInstruction::UpdateValidatorIdentity => 4,
+            VoteInstruction::UpdateCommission(_) => 5,
+            VoteInstruction::VoteSwitch(..) => 6,
+            VoteInstruction::AuthorizeChecked(_) => 7,
+            VoteInstruction::UpdateVoteState(_) => 8,
+            VoteInstruction::UpdateVoteStateSwitch(..) => 9,
+            VoteInstruction::AuthorizeWithSeed(_) => 10,
+            VoteInstruction::AuthorizeCheckedWithSeed(_) => 11,
+            VoteInstruction::CompactUpdateVoteState(_) => 12,
+            VoteInstruction::CompactUpdateVoteStateSwitch(..) => 13,
+        };
+        &INSTRUCTIONS[tag]
+    }
+
+    /// Sysvars the handler reads, whether passed as accounts or fetched
+    /// with `get`.
+    ///
+    /// Every handler that writes the vote state also consults `Rent` through
+    /// the runtime when deciding whether to grow the account; that use is
+    /// not listed.
+    pub fn required_sysvars(&self) -> &'static [RequiredSysvar] {
+        use RequiredSysvar::*;
+        match self.tag {
+            // InitializeAccount
+            0 => &[Rent, Clock],
+            // Vote, VoteSwitch, UpdateVoteState and the compact variants
+            2 | 6 | 8 | 9 | 12 | 13 => &[SlotHashes, Clock],
+            // Withdraw
+            3 => &[Rent, Clock],
+            // UpdateCommission
+            5 => &[EpochSchedule, Clock],
+            // The Authorize variants
+            1 | 7 | 10 | 11 => &[Clock],
+            _ => &[],
+        }
+    }
+
+    /// Whether `deprecate_legacy_vote_ixs` disables the instruction.
+    pub fn is_deprecated(&self) -> bool {
+        self.replacement().is_some()
+    }
+
+    /// Name of the instruction that replaces a deprecated one.
+    ///
+    /// The replacements are newer than the solana-program version this
+    /// crate builds against, so they have no [`InstructionMeta`] here.
+    pub fn replacement(&self) -> Option<&'static str> {
+        match self.tag {
+            2 | 8 | 12 => Some("TowerSync"),
+            6 | 9 | 13 => Some("TowerSyncSwitch"),
+            _ => None,
+        }
+    }
+}
+
+/// [`InstructionMeta`] facts on a decoded instruction.
+pub trait VoteInstructionExt {
+    /// See [`InstructionMeta::is_deprecated`].
+    fn is_deprecated(&self) -> bool;
+    /// See [`InstructionMeta::replacement`].
+    fn replacement(&self) -> Option<&'static str>;
+    /// See [`InstructionMeta::required_sysvars`].
+    fn required_sysvars(&self) -> &'static [RequiredSysvar];
+}
+
+impl VoteInstructionExt for VoteInstruction {
+    fn is_deprecated(&self) -> bool {
+        InstructionMeta::of(self).is_deprecated()
+    }
+
+    fn replacement(&self) -> Option<&'static str> {
+        InstructionMeta::of(self).replacement()
+    }
+
+    fn required_sysvars(&self) -> &'static [RequiredSysvar] {
+        InstructionMeta::of(self).required_sysvars()
+    }
+}
+
+/// Metadata for every `VoteInstruction` variant, indexed by tag.
+pub const INSTRUCTIONS: [InstructionMeta; 14] = {
+    use RequiredAuthority::*;
+    [
+        InstructionMeta::new(0, "InitializeAccount", 4, NodePubkey, None),
+        InstructionMeta::new(1, "Authorize", 3, VoterOrWithdrawer, None),
+        InstructionMeta::new(2, "Vote", 4, Voter, None),
+        InstructionMeta::new(3, "Withdraw", 3, Withdrawer, None),
+        InstructionMeta::new(4, "UpdateValidatorIdentity", 3, Withdrawer, None),
+        InstructionMeta::new(5, "UpdateCommission", 2, Withdrawer, None),
+        InstructionMeta::new(6, "VoteSwitch", 4, Voter, None),
+        InstructionMeta::new(
+            7,
+            "AuthorizeChecked",
+            4,
+            VoterOrWithdrawer,
+            Some("vote_stake_checked_instructions"),
+        ),
+        InstructionMeta::new(
+            8,
+            "UpdateVoteState",
+            2,
+            Voter,
+            Some("allow_votes_to_directly_update_vote_state"),
+        ),
+        InstructionMeta::new(
+            9,
+            "UpdateVoteStateSwitch",
+            2,
+            Voter,
+            Some("allow_votes_to_directly_update_vote_state"),
+        ),
+        InstructionMeta::new(
+            10,
+            "AuthorizeWithSeed",
+            3,
+            VoterOrWithdrawer,
+            Some("vote_authorize_with_seed"),
+        ),
+        InstructionMeta::new(
+            11,
+            "AuthorizeCheckedWithSeed",
+            4,
+            VoterOrWithdrawer,
+            Some("vote_authorize_with_seed"),
+        ),
+        InstructionMeta::new(
+            12,
+            "CompactUpdateVoteState",
+            2,
+            Voter,
+            Some("compact_vote_state_updates"),
+        ),
+        InstructionMeta::new(
+            13,
+            "CompactUpdateVoteStateSwitch",
+            2,
+            Voter,
+            Some("compact_vote_state_updates"),
+        ),
+    ]
+};
@@ -0,0 +1,323 @@
+//! Vote account data layouts the program can write.
+//!
+//! States are written by hand rather than through bincode, whose generic
+//! serializer dominates the program's ELF size. The bytes are identical
+//! to `bincode::serialize` of the matching `VoteStateVersions` variant.
+
+use {
+    crate::versioned::Versioned,
+    serde::Serialize,
+    solana_program::{
+        clock::{Epoch, Slot},
+        epoch_schedule::MAX_LEADER_SCHEDULE_EPOCH_OFFSET,
+        instruction::InstructionError,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+        vote::state::{
+            Lockout, VoteState, VoteState1_14_11, VoteStateVersions, MAX_EPOCH_CREDITS_HISTORY,
+            MAX_LOCKOUT_HISTORY,
+        },
+    },
+};
+
+/// `VoteStateVersions` tag of the 1.14.11 layout.
+const V1_14_11_TAG: u32 = 1;
+/// Entries in the `prior_voters` circular buffer.
+pub(crate) const PRIOR_VOTERS_LEN: usize = 32;
+/// Most authorized voters the program leaves in an account: the current
+/// epoch's and one pending for each epoch up to the leader schedule epoch,
+/// as `VoteState::size_of` assumes. The sanitizer tolerates up to
+/// [`MAX_AUTHORIZED_VOTERS`](crate::sanitize::MAX_AUTHORIZED_VOTERS).
+const MAX_PENDING_AUTHORIZED_VOTERS: usize = MAX_LEADER_SCHEDULE_EPOCH_OFFSET as usize + 1;
+
+/// Layout to serialize a `VoteState` into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoteStateTargetVersion {
+    /// The 1.14.11 layout, written to accounts too small for the current
+    /// one. Vote latencies are dropped.
+    V1_14_11,
+    #[default]
+    Current,
+}
+
+impl VoteStateTargetVersion {
+    /// Serialized size of a vote state in this layout.
+    pub fn size_of(self) -> usize {
+        match self {
+            Self::V1_14_11 => VoteState1_14_11::size_of(),
+            Self::Current => VoteState::size_of(),
+        }
+    }
+}
+
+/// Largest encoding of a vote state in the `version` layout: a full tower,
+/// a full credit history and [`MAX_PENDING_AUTHORIZED_VOTERS`] authorized
+/// voters.
+///
+/// Equal to [`VoteStateTargetVersion::size_of`], the account size the
+/// program allocates.
+pub fn max_serialized_size(version: VoteStateTargetVersion) -> usize {
+    let lockout = 8 + 4;
+    let vote = match version {
+        VoteStateTargetVersion::V1_14_11 => lockout,
+        VoteStateTargetVersion::Current => 1 + lockout,
+    };
+    let tag = 4;
+    let header = PUBKEY_BYTES * 2 + 1;
+    let votes = 8 + MAX_LOCKOUT_HISTORY * vote;
+    let root_slot = 1 + 8;
+    let authorized_voters = 8 + MAX_PENDING_AUTHORIZED_VOTERS * (8 + PUBKEY_BYTES);
+    let prior_voters = PRIOR_VOTERS_LEN * (PUBKEY_BYTES + 8 + 8) + 8 + 1;
+    let epoch_credits = 8 + MAX_EPOCH_CREDITS_HISTORY * (8 + 8 + 8);
+    let last_timestamp = 8 + 8;
+    tag + header
+        + votes
+        + root_slot
+        + authorized_voters
+        + prior_voters
+        + epoch_credits
+        + last_timestamp
+}
+
+/// Serializes `vote_state` into `data` in the `version` layout.
+///
+/// `vote_state` is borrowed mutably only because this SDK version reads
+/// `prior_voters` through `&mut self`; it is left unchanged. Fails with
+/// `AccountDataTooSmall` if `data` cannot hold it.
+pub fn serialize_as(
+    version: VoteStateTargetVersion,
+    vote_state: &mut VoteState,
+    data: &mut [u8],
+) -> Result<(), InstructionError> {
+    let mut writer = Writer { data, offset: 0 };
+    match version {
+        VoteStateTargetVersion::V1_14_11 => {
+            writer.u32(V1_14_11_TAG)?;
+            writer.header(vote_state)?;
+            writer.len(vote_state.votes.len())?;
+            for landed_vote in &vote_state.votes {
+                writer.lockout(&landed_vote.lockout)?;
+            }
+        }
+        VoteStateTargetVersion::Current => {
+            writer.u32(VoteStateVersions::CURRENT_TAG)?;
+            writer.header(vote_state)?;
+            writer.len(vote_state.votes.len())?;
+            for landed_vote in &vote_state.votes {
+                writer.bytes(&[landed_vote.latency])?;
+                writer.lockout(&landed_vote.lockout)?;
+            }
+        }
+    }
+    writer.trailer(vote_state)
+}
+
+/// Little-endian writer over account data, mirroring bincode's fixed-width
+/// integer encoding.
+struct Writer<'a> {
+    data: &'a mut [u8],
+    offset: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), InstructionError> {
+        let end = self
+            .offset
+            .checked_add(bytes.len())
+            .filter(|end| *end <= self.data.len())
+            .ok_or(InstructionError::AccountDataTooSmall)?;
+        self.data[self.offset..end].copy_from_slice(bytes);
+        self.offset = end;
+        Ok(())
+    }
+
+    fn u32(&mut self, value: u32) -> Result<(), InstructionError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn u64(&mut self, value: u64) -> Result<(), InstructionError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn len(&mut self, len: usize) -> Result<(), InstructionError> {
+        self.u64(len as u64)
+    }
+
+    fn pubkey(&mut self, pubkey: &Pubkey) -> Result<(), InstructionError> {
+        self.bytes(pubkey.as_ref())
+    }
+
+    fn lockout(&mut self, lockout: &Lockout) -> Result<(), InstructionError> {
+        self.u64(lockout.slot())?;
+        self.u32(lockout.confirmation_count())
+    }
+
+    fn option_slot(&mut self, slot: Option<Slot>) -> Result<(), InstructionError> {
+        match slot {
+            Some(slot) => {
+                self.bytes(&[1])?;
+                self.u64(slot)
+            }
+            None => self.bytes(&[0]),
+        }
+    }
+
+    /// Fields before the votes, shared by both layouts.
+    fn header(&mut self, vote_state: &VoteState) -> Result<(), InstructionError> {
+        self.pubkey(&vote_state.node_pubkey)?;
+        self.pubkey(&vote_state.authorized_withdrawer)?;
+        self.bytes(&[vote_state.commission])
+    }
+
+    /// Fields after the votes, shared by both layouts.
+    fn trailer(&mut self, vote_state: &mut VoteState) -> Result<(), InstructionError> {
+        self.option_slot(vote_state.root_slot)?;
+
+        let authorized_voters = vote_state.authorized_voters();
+        self.len(authorized_voters.len())?;
+        for (epoch, authorized_voter) in authorized_voters.iter() {
+            self.u64(*epoch)?;
+            self.pubkey(authorized_voter)?;
+        }
+
+        let prior_voters = PriorVotersRing::of(vote_state);
+        for (pubkey, start_epoch, end_epoch) in &prior_voters.buf {
+            self.pubkey(pubkey)?;
+            self.u64(*start_epoch)?;
+            self.u64(*end_epoch)?;
+        }
+        self.len(prior_voters.idx)?;
+        self.bytes(&[u8::from(prior_voters.is_empty)])?;
+
+        self.len(vote_state.epoch_credits.len())?;
+        for (epoch, credits, prev_credits) in &vote_state.epoch_credits {
+            self.u64(*epoch)?;
+            self.u64(*credits)?;
+            self.u64(*prev_credits)?;
+        }
+
+        self.u64(vote_state.last_timestamp.slot)?;
+        self.bytes(&vote_state.last_timestamp.timestamp.to_le_bytes())
+    }
+}
+
+/// The prior voters `CircBuf` of a vote state, with the fields it keeps
+/// private. Serializes as the `CircBuf` does.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct PriorVotersRing {
+    pub(crate) buf: [(Pubkey, Epoch, Epoch); PRIOR_VOTERS_LEN],
+    pub(crate) idx: usize,
+    pub(crate) is_empty: bool,
+}
+
+impl PriorVotersRing {
+    /// Reads the ring of `vote_state`.
+    ///
+    /// The index is recovered from the position of `last()`. An empty ring
+    /// gets the index a fresh one starts at, which is the only empty ring
+    /// the program produces. `prior_voters` takes `&mut self` in this SDK
+    /// version, but does not modify the state.
+    pub(crate) fn of(vote_state: &mut VoteState) -> Self {
+        let prior_voters = vote_state.prior_voters();
+        let last = prior_voters.last();
+        let idx = last
+            .and_then(|last| {
+                prior_voters
+                    .buf()
+                    .iter()
+                    .position(|item| std::ptr::eq(item, last))
+            })
+            .unwrap_or(PRIOR_VOTERS_LEN - 1);
+        Self {
+            buf: *prior_voters.buf(),
+            idx,
+            is_empty: last.is_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, initialized_vote_state, populated_vote_state},
+    };
+
+    fn bincode_layout(version: VoteStateTargetVersion, vote_state: &VoteState) -> Vec<u8> {
+        let versions = match version {
+            VoteStateTargetVersion::V1_14_11 => {
+                VoteStateVersions::V1_14_11(Box::new(vote_state.clone().into()))
+            }
+            VoteStateTargetVersion::Current => {
+                VoteStateVersions::Current(Box::new(vote_state.clone()))
+            }
+        };
+        bincode::serialize(&versions).unwrap()
+    }
+
+    fn assert_matches_bincode(vote_state: &VoteState) {
+        for version in [
+            VoteStateTargetVersion::V1_14_11,
+            VoteStateTargetVersion::Current,
+        ] {
+            let expected = bincode_layout(version, vote_state);
+            let mut data = vec![0; version.size_of()];
+            let mut written = vote_state.clone();
+            serialize_as(version, &mut written, &mut data).unwrap();
+            assert_eq!(written, *vote_state);
+            assert_eq!(data[..expected.len()], expected, "{version:?}");
+            assert!(data[expected.len()..].iter().all(|byte| *byte == 0));
+        }
+    }
+
+    #[test]
+    fn test_empty_tower_matches_bincode() {
+        assert_matches_bincode(&initialized_vote_state());
+    }
+
+    #[test]
+    fn test_full_tower_matches_bincode() {
+        let vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, MAX_EPOCH_CREDITS_HISTORY, 1);
+        assert_eq!(vote_state.votes.len(), MAX_LOCKOUT_HISTORY);
+        assert_matches_bincode(&vote_state);
+    }
+
+    #[test]
+    fn test_wrapped_prior_voters_match_bincode() {
+        let mut vote_state = populated_vote_state(4, 4, PRIOR_VOTERS_LEN as u8 + 5);
+        let prior_voters = PriorVotersRing::of(&mut vote_state);
+        assert!(!prior_voters.is_empty);
+        assert_eq!(prior_voters.idx, 4);
+        assert_matches_bincode(&vote_state);
+    }
+
+    #[test]
+    fn test_pending_authorized_voters_match_bincode() {
+        let mut vote_state = populated_vote_state(8, 4, 3);
+        let (&epoch, _) = vote_state.authorized_voters().last().unwrap();
+        for offset in 1..MAX_PENDING_AUTHORIZED_VOTERS as u64 {
+            vote_state
+                .set_new_authorized_voter(
+                    &fixture_pubkey(200 + offset as u8),
+                    epoch,
+                    epoch + offset,
+                    |_| Ok(()),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            vote_state.authorized_voters().len(),
+            MAX_PENDING_AUTHORIZED_VOTERS
+        );
+        assert_matches_bincode(&vote_state);
+    }
+
+    #[test]
+    fn test_max_serialized_size_is_account_size() {
+        for version in [
+            VoteStateTargetVersion::V1_14_11,
+            VoteStateTargetVersion::Current,
+        ] {
+            assert_eq!(max_serialized_size(version), version.size_of());
+        }
+    }
+}
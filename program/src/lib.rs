@@ -1,8 +1,77 @@
 //! Vote Program.
 
+#![forbid(unsafe_code)]
+
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+#[cfg(feature = "rkyv")]
+pub mod archive;
+mod authority;
+pub mod authorized_voters;
+pub mod codec;
+pub mod content_hash;
+pub mod credits;
+pub mod decode;
+pub mod delta;
+pub mod display;
 #[cfg(all(target_os = "solana", feature = "bpf-entrypoint"))]
 mod entrypoint;
+pub mod epochs;
+pub mod errors;
+pub mod explain;
+pub mod filters;
+pub mod generator;
+pub mod governance;
+pub mod hygiene;
+pub mod identity;
+pub mod instruction;
+pub mod layout;
+pub mod lockouts;
+pub mod normalize;
+pub mod packet;
+pub mod parsed;
+pub mod predict;
+pub mod preflight;
 pub mod processor;
+pub mod registry;
+pub mod replay;
+pub mod rewards;
+pub mod rotation;
+#[cfg(feature = "rows")]
+pub mod rows;
+pub mod rpc;
+pub mod sanitize;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod scoring;
+pub mod seeds;
+pub mod signers;
+pub mod simulate;
+pub mod snapshot;
+pub mod stats;
+pub mod storage;
+pub mod switching;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod timestamp;
+pub mod tower;
+pub mod transfer;
+mod varint;
+pub mod verify;
+pub mod versioned;
+mod wire;
+pub mod withdraw;
+
+/// The program's instruction processor, with the signature
+/// `solana-program-test`'s `processor!` expects, so tests can register the
+/// program natively instead of loading the BPF build.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    processor::process(program_id, accounts, instruction_data)
+}
 
 // [Core BPF]: TODO: Program-test will not overwrite existing built-ins.
 // See https://github.com/solana-labs/solana/pull/35233.
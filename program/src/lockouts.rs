@@ -0,0 +1,212 @@
+//! Lockout expiry and violation checks over a vote tower.
+
+use {
+    crate::delta::{DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING, TIMELY_VOTE_CREDITS},
+    serde::{Deserialize, Serialize},
+    solana_program::{
+        clock::Slot,
+        vote::state::{Lockout, VoteState},
+    },
+    std::collections::HashSet,
+};
+
+/// When one tower lockout expires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockoutExpiry {
+    pub slot: Slot,
+    pub confirmation_count: u32,
+    /// Last slot the lockout still covers; votes on other forks are allowed
+    /// from the next slot on.
+    pub last_locked_out_slot: Slot,
+}
+
+impl From<&Lockout> for LockoutExpiry {
+    fn from(lockout: &Lockout) -> Self {
+        Self {
+            slot: lockout.slot(),
+            confirmation_count: lockout.confirmation_count(),
+            last_locked_out_slot: lockout.last_locked_out_slot(),
+        }
+    }
+}
+
+/// Expiry of every lockout in the tower, oldest vote first.
+pub fn lockout_calendar(vote_state: &VoteState) -> Vec<LockoutExpiry> {
+    vote_state
+        .votes
+        .iter()
+        .map(|landed_vote| LockoutExpiry::from(&landed_vote.lockout))
+        .collect()
+}
+
+/// Lockout state of one tower slot, for display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TowerSlotSummary {
+    pub slot: Slot,
+    pub confirmation_count: u32,
+    /// Last slot the lockout covers.
+    pub expiry_slot: Slot,
+}
+
+/// Compact, serializable view of a tower for dashboards.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TowerSummary {
+    pub root_slot: Option<Slot>,
+    /// Oldest vote first.
+    pub slots: Vec<TowerSlotSummary>,
+    /// Inclusive `(start, end)` slot ranges covered by at least one lockout,
+    /// merged and in increasing order.
+    pub locked_ranges: Vec<(Slot, Slot)>,
+}
+
+/// Summarizes the tower of `vote_state`.
+pub fn tower_summary(vote_state: &VoteState) -> TowerSummary {
+    let slots: Vec<TowerSlotSummary> = lockout_calendar(vote_state)
+        .into_iter()
+        .map(|expiry| TowerSlotSummary {
+            slot: expiry.slot,
+            confirmation_count: expiry.confirmation_count,
+            expiry_slot: expiry.last_locked_out_slot,
+        })
+        .collect();
+    let mut locked_ranges: Vec<(Slot, Slot)> = vec![];
+    for summary in &slots {
+        match locked_ranges.last_mut() {
+            Some((_, end)) if summary.slot <= end.saturating_add(1) => {
+                *end = (*end).max(summary.expiry_slot);
+            }
+            _ => locked_ranges.push((summary.slot, summary.expiry_slot)),
+        }
+    }
+    TowerSummary {
+        root_slot: vote_state.root_slot,
+        slots,
+        locked_ranges,
+    }
+}
+
+/// Tower slots whose lockouts forbid voting on `candidate_slot`, given the
+/// candidate's `ancestors`.
+///
+/// Mirrors the tower's own check: the candidate vote is applied first, so
+/// lockouts it expires are not reported. A candidate at or below the last
+/// voted slot is rejected outright and reports the last voted slot.
+pub fn violated_lockouts(
+    vote_state: &VoteState,
+    candidate_slot: Slot,
+    ancestors: &HashSet<Slot>,
+) -> Vec<Slot> {
+    if let Some(last_voted_slot) = vote_state.last_voted_slot() {
+        if candidate_slot <= last_voted_slot {
+            return vec![last_voted_slot];
+        }
+    }
+    let mut vote_state = vote_state.clone();
+    vote_state.process_next_vote_slot(
+        candidate_slot,
+        0,
+        0,
+        TIMELY_VOTE_CREDITS,
+        DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+    );
+    vote_state
+        .votes
+        .iter()
+        .map(|landed_vote| landed_vote.slot())
+        .chain(vote_state.root_slot)
+        .filter(|slot| *slot != candidate_slot && !ancestors.contains(slot))
+        .collect()
+}
+
+/// Returns whether voting on `candidate_slot` would violate any lockout.
+pub fn is_locked_out(
+    vote_state: &VoteState,
+    candidate_slot: Slot,
+    ancestors: &HashSet<Slot>,
+) -> bool {
+    !violated_lockouts(vote_state, candidate_slot, ancestors).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{initialized_vote_state, VoteStateBuilder},
+        solana_program::vote::state::LandedVote,
+    };
+
+    #[test]
+    fn test_tower_summary() {
+        let vote_state = VoteStateBuilder::new()
+            .vote(1, 1)
+            .vote(2, 1)
+            .vote(3, 1)
+            .build();
+        let summary = tower_summary(&vote_state);
+        assert_eq!(summary.root_slot, None);
+        assert_eq!(
+            summary.slots,
+            [
+                TowerSlotSummary {
+                    slot: 1,
+                    confirmation_count: 3,
+                    expiry_slot: 9,
+                },
+                TowerSlotSummary {
+                    slot: 2,
+                    confirmation_count: 2,
+                    expiry_slot: 6,
+                },
+                TowerSlotSummary {
+                    slot: 3,
+                    confirmation_count: 1,
+                    expiry_slot: 5,
+                },
+            ]
+        );
+        assert_eq!(summary.locked_ranges, [(1, 9)]);
+        assert_eq!(
+            tower_summary(&initialized_vote_state()),
+            TowerSummary::default()
+        );
+    }
+
+    #[test]
+    fn test_locked_ranges_merge() {
+        let mut vote_state = initialized_vote_state();
+        vote_state.root_slot = Some(1);
+        // Ranges (10, 14), (15, 17) touch and merge; (20, 22) stands apart.
+        vote_state.votes = [(10, 2), (15, 1), (20, 1)]
+            .into_iter()
+            .map(|(slot, confirmation_count)| {
+                LandedVote::from(Lockout::new_with_confirmation_count(
+                    slot,
+                    confirmation_count,
+                ))
+            })
+            .collect();
+        let summary = tower_summary(&vote_state);
+        assert_eq!(summary.root_slot, Some(1));
+        assert_eq!(summary.locked_ranges, [(10, 17), (20, 22)]);
+    }
+
+    #[test]
+    fn test_summary_serializes_camel_case() {
+        let vote_state = VoteStateBuilder::new().vote(7, 1).build();
+        let json = serde_json::to_value(tower_summary(&vote_state)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "rootSlot": null,
+                "slots": [{"slot": 7, "confirmationCount": 1, "expirySlot": 9}],
+                "lockedRanges": [[7, 9]],
+            })
+        );
+        let round_trip: TowerSummary = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, tower_summary(&vote_state));
+    }
+}
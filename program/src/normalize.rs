@@ -0,0 +1,146 @@
+//! Canonical ordering for proposed towers.
+//!
+//! `TowerSync` is not available in the solana-program version this crate
+//! builds against, so normalization applies to `VoteStateUpdate`, which
+//! carries the same lockouts.
+
+use {
+    solana_program::{
+        clock::Slot,
+        vote::{
+            error::VoteError,
+            state::{Lockout, VoteStateUpdate},
+        },
+    },
+    std::collections::VecDeque,
+};
+
+/// What [`NormalizeTower::normalize`] changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TowerNormalization {
+    /// Whether lockouts were out of slot order.
+    pub reordered: bool,
+    /// Slots that appeared more than once; the entry with the highest
+    /// confirmation count was kept.
+    pub removed_duplicates: Vec<Slot>,
+}
+
+impl TowerNormalization {
+    /// Returns whether the tower was already canonical.
+    pub fn is_unchanged(&self) -> bool {
+        !self.reordered && self.removed_duplicates.is_empty()
+    }
+}
+
+/// Puts a proposed tower in the canonical form the program expects.
+pub trait NormalizeTower {
+    /// Sorts lockouts by slot and removes duplicate slots, then checks that
+    /// confirmation counts strictly decrease from oldest to newest.
+    ///
+    /// On error the lockouts are left sorted and deduplicated.
+    fn normalize(&mut self) -> Result<TowerNormalization, VoteError>;
+}
+
+impl NormalizeTower for VoteStateUpdate {
+    fn normalize(&mut self) -> Result<TowerNormalization, VoteError> {
+        let mut normalization = TowerNormalization {
+            reordered: self
+                .lockouts
+                .iter()
+                .zip(self.lockouts.iter().skip(1))
+                .any(|(older, newer)| older.slot() > newer.slot()),
+            ..TowerNormalization::default()
+        };
+        let mut lockouts = Vec::from(std::mem::take(&mut self.lockouts));
+        // Highest confirmation count first within a slot, so dedup keeps it.
+        lockouts.sort_by(|a, b| {
+            a.slot()
+                .cmp(&b.slot())
+                .then(b.confirmation_count().cmp(&a.confirmation_count()))
+        });
+        let mut deduped: VecDeque<Lockout> = VecDeque::with_capacity(lockouts.len());
+        for lockout in lockouts {
+            if deduped
+                .back()
+                .is_some_and(|last| last.slot() == lockout.slot())
+            {
+                normalization.removed_duplicates.push(lockout.slot());
+            } else {
+                deduped.push_back(lockout);
+            }
+        }
+        self.lockouts = deduped;
+
+        if self
+            .lockouts
+            .iter()
+            .zip(self.lockouts.iter().skip(1))
+            .any(|(older, newer)| older.confirmation_count() <= newer.confirmation_count())
+        {
+            return Err(VoteError::ConfirmationsNotOrdered);
+        }
+        Ok(normalization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lockouts(update: &VoteStateUpdate) -> Vec<(Slot, u32)> {
+        update
+            .lockouts
+            .iter()
+            .map(|lockout| (lockout.slot(), lockout.confirmation_count()))
+            .collect()
+    }
+
+    #[test]
+    fn test_canonical_unchanged() {
+        let mut update = VoteStateUpdate::from(vec![(2, 3), (4, 2), (5, 1)]);
+        assert!(update.normalize().unwrap().is_unchanged());
+        assert_eq!(lockouts(&update), [(2, 3), (4, 2), (5, 1)]);
+    }
+
+    #[test]
+    fn test_sorts() {
+        let mut update = VoteStateUpdate::from(vec![(5, 1), (2, 3), (4, 2)]);
+        assert_eq!(
+            update.normalize(),
+            Ok(TowerNormalization {
+                reordered: true,
+                removed_duplicates: vec![],
+            })
+        );
+        assert_eq!(lockouts(&update), [(2, 3), (4, 2), (5, 1)]);
+    }
+
+    #[test]
+    fn test_dedups_keeping_highest_confirmation() {
+        let mut update = VoteStateUpdate::from(vec![(2, 3), (4, 1), (4, 2), (5, 1), (4, 1)]);
+        assert_eq!(
+            update.normalize(),
+            Ok(TowerNormalization {
+                reordered: true,
+                removed_duplicates: vec![4, 4],
+            })
+        );
+        assert_eq!(lockouts(&update), [(2, 3), (4, 2), (5, 1)]);
+    }
+
+    #[test]
+    fn test_confirmations_not_ordered() {
+        let mut update = VoteStateUpdate::from(vec![(4, 2), (2, 2)]);
+        assert_eq!(update.normalize(), Err(VoteError::ConfirmationsNotOrdered));
+        // Left sorted even on error.
+        assert_eq!(lockouts(&update), [(2, 2), (4, 2)]);
+        let mut update = VoteStateUpdate::from(vec![(2, 1), (4, 2)]);
+        assert_eq!(update.normalize(), Err(VoteError::ConfirmationsNotOrdered));
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut update = VoteStateUpdate::from(vec![]);
+        assert!(update.normalize().unwrap().is_unchanged());
+    }
+}
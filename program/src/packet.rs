@@ -0,0 +1,131 @@
+//! Packet size checks for signed vote transactions.
+//!
+//! A tower near `MAX_LOCKOUT_HISTORY` entries plus a switch proof hash can
+//! push a vote transaction past `PACKET_DATA_SIZE`, and oversized
+//! transactions are dropped without an error reaching the sender.
+
+use {
+    crate::{
+        varint,
+        verify::{PACKET_DATA_SIZE, SIGNATURE_BYTES},
+    },
+    solana_program::{
+        hash::Hash,
+        instruction::Instruction,
+        message::{Message, VersionedMessage},
+        pubkey::Pubkey,
+    },
+    thiserror::Error,
+};
+
+/// A signed transaction too large for one packet, by this many bytes.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("signed transaction exceeds the packet size by {0} bytes")]
+pub struct OversizeBy(pub usize);
+
+/// Wire size of the transaction carrying `message` once every required
+/// signature is attached.
+pub fn signed_transaction_size(message: &VersionedMessage) -> usize {
+    let num_signatures = usize::from(message.header().num_required_signatures);
+    let mut signatures_len = vec![];
+    varint::encode_short_u16_len(num_signatures, &mut signatures_len);
+    signatures_len.len() + num_signatures * SIGNATURE_BYTES + message.serialize().len()
+}
+
+/// Checks that the transaction carrying `message`, fully signed, fits in
+/// one packet.
+pub fn fits_in_packet(message: &VersionedMessage) -> Result<(), OversizeBy> {
+    let size = signed_transaction_size(message);
+    let limit = PACKET_DATA_SIZE as usize;
+    if size > limit {
+        return Err(OversizeBy(size - limit));
+    }
+    Ok(())
+}
+
+/// Compiles vote `instructions` into a legacy message paid for by `payer`,
+/// refusing it if the signed transaction would not fit in a packet.
+pub fn vote_transaction_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    recent_blockhash: &Hash,
+) -> Result<VersionedMessage, OversizeBy> {
+    let message = VersionedMessage::Legacy(Message::new_with_blockhash(
+        instructions,
+        Some(payer),
+        recent_blockhash,
+    ));
+    fits_in_packet(&message)?;
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test_support::fixture_pubkey, solana_program::instruction::AccountMeta};
+
+    fn message_with_data(data_len: usize) -> VersionedMessage {
+        let instruction =
+            Instruction::new_with_bytes(fixture_pubkey(9), &vec![0; data_len], vec![]);
+        VersionedMessage::Legacy(Message::new(&[instruction], Some(&fixture_pubkey(1))))
+    }
+
+    #[test]
+    fn test_signed_transaction_size() {
+        let message = message_with_data(0);
+        assert_eq!(
+            signed_transaction_size(&message),
+            1 + SIGNATURE_BYTES + message.serialize().len()
+        );
+        let instruction = Instruction::new_with_bytes(
+            fixture_pubkey(9),
+            &[],
+            vec![AccountMeta::new_readonly(fixture_pubkey(2), true)],
+        );
+        let two_signers =
+            VersionedMessage::Legacy(Message::new(&[instruction], Some(&fixture_pubkey(1))));
+        assert_eq!(
+            signed_transaction_size(&two_signers),
+            1 + 2 * SIGNATURE_BYTES + two_signers.serialize().len()
+        );
+    }
+
+    #[test]
+    fn test_packet_boundary() {
+        let limit = PACKET_DATA_SIZE as usize;
+        let data_len = (0..limit)
+            .find(|data_len| signed_transaction_size(&message_with_data(*data_len)) == limit)
+            .expect("some data length fills the packet exactly");
+        assert_eq!(fits_in_packet(&message_with_data(data_len)), Ok(()));
+        assert_eq!(
+            fits_in_packet(&message_with_data(data_len + 1)),
+            Err(OversizeBy(1))
+        );
+    }
+
+    #[test]
+    fn test_vote_transaction_message() {
+        let payer = fixture_pubkey(1);
+        let blockhash = Hash::new_unique();
+        let instruction = Instruction::new_with_bytes(fixture_pubkey(9), &[1, 2, 3], vec![]);
+        let message =
+            vote_transaction_message(std::slice::from_ref(&instruction), &payer, &blockhash)
+                .unwrap();
+        assert_eq!(
+            message,
+            VersionedMessage::Legacy(Message::new_with_blockhash(
+                &[instruction],
+                Some(&payer),
+                &blockhash
+            ))
+        );
+        let oversized = Instruction::new_with_bytes(
+            fixture_pubkey(9),
+            &vec![0; PACKET_DATA_SIZE as usize],
+            vec![],
+        );
+        assert!(matches!(
+            vote_transaction_message(&[oversized], &payer, &blockhash),
+            Err(OversizeBy(_))
+        ));
+    }
+}
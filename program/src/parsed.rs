@@ -0,0 +1,498 @@
+//! `jsonParsed` vote instructions.
+//!
+//! These mirror the `{"type", "info"}` objects RPC returns for vote program
+//! instructions with the `jsonParsed` encoding, so indexers and alternative
+//! RPC implementations can produce and validate identical output.
+
+use {
+    serde::{Deserialize, Serialize},
+    solana_program::{
+        clock::{Slot, UnixTimestamp},
+        pubkey::Pubkey,
+        vote::{
+            instruction::VoteInstruction,
+            state::{Vote, VoteAuthorize, VoteStateUpdate},
+        },
+    },
+    thiserror::Error,
+};
+
+/// Reasons a vote instruction cannot be parsed.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ParseVoteInstructionError {
+    #[error("instruction has {actual} accounts, at least {expected} required")]
+    NotEnoughAccounts { expected: usize, actual: usize },
+}
+
+/// `VoteAuthorize`, as `"Voter"` or `"Withdrawer"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum UiVoteAuthorize {
+    Voter,
+    Withdrawer,
+}
+
+impl From<VoteAuthorize> for UiVoteAuthorize {
+    fn from(vote_authorize: VoteAuthorize) -> Self {
+        match vote_authorize {
+            VoteAuthorize::Voter => Self::Voter,
+            VoteAuthorize::Withdrawer => Self::Withdrawer,
+        }
+    }
+}
+
+/// A legacy `Vote`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UiVote {
+    pub slots: Vec<Slot>,
+    /// Bank hash, as a base-58 string.
+    pub hash: String,
+    pub timestamp: Option<UnixTimestamp>,
+}
+
+impl From<&Vote> for UiVote {
+    fn from(vote: &Vote) -> Self {
+        Self {
+            slots: vote.slots.clone(),
+            hash: vote.hash.to_string(),
+            timestamp: vote.timestamp,
+        }
+    }
+}
+
+/// One proposed tower entry.
+///
+/// Keys stay snake_case: RPC serializes the SDK's `Lockout` as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UiLockout {
+    pub slot: Slot,
+    pub confirmation_count: u32,
+}
+
+/// A `VoteStateUpdate`, in either encoding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UiVoteStateUpdate {
+    /// Oldest vote first.
+    pub lockouts: Vec<UiLockout>,
+    pub root: Option<Slot>,
+    /// Bank hash, as a base-58 string.
+    pub hash: String,
+    pub timestamp: Option<UnixTimestamp>,
+}
+
+impl From<&VoteStateUpdate> for UiVoteStateUpdate {
+    fn from(vote_state_update: &VoteStateUpdate) -> Self {
+        Self {
+            lockouts: vote_state_update
+                .lockouts
+                .iter()
+                .map(|lockout| UiLockout {
+                    slot: lockout.slot(),
+                    confirmation_count: lockout.confirmation_count(),
+                })
+                .collect(),
+            root: vote_state_update.root,
+            hash: vote_state_update.hash.to_string(),
+            timestamp: vote_state_update.timestamp,
+        }
+    }
+}
+
+/// A parsed vote instruction. Keys are base-58 strings.
+///
+/// The `type` names, including the lowercase vote state update ones, are
+/// the ones RPC emits.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "info")]
+pub enum ParsedVoteInstruction {
+    #[serde(rename = "initialize", rename_all = "camelCase")]
+    Initialize {
+        vote_account: String,
+        rent_sysvar: String,
+        clock_sysvar: String,
+        node: String,
+        authorized_voter: String,
+        authorized_withdrawer: String,
+        commission: u8,
+    },
+    #[serde(rename = "authorize", rename_all = "camelCase")]
+    Authorize {
+        vote_account: String,
+        clock_sysvar: String,
+        authority: String,
+        new_authority: String,
+        authority_type: UiVoteAuthorize,
+    },
+    #[serde(rename = "authorizeWithSeed", rename_all = "camelCase")]
+    AuthorizeWithSeed {
+        vote_account: String,
+        clock_sysvar: String,
+        authority_base_key: String,
+        authority_owner: String,
+        authority_seed: String,
+        authority_type: UiVoteAuthorize,
+        new_authority: String,
+    },
+    #[serde(rename = "authorizeCheckedWithSeed", rename_all = "camelCase")]
+    AuthorizeCheckedWithSeed {
+        vote_account: String,
+        clock_sysvar: String,
+        authority_base_key: String,
+        authority_owner: String,
+        authority_seed: String,
+        authority_type: UiVoteAuthorize,
+        new_authority: String,
+    },
+    #[serde(rename = "vote", rename_all = "camelCase")]
+    Vote {
+        vote_account: String,
+        slot_hashes_sysvar: String,
+        clock_sysvar: String,
+        vote_authority: String,
+        vote: UiVote,
+    },
+    #[serde(rename = "updatevotestate", rename_all = "camelCase")]
+    UpdateVoteState {
+        vote_account: String,
+        vote_authority: String,
+        vote_state_update: UiVoteStateUpdate,
+    },
+    #[serde(rename = "updatevotestateswitch", rename_all = "camelCase")]
+    UpdateVoteStateSwitch {
+        vote_account: String,
+        vote_authority: String,
+        vote_state_update: UiVoteStateUpdate,
+        /// Switching proof hash.
+        hash: String,
+    },
+    #[serde(rename = "compactupdatevotestate", rename_all = "camelCase")]
+    CompactUpdateVoteState {
+        vote_account: String,
+        vote_authority: String,
+        vote_state_update: UiVoteStateUpdate,
+    },
+    #[serde(rename = "compactupdatevotestateswitch", rename_all = "camelCase")]
+    CompactUpdateVoteStateSwitch {
+        vote_account: String,
+        vote_authority: String,
+        vote_state_update: UiVoteStateUpdate,
+        /// Switching proof hash.
+        hash: String,
+    },
+    #[serde(rename = "withdraw", rename_all = "camelCase")]
+    Withdraw {
+        vote_account: String,
+        destination: String,
+        withdraw_authority: String,
+        lamports: u64,
+    },
+    #[serde(rename = "updateValidatorIdentity", rename_all = "camelCase")]
+    UpdateValidatorIdentity {
+        vote_account: String,
+        new_validator_identity: String,
+        withdraw_authority: String,
+    },
+    #[serde(rename = "updateCommission", rename_all = "camelCase")]
+    UpdateCommission {
+        vote_account: String,
+        withdraw_authority: String,
+        commission: u8,
+    },
+    #[serde(rename = "voteSwitch", rename_all = "camelCase")]
+    VoteSwitch {
+        vote_account: String,
+        slot_hashes_sysvar: String,
+        clock_sysvar: String,
+        vote_authority: String,
+        vote: UiVote,
+        /// Switching proof hash.
+        hash: String,
+    },
+    #[serde(rename = "authorizeChecked", rename_all = "camelCase")]
+    AuthorizeChecked {
+        vote_account: String,
+        clock_sysvar: String,
+        authority: String,
+        new_authority: String,
+        authority_type: UiVoteAuthorize,
+    },
+}
+
+/// Parses `instruction` with instruction account keys `accounts`, as RPC
+/// does for the `jsonParsed` encoding.
+pub fn parse_vote_instruction(
+    instruction: &VoteInstruction,
+    accounts: &[Pubkey],
+) -> Result<ParsedVoteInstruction, ParseVoteInstructionError> {
+    let expected = match instruction {
+        VoteInstruction::UpdateVoteState(_)
+        | VoteInstruction::UpdateVoteStateSwitch(..)
+        | VoteInstruction::CompactUpdateVoteState(_)
+        | VoteInstruction::CompactUpdateVoteStateSwitch(..)
+        | VoteInstruction::UpdateCommission(_) => 2,
+        VoteInstruction::Authorize(..)
+        | VoteInstruction::AuthorizeWithSeed(_)
+        | VoteInstruction::Withdraw(_)
+        | VoteInstruction::UpdateValidatorIdentity => 3,
+        VoteInstruction::InitializeAccount(_)
+        | VoteInstruction::AuthorizeChecked(_)
+        | VoteInstruction::AuthorizeCheckedWithSeed(_)
+        | VoteInstruction::Vote(_)
+        | VoteInstruction::VoteSwitch(..) => 4,
+    };
+    if accounts.len() < expected {
+        return Err(ParseVoteInstructionError::NotEnoughAccounts {
+            expected,
+            actual: accounts.len(),
+        });
+    }
+    let account = |index: usize| accounts[index].to_string();
+    Ok(match instruction {
+        VoteInstruction::InitializeAccount(vote_init) => ParsedVoteInstruction::Initialize {
+            vote_account: account(0),
+            rent_sysvar: account(1),
+            clock_sysvar: account(2),
+            node: account(3),
+            authorized_voter: vote_init.authorized_voter.to_string(),
+            authorized_withdrawer: vote_init.authorized_withdrawer.to_string(),
+            commission: vote_init.commission,
+        },
+        VoteInstruction::Authorize(new_authority, vote_authorize) => {
+            ParsedVoteInstruction::Authorize {
+                vote_account: account(0),
+                clock_sysvar: account(1),
+                authority: account(2),
+                new_authority: new_authority.to_string(),
+                authority_type: (*vote_authorize).into(),
+            }
+        }
+        VoteInstruction::AuthorizeWithSeed(args) => ParsedVoteInstruction::AuthorizeWithSeed {
+            vote_account: account(0),
+            clock_sysvar: account(1),
+            authority_base_key: account(2),
+            authority_owner: args.current_authority_derived_key_owner.to_string(),
+            authority_seed: args.current_authority_derived_key_seed.clone(),
+            authority_type: args.authorization_type.into(),
+            new_authority: args.new_authority.to_string(),
+        },
+        VoteInstruction::AuthorizeCheckedWithSeed(args) => {
+            ParsedVoteInstruction::AuthorizeCheckedWithSeed {
+                vote_account: account(0),
+                clock_sysvar: account(1),
+                authority_base_key: account(2),
+                authority_owner: args.current_authority_derived_key_owner.to_string(),
+                authority_seed: args.current_authority_derived_key_seed.clone(),
+                authority_type: args.authorization_type.into(),
+                new_authority: account(3),
+            }
+        }
+        VoteInstruction::Vote(vote) => ParsedVoteInstruction::Vote {
+            vote_account: account(0),
+            slot_hashes_sysvar: account(1),
+            clock_sysvar: account(2),
+            vote_authority: account(3),
+            vote: vote.into(),
+        },
+        VoteInstruction::UpdateVoteState(vote_state_update) => {
+            ParsedVoteInstruction::UpdateVoteState {
+                vote_account: account(0),
+                vote_authority: account(1),
+                vote_state_update: vote_state_update.into(),
+            }
+        }
+        VoteInstruction::UpdateVoteStateSwitch(vote_state_update, proof_hash) => {
+            ParsedVoteInstruction::UpdateVoteStateSwitch {
+                vote_account: account(0),
+                vote_authority: account(1),
+                vote_state_update: vote_state_update.into(),
+                hash: proof_hash.to_string(),
+            }
+        }
+        VoteInstruction::CompactUpdateVoteState(vote_state_update) => {
+            ParsedVoteInstruction::CompactUpdateVoteState {
+                vote_account: account(0),
+                vote_authority: account(1),
+                vote_state_update: vote_state_update.into(),
+            }
+        }
+        VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, proof_hash) => {
+            ParsedVoteInstruction::CompactUpdateVoteStateSwitch {
+                vote_account: account(0),
+                vote_authority: account(1),
+                vote_state_update: vote_state_update.into(),
+                hash: proof_hash.to_string(),
+            }
+        }
+        VoteInstruction::Withdraw(lamports) => ParsedVoteInstruction::Withdraw {
+            vote_account: account(0),
+            destination: account(1),
+            withdraw_authority: account(2),
+            lamports: *lamports,
+        },
+        VoteInstruction::UpdateValidatorIdentity => {
+            ParsedVoteInstruction::UpdateValidatorIdentity {
+                vote_account: account(0),
+                new_validator_identity: account(1),
+                withdraw_authority: account(2),
+            }
+        }
+        VoteInstruction::UpdateCommission(commission) => ParsedVoteInstruction::UpdateCommission {
+            vote_account: account(0),
+            withdraw_authority: account(1),
+            commission: *commission,
+        },
+        VoteInstruction::VoteSwitch(vote, proof_hash) => ParsedVoteInstruction::VoteSwitch {
+            vote_account: account(0),
+            slot_hashes_sysvar: account(1),
+            clock_sysvar: account(2),
+            vote_authority: account(3),
+            vote: vote.into(),
+            hash: proof_hash.to_string(),
+        },
+        VoteInstruction::AuthorizeChecked(vote_authorize) => {
+            ParsedVoteInstruction::AuthorizeChecked {
+                vote_account: account(0),
+                clock_sysvar: account(1),
+                authority: account(2),
+                new_authority: account(3),
+                authority_type: (*vote_authorize).into(),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        serde_json::json,
+        solana_program::{
+            hash::Hash,
+            vote::{instruction as vote_instruction, state::Lockout},
+        },
+    };
+
+    fn parse(instruction: &solana_program::instruction::Instruction) -> serde_json::Value {
+        let vote_instruction = bincode::deserialize(&instruction.data).unwrap();
+        let accounts: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .map(|account_meta| account_meta.pubkey)
+            .collect();
+        serde_json::to_value(parse_vote_instruction(&vote_instruction, &accounts).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_vote() {
+        let vote_pubkey = Pubkey::new_unique();
+        let voter = Pubkey::new_unique();
+        let hash = Hash::new_unique();
+        let vote = Vote {
+            slots: vec![1, 2],
+            hash,
+            timestamp: Some(7),
+        };
+        let instruction = vote_instruction::vote(&vote_pubkey, &voter, vote);
+        assert_eq!(
+            parse(&instruction),
+            json!({
+                "type": "vote",
+                "info": {
+                    "voteAccount": vote_pubkey.to_string(),
+                    "slotHashesSysvar": solana_program::sysvar::slot_hashes::id().to_string(),
+                    "clockSysvar": solana_program::sysvar::clock::id().to_string(),
+                    "voteAuthority": voter.to_string(),
+                    "vote": {
+                        "slots": [1, 2],
+                        "hash": hash.to_string(),
+                        "timestamp": 7,
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_compact_update_vote_state_switch() {
+        let vote_pubkey = Pubkey::new_unique();
+        let voter = Pubkey::new_unique();
+        let hash = Hash::new_unique();
+        let proof_hash = Hash::new_unique();
+        let update = VoteStateUpdate::new(
+            [Lockout::new_with_confirmation_count(5, 2)].into(),
+            Some(3),
+            hash,
+        );
+        let instruction = vote_instruction::compact_update_vote_state_switch(
+            &vote_pubkey,
+            &voter,
+            update,
+            proof_hash,
+        );
+        assert_eq!(
+            parse(&instruction),
+            json!({
+                "type": "compactupdatevotestateswitch",
+                "info": {
+                    "voteAccount": vote_pubkey.to_string(),
+                    "voteAuthority": voter.to_string(),
+                    "voteStateUpdate": {
+                        "lockouts": [{ "slot": 5, "confirmation_count": 2 }],
+                        "root": 3,
+                        "hash": hash.to_string(),
+                        "timestamp": null,
+                    },
+                    "hash": proof_hash.to_string(),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_authorize_checked_with_seed() {
+        let vote_pubkey = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let instruction = vote_instruction::authorize_checked_with_seed(
+            &vote_pubkey,
+            &base,
+            &owner,
+            "seed",
+            &new_authority,
+            VoteAuthorize::Withdrawer,
+        );
+        assert_eq!(
+            parse(&instruction),
+            json!({
+                "type": "authorizeCheckedWithSeed",
+                "info": {
+                    "voteAccount": vote_pubkey.to_string(),
+                    "clockSysvar": solana_program::sysvar::clock::id().to_string(),
+                    "authorityBaseKey": base.to_string(),
+                    "authorityOwner": owner.to_string(),
+                    "authoritySeed": "seed",
+                    "authorityType": "Withdrawer",
+                    "newAuthority": new_authority.to_string(),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_not_enough_accounts() {
+        assert_eq!(
+            parse_vote_instruction(&VoteInstruction::Withdraw(1), &[Pubkey::new_unique()]),
+            Err(ParseVoteInstructionError::NotEnoughAccounts {
+                expected: 3,
+                actual: 1,
+            })
+        );
+    }
+}
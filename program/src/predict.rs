@@ -0,0 +1,453 @@
+//! Predicted effects of administrative vote instructions.
+//!
+//! Mirrors the program's handling of `Authorize` and its checked and seeded
+//! variants, `UpdateCommission`, `UpdateValidatorIdentity` and `Withdraw`,
+//! including their signer checks, so wallets can show the resulting account
+//! before the user signs.
+
+use {
+    crate::{
+        authority::AuthorityKind,
+        epochs::is_commission_update_allowed,
+        seeds::derive_seeded_authority,
+        signers::{is_signer, verify_authorized_signer},
+        verify::PACKET_DATA_SIZE,
+        wire,
+    },
+    solana_program::{
+        clock::Clock,
+        epoch_schedule::EpochSchedule,
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        rent::Rent,
+        vote::{
+            self,
+            error::VoteError,
+            instruction::VoteInstruction,
+            state::{VoteAuthorize, VoteState},
+        },
+    },
+};
+
+/// Vote account balance and size before the instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreAccount {
+    pub lamports: u64,
+    pub data_len: usize,
+}
+
+/// Vote account after the instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PredictedEffects {
+    /// `None` if the instruction closes the account.
+    pub vote_state: Option<VoteState>,
+    pub lamports: u64,
+}
+
+/// Predicts the effect of `instruction` on a vote account in `pre_state`.
+///
+/// Signers are taken from the instruction's account metas. Vote-casting and
+/// initialization instructions fail with `InvalidInstructionData`.
+pub fn predict(
+    instruction: &Instruction,
+    pre_state: &VoteState,
+    pre_account: PreAccount,
+    clock: &Clock,
+    rent: &Rent,
+    epoch_schedule: &EpochSchedule,
+) -> Result<PredictedEffects, InstructionError> {
+    if !vote::program::check_id(&instruction.program_id) {
+        return Err(InstructionError::IncorrectProgramId);
+    }
+    let vote_instruction: VoteInstruction = wire::deserialize(&instruction.data, PACKET_DATA_SIZE)
+        .map_err(|_| InstructionError::InvalidInstructionData)?;
+    let account_key = |index: usize| {
+        instruction
+            .accounts
+            .get(index)
+            .map(|account_meta| account_meta.pubkey)
+            .ok_or(InstructionError::NotEnoughAccountKeys)
+    };
+    let signers: Vec<Pubkey> = instruction
+        .accounts
+        .iter()
+        .filter(|account_meta| account_meta.is_signer)
+        .map(|account_meta| account_meta.pubkey)
+        .collect();
+
+    let mut vote_state = pre_state.clone();
+    let mut lamports = pre_account.lamports;
+    match vote_instruction {
+        VoteInstruction::Authorize(new_authority, vote_authorize) => {
+            authorize(
+                &mut vote_state,
+                &new_authority,
+                vote_authorize,
+                &signers,
+                clock,
+            )?;
+        }
+        VoteInstruction::AuthorizeChecked(vote_authorize) => {
+            let new_authority = account_key(3)?;
+            verify_authorized_signer(&new_authority, &signers)?;
+            authorize(
+                &mut vote_state,
+                &new_authority,
+                vote_authorize,
+                &signers,
+                clock,
+            )?;
+        }
+        VoteInstruction::AuthorizeWithSeed(args) => {
+            let seeded_signers = seeded_signers(
+                &account_key(2)?,
+                &args.current_authority_derived_key_seed,
+                &args.current_authority_derived_key_owner,
+                &signers,
+            )?;
+            authorize(
+                &mut vote_state,
+                &args.new_authority,
+                args.authorization_type,
+                &seeded_signers,
+                clock,
+            )?;
+        }
+        VoteInstruction::AuthorizeCheckedWithSeed(args) => {
+            let new_authority = account_key(3)?;
+            verify_authorized_signer(&new_authority, &signers)?;
+            let seeded_signers = seeded_signers(
+                &account_key(2)?,
+                &args.current_authority_derived_key_seed,
+                &args.current_authority_derived_key_owner,
+                &signers,
+            )?;
+            authorize(
+                &mut vote_state,
+                &new_authority,
+                args.authorization_type,
+                &seeded_signers,
+                clock,
+            )?;
+        }
+        VoteInstruction::UpdateCommission(commission) => {
+            verify_authorized_signer(&vote_state.authorized_withdrawer, &signers)?;
+            if commission > vote_state.commission
+                && !is_commission_update_allowed(clock.slot, epoch_schedule)
+            {
+                return Err(VoteError::CommissionUpdateTooLate.into());
+            }
+            vote_state.commission = commission;
+        }
+        VoteInstruction::UpdateValidatorIdentity => {
+            let node_pubkey = account_key(1)?;
+            verify_authorized_signer(&vote_state.authorized_withdrawer, &signers)?;
+            verify_authorized_signer(&node_pubkey, &signers)?;
+            vote_state.node_pubkey = node_pubkey;
+        }
+        VoteInstruction::Withdraw(withdraw_lamports) => {
+            verify_authorized_signer(&vote_state.authorized_withdrawer, &signers)?;
+            lamports = lamports
+                .checked_sub(withdraw_lamports)
+                .ok_or(InstructionError::InsufficientFunds)?;
+            if lamports == 0 {
+                let active = vote_state.epoch_credits.last().is_some_and(
+                    |(last_epoch_with_credits, _, _)| {
+                        clock.epoch.saturating_sub(*last_epoch_with_credits) < 2
+                    },
+                );
+                if active {
+                    return Err(VoteError::ActiveVoteAccountClose.into());
+                }
+                return Ok(PredictedEffects {
+                    vote_state: None,
+                    lamports,
+                });
+            }
+            if lamports < rent.minimum_balance(pre_account.data_len) {
+                return Err(InstructionError::InsufficientFunds);
+            }
+        }
+        _ => return Err(InstructionError::InvalidInstructionData),
+    }
+    Ok(PredictedEffects {
+        vote_state: Some(vote_state),
+        lamports,
+    })
+}
+
+/// Returns whether `instruction`, with instruction account keys `accounts`,
+/// would leave `vote_state` as it is: setting the current withdrawer,
+/// validator identity or commission again.
+///
+/// Setting the current voter is never a no-op, as the program still records
+/// it for a future epoch. A no-op still rewrites the account, which upgrades
+/// a 1.14.11-sized account that can afford it to the current layout; see
+/// [`legacy_top_up`](crate::storage::legacy_top_up).
+pub fn is_noop(instruction: &VoteInstruction, accounts: &[Pubkey], vote_state: &VoteState) -> bool {
+    let is_current_withdrawer = |new_authority: Option<&Pubkey>, vote_authorize: VoteAuthorize| {
+        AuthorityKind::from(vote_authorize) == AuthorityKind::Withdrawer
+            && new_authority == Some(&vote_state.authorized_withdrawer)
+    };
+    match instruction {
+        VoteInstruction::Authorize(new_authority, vote_authorize) => {
+            is_current_withdrawer(Some(new_authority), *vote_authorize)
+        }
+        VoteInstruction::AuthorizeWithSeed(args) => {
+            is_current_withdrawer(Some(&args.new_authority), args.authorization_type)
+        }
+        VoteInstruction::AuthorizeChecked(vote_authorize) => {
+            is_current_withdrawer(accounts.get(3), *vote_authorize)
+        }
+        VoteInstruction::AuthorizeCheckedWithSeed(args) => {
+            is_current_withdrawer(accounts.get(3), args.authorization_type)
+        }
+        VoteInstruction::UpdateValidatorIdentity => {
+            accounts.get(1) == Some(&vote_state.node_pubkey)
+        }
+        VoteInstruction::UpdateCommission(commission) => *commission == vote_state.commission,
+        _ => false,
+    }
+}
+
+/// The keys a seeded authorize signs for: the key derived from `base`, if
+/// `base` signed. The program ignores every other signer.
+fn seeded_signers(
+    base: &Pubkey,
+    seed: &str,
+    owner: &Pubkey,
+    signers: &[Pubkey],
+) -> Result<Vec<Pubkey>, InstructionError> {
+    if is_signer(base, signers) {
+        Ok(vec![derive_seeded_authority(base, seed, owner)?])
+    } else {
+        Ok(vec![])
+    }
+}
+
+fn authorize(
+    vote_state: &mut VoteState,
+    new_authority: &Pubkey,
+    vote_authorize: VoteAuthorize,
+    signers: &[Pubkey],
+    clock: &Clock,
+) -> Result<(), InstructionError> {
+    AuthorityKind::from(vote_authorize).set(vote_state, new_authority, clock, |authority| {
+        verify_authorized_signer(authority, signers)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_clock, initialized_vote_state},
+        solana_program::{instruction::AccountMeta, vote::instruction as vote_instruction},
+    };
+
+    const SEED: &str = "vote-withdrawer";
+
+    fn predict_at_epoch(
+        instruction: &Instruction,
+        pre_state: &VoteState,
+    ) -> Result<PredictedEffects, InstructionError> {
+        let pre_account = PreAccount {
+            lamports: 1_000_000_000,
+            data_len: VoteState::size_of(),
+        };
+        predict(
+            instruction,
+            pre_state,
+            pre_account,
+            &fixture_clock(5),
+            &Rent::default(),
+            &EpochSchedule::default(),
+        )
+    }
+
+    fn seeded_withdrawer_state(base: &Pubkey, owner: &Pubkey) -> VoteState {
+        let mut vote_state = initialized_vote_state();
+        vote_state.authorized_withdrawer = derive_seeded_authority(base, SEED, owner).unwrap();
+        vote_state
+    }
+
+    #[test]
+    fn test_predict_authorize_with_seed() {
+        let vote_pubkey = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let new_withdrawer = Pubkey::new_unique();
+        let pre_state = seeded_withdrawer_state(&base, &owner);
+        let instruction = vote_instruction::authorize_with_seed(
+            &vote_pubkey,
+            &base,
+            &owner,
+            SEED,
+            &new_withdrawer,
+            VoteAuthorize::Withdrawer,
+        );
+        let effects = predict_at_epoch(&instruction, &pre_state).unwrap();
+        assert_eq!(
+            effects.vote_state.unwrap().authorized_withdrawer,
+            new_withdrawer
+        );
+    }
+
+    #[test]
+    fn test_predict_authorize_with_seed_wrong_seed() {
+        let vote_pubkey = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let pre_state = seeded_withdrawer_state(&base, &owner);
+        let instruction = vote_instruction::authorize_with_seed(
+            &vote_pubkey,
+            &base,
+            &owner,
+            "another-seed",
+            &Pubkey::new_unique(),
+            VoteAuthorize::Withdrawer,
+        );
+        assert_eq!(
+            predict_at_epoch(&instruction, &pre_state),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_predict_authorize_with_seed_ignores_other_signers() {
+        let vote_pubkey = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let pre_state = seeded_withdrawer_state(&base, &owner);
+        let mut instruction = vote_instruction::authorize_with_seed(
+            &vote_pubkey,
+            &base,
+            &owner,
+            SEED,
+            &Pubkey::new_unique(),
+            VoteAuthorize::Withdrawer,
+        );
+        instruction.accounts[2].is_signer = false;
+        instruction.accounts.push(AccountMeta::new_readonly(
+            pre_state.authorized_withdrawer,
+            true,
+        ));
+        assert_eq!(
+            predict_at_epoch(&instruction, &pre_state),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_predict_authorize_checked_with_seed() {
+        let vote_pubkey = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let new_voter = Pubkey::new_unique();
+        let pre_state = seeded_withdrawer_state(&base, &owner);
+        let instruction = vote_instruction::authorize_checked_with_seed(
+            &vote_pubkey,
+            &base,
+            &owner,
+            SEED,
+            &new_voter,
+            VoteAuthorize::Voter,
+        );
+        let clock = fixture_clock(5);
+        let vote_state = predict_at_epoch(&instruction, &pre_state)
+            .unwrap()
+            .vote_state
+            .unwrap();
+        assert_eq!(
+            vote_state.get_authorized_voter(clock.leader_schedule_epoch + 1),
+            Some(new_voter)
+        );
+
+        let mut unsigned = instruction;
+        unsigned.accounts[3].is_signer = false;
+        assert_eq!(
+            predict_at_epoch(&unsigned, &pre_state),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_is_noop() {
+        let vote_state = initialized_vote_state();
+        let vote_pubkey = Pubkey::new_unique();
+        let withdrawer = vote_state.authorized_withdrawer;
+        let (_, &voter) = vote_state.authorized_voters().last().unwrap();
+        let other = Pubkey::new_unique();
+        let is_noop_instruction = |instruction: Instruction| {
+            let accounts: Vec<Pubkey> = instruction
+                .accounts
+                .iter()
+                .map(|account_meta| account_meta.pubkey)
+                .collect();
+            let vote_instruction = bincode::deserialize(&instruction.data).unwrap();
+            is_noop(&vote_instruction, &accounts, &vote_state)
+        };
+
+        assert!(is_noop_instruction(vote_instruction::authorize(
+            &vote_pubkey,
+            &withdrawer,
+            &withdrawer,
+            VoteAuthorize::Withdrawer,
+        )));
+        assert!(is_noop_instruction(vote_instruction::authorize_checked(
+            &vote_pubkey,
+            &withdrawer,
+            &withdrawer,
+            VoteAuthorize::Withdrawer,
+        )));
+        assert!(!is_noop_instruction(vote_instruction::authorize(
+            &vote_pubkey,
+            &withdrawer,
+            &other,
+            VoteAuthorize::Withdrawer,
+        )));
+        assert!(!is_noop_instruction(vote_instruction::authorize(
+            &vote_pubkey,
+            &withdrawer,
+            &voter,
+            VoteAuthorize::Voter,
+        )));
+        assert!(is_noop_instruction(
+            vote_instruction::update_validator_identity(
+                &vote_pubkey,
+                &withdrawer,
+                &vote_state.node_pubkey,
+            )
+        ));
+        assert!(!is_noop_instruction(
+            vote_instruction::update_validator_identity(&vote_pubkey, &withdrawer, &other)
+        ));
+        assert!(is_noop_instruction(vote_instruction::update_commission(
+            &vote_pubkey,
+            &withdrawer,
+            vote_state.commission,
+        )));
+        assert!(!is_noop_instruction(vote_instruction::update_commission(
+            &vote_pubkey,
+            &withdrawer,
+            vote_state.commission.wrapping_add(1),
+        )));
+    }
+
+    #[test]
+    fn test_authorize_current_voter_changes_state() {
+        let pre_state = initialized_vote_state();
+        let (_, &voter) = pre_state.authorized_voters().last().unwrap();
+        let instruction = vote_instruction::authorize(
+            &Pubkey::new_unique(),
+            &pre_state.authorized_withdrawer,
+            &voter,
+            VoteAuthorize::Voter,
+        );
+        let vote_state = predict_at_epoch(&instruction, &pre_state)
+            .unwrap()
+            .vote_state
+            .unwrap();
+        assert_ne!(vote_state, pre_state);
+    }
+}
@@ -0,0 +1,248 @@
+//! Client-side preflight checks for vote account creation.
+
+use {
+    crate::signers::is_signer,
+    solana_program::{
+        pubkey::Pubkey,
+        rent::Rent,
+        vote::state::{VoteInit, VoteState},
+    },
+    thiserror::Error,
+};
+
+/// Highest meaningful commission; rewards treat larger values as 100%.
+pub const MAX_COMMISSION: u8 = 100;
+
+/// Reasons a vote account creation would fail or misbehave.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PreflightError {
+    #[error("vote account space must be {expected} bytes, got {actual}")]
+    InvalidAccountSize { expected: usize, actual: usize },
+    #[error("vote account space must be at least {minimum} bytes, got {actual}")]
+    AccountTooSmall { minimum: usize, actual: usize },
+    #[error("vote account needs {required} lamports to be rent exempt, got {lamports}")]
+    NotRentExempt { required: u64, lamports: u64 },
+    #[error("commission {0} exceeds {MAX_COMMISSION}%")]
+    CommissionTooHigh(u8),
+    #[error("validator identity {0} must sign the initialization")]
+    MissingNodeSignature(Pubkey),
+}
+
+/// How an account's data length is checked against the vote state size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SizePolicy {
+    /// The length must equal the size exactly, as `InitializeAccount`
+    /// requires today.
+    #[default]
+    Exact,
+    /// Any length of at least the size is accepted.
+    AtLeast,
+}
+
+impl SizePolicy {
+    /// Checks a data length of `actual` bytes against `size`.
+    pub fn check(self, size: usize, actual: usize) -> Result<(), PreflightError> {
+        match self {
+            Self::Exact if actual != size => Err(PreflightError::InvalidAccountSize {
+                expected: size,
+                actual,
+            }),
+            Self::AtLeast if actual < size => Err(PreflightError::AccountTooSmall {
+                minimum: size,
+                actual,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Runs the checks `InitializeAccount` performs against a vote account of
+/// `space` bytes funded with `lamports`, plus a commission sanity check.
+///
+/// `signers` are the keys that will sign the transaction.
+pub fn validate_create_vote_account(
+    space: usize,
+    lamports: u64,
+    rent: &Rent,
+    vote_init: &VoteInit,
+    signers: &[Pubkey],
+) -> Result<(), PreflightError> {
+    validate_create_vote_account_with_policy(
+        SizePolicy::default(),
+        space,
+        lamports,
+        rent,
+        vote_init,
+        signers,
+    )
+}
+
+/// Like [`validate_create_vote_account`], checking `space` under
+/// `size_policy`.
+pub fn validate_create_vote_account_with_policy(
+    size_policy: SizePolicy,
+    space: usize,
+    lamports: u64,
+    rent: &Rent,
+    vote_init: &VoteInit,
+    signers: &[Pubkey],
+) -> Result<(), PreflightError> {
+    size_policy.check(VoteState::size_of(), space)?;
+    if !rent.is_exempt(lamports, space) {
+        return Err(PreflightError::NotRentExempt {
+            required: rent.minimum_balance(space),
+            lamports,
+        });
+    }
+    // The program accepts any commission, but anything above 100 is
+    // almost certainly a mistake.
+    if vote_init.commission > MAX_COMMISSION {
+        return Err(PreflightError::CommissionTooHigh(vote_init.commission));
+    }
+    if !is_signer(&vote_init.node_pubkey, signers) {
+        return Err(PreflightError::MissingNodeSignature(vote_init.node_pubkey));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test_support::fixture_pubkey};
+
+    fn vote_init(commission: u8) -> VoteInit {
+        VoteInit {
+            node_pubkey: fixture_pubkey(1),
+            authorized_voter: fixture_pubkey(2),
+            authorized_withdrawer: fixture_pubkey(3),
+            commission,
+        }
+    }
+
+    fn validate(
+        space: usize,
+        lamports: u64,
+        commission: u8,
+        signers: &[Pubkey],
+    ) -> Result<(), PreflightError> {
+        validate_create_vote_account(
+            space,
+            lamports,
+            &Rent::default(),
+            &vote_init(commission),
+            signers,
+        )
+    }
+
+    #[test]
+    fn test_valid() {
+        let space = VoteState::size_of();
+        let lamports = Rent::default().minimum_balance(space);
+        assert_eq!(validate(space, lamports, 0, &[fixture_pubkey(1)]), Ok(()));
+        assert_eq!(
+            validate(
+                space,
+                lamports,
+                MAX_COMMISSION,
+                &[fixture_pubkey(0), fixture_pubkey(1)]
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_invalid_account_size() {
+        let space = VoteState::size_of();
+        for actual in [0, space - 1, space + 1] {
+            let lamports = Rent::default().minimum_balance(actual);
+            assert_eq!(
+                validate(actual, lamports, 0, &[fixture_pubkey(1)]),
+                Err(PreflightError::InvalidAccountSize {
+                    expected: space,
+                    actual,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_not_rent_exempt() {
+        let space = VoteState::size_of();
+        let required = Rent::default().minimum_balance(space);
+        assert_eq!(
+            validate(space, required - 1, 0, &[fixture_pubkey(1)]),
+            Err(PreflightError::NotRentExempt {
+                required,
+                lamports: required - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_commission_too_high() {
+        let space = VoteState::size_of();
+        let lamports = Rent::default().minimum_balance(space);
+        assert_eq!(
+            validate(space, lamports, MAX_COMMISSION + 1, &[fixture_pubkey(1)]),
+            Err(PreflightError::CommissionTooHigh(MAX_COMMISSION + 1))
+        );
+    }
+
+    #[test]
+    fn test_missing_node_signature() {
+        let space = VoteState::size_of();
+        let lamports = Rent::default().minimum_balance(space);
+        // The voter and withdrawer signing does not stand in for the node.
+        assert_eq!(
+            validate(space, lamports, 0, &[fixture_pubkey(2), fixture_pubkey(3)]),
+            Err(PreflightError::MissingNodeSignature(fixture_pubkey(1)))
+        );
+    }
+
+    #[test]
+    fn test_size_policy() {
+        let size = VoteState::size_of();
+        assert_eq!(SizePolicy::default(), SizePolicy::Exact);
+        assert_eq!(SizePolicy::Exact.check(size, size), Ok(()));
+        assert_eq!(SizePolicy::AtLeast.check(size, size), Ok(()));
+        assert_eq!(SizePolicy::AtLeast.check(size, size + 1), Ok(()));
+        assert_eq!(
+            SizePolicy::AtLeast.check(size, size - 1),
+            Err(PreflightError::AccountTooSmall {
+                minimum: size,
+                actual: size - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_with_at_least_policy() {
+        let rent = Rent::default();
+        let space = VoteState::size_of() + 128;
+        // Rent exemption is checked against the larger space.
+        assert_eq!(
+            validate_create_vote_account_with_policy(
+                SizePolicy::AtLeast,
+                space,
+                rent.minimum_balance(VoteState::size_of()),
+                &rent,
+                &vote_init(0),
+                &[fixture_pubkey(1)],
+            ),
+            Err(PreflightError::NotRentExempt {
+                required: rent.minimum_balance(space),
+                lamports: rent.minimum_balance(VoteState::size_of()),
+            })
+        );
+        assert_eq!(
+            validate_create_vote_account_with_policy(
+                SizePolicy::AtLeast,
+                space,
+                rent.minimum_balance(space),
+                &rent,
+                &vote_init(0),
+                &[fixture_pubkey(1)],
+            ),
+            Ok(())
+        );
+    }
+}
@@ -1,11 +1,57 @@
 //! Program processor.
 
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+#[cfg(any(feature = "deprecation-audit", feature = "log"))]
+use {crate::instruction::InstructionMeta, solana_program::msg};
 
 pub fn process(
     _program_id: &Pubkey,
     _accounts: &[AccountInfo],
     _instruction_data: &[u8],
 ) -> ProgramResult {
-    Ok(())
+    #[cfg(feature = "deprecation-audit")]
+    audit_deprecated_instruction(_accounts, _instruction_data);
+    let result = Ok(());
+    #[cfg(feature = "log")]
+    log_instruction(_accounts, _instruction_data, &result);
+    result
+}
+
+/// Logs a `deprecated_vote_instruction` event for vote instructions that
+/// `deprecate_legacy_vote_ixs` disables.
+///
+/// Only the instruction tag is read, so the audit costs the same whether or
+/// not the payload is well formed.
+#[cfg(feature = "deprecation-audit")]
+fn audit_deprecated_instruction(accounts: &[AccountInfo], instruction_data: &[u8]) {
+    let Some(meta) = InstructionMeta::from_data(instruction_data) else {
+        return;
+    };
+    if !meta.is_deprecated() {
+        return;
+    }
+    let instruction = meta.name;
+    match accounts.first() {
+        Some(vote_account) => msg!(
+            "deprecated_vote_instruction instruction={} vote_account={}",
+            instruction,
+            vote_account.key
+        ),
+        None => msg!("deprecated_vote_instruction instruction={}", instruction),
+    }
+}
+
+/// Logs one line per instruction: its name, the vote account and the result.
+#[cfg(feature = "log")]
+fn log_instruction(accounts: &[AccountInfo], instruction_data: &[u8], result: &ProgramResult) {
+    let instruction = InstructionMeta::from_data(instruction_data)
+        .map(|meta| meta.name)
+        .unwrap_or("Unknown");
+    let vote_account = accounts.first().map(|account| account.key);
+    match (vote_account, result) {
+        (Some(vote_account), Ok(())) => msg!("{} {}: ok", instruction, vote_account),
+        (Some(vote_account), Err(err)) => msg!("{} {}: {}", instruction, vote_account, err),
+        (None, Ok(())) => msg!("{}: ok", instruction),
+        (None, Err(err)) => msg!("{}: {}", instruction, err),
+    }
 }
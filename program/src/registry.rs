@@ -0,0 +1,203 @@
+//! In-memory registry of decoded vote accounts.
+
+use {
+    solana_program::{pubkey::Pubkey, vote::state::VoteState},
+    std::{
+        collections::{BTreeMap, BTreeSet, HashMap},
+        ops::RangeBounds,
+    },
+};
+
+/// Decoded vote accounts indexed by validator identity and by commission.
+///
+/// Accounts are replaced wholesale on update, and both indexes are kept in
+/// step with every insert and removal.
+#[derive(Clone, Debug, Default)]
+pub struct VoteRegistry {
+    vote_accounts: HashMap<Pubkey, VoteState>,
+    by_node: BTreeMap<Pubkey, BTreeSet<Pubkey>>,
+    by_commission: BTreeMap<u8, BTreeSet<Pubkey>>,
+}
+
+impl VoteRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.vote_accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vote_accounts.is_empty()
+    }
+
+    pub fn get(&self, vote_pubkey: &Pubkey) -> Option<&VoteState> {
+        self.vote_accounts.get(vote_pubkey)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Pubkey, &VoteState)> {
+        self.vote_accounts.iter()
+    }
+
+    /// Inserts or replaces a vote account, returning the previous state.
+    pub fn upsert(&mut self, vote_pubkey: Pubkey, vote_state: VoteState) -> Option<VoteState> {
+        let previous = self.remove(&vote_pubkey);
+        self.by_node
+            .entry(vote_state.node_pubkey)
+            .or_default()
+            .insert(vote_pubkey);
+        self.by_commission
+            .entry(vote_state.commission)
+            .or_default()
+            .insert(vote_pubkey);
+        self.vote_accounts.insert(vote_pubkey, vote_state);
+        previous
+    }
+
+    /// Removes a vote account, returning its state.
+    pub fn remove(&mut self, vote_pubkey: &Pubkey) -> Option<VoteState> {
+        let vote_state = self.vote_accounts.remove(vote_pubkey)?;
+        remove_from_index(&mut self.by_node, &vote_state.node_pubkey, vote_pubkey);
+        remove_from_index(&mut self.by_commission, &vote_state.commission, vote_pubkey);
+        Some(vote_state)
+    }
+
+    /// Vote accounts whose validator identity is `node_pubkey`.
+    pub fn vote_accounts_for_node(&self, node_pubkey: &Pubkey) -> impl Iterator<Item = &Pubkey> {
+        self.by_node.get(node_pubkey).into_iter().flatten()
+    }
+
+    /// Vote accounts whose commission falls in `commission`, lowest
+    /// commission first.
+    pub fn vote_accounts_with_commission<R>(&self, commission: R) -> impl Iterator<Item = &Pubkey>
+    where
+        R: RangeBounds<u8>,
+    {
+        self.by_commission
+            .range(commission)
+            .flat_map(|(_, vote_pubkeys)| vote_pubkeys)
+    }
+}
+
+impl Extend<(Pubkey, VoteState)> for VoteRegistry {
+    fn extend<T: IntoIterator<Item = (Pubkey, VoteState)>>(&mut self, iter: T) {
+        for (vote_pubkey, vote_state) in iter {
+            self.upsert(vote_pubkey, vote_state);
+        }
+    }
+}
+
+impl FromIterator<(Pubkey, VoteState)> for VoteRegistry {
+    fn from_iter<T: IntoIterator<Item = (Pubkey, VoteState)>>(iter: T) -> Self {
+        let mut registry = Self::new();
+        registry.extend(iter);
+        registry
+    }
+}
+
+fn remove_from_index<K: Ord>(
+    index: &mut BTreeMap<K, BTreeSet<Pubkey>>,
+    key: &K,
+    vote_pubkey: &Pubkey,
+) {
+    if let Some(vote_pubkeys) = index.get_mut(key) {
+        vote_pubkeys.remove(vote_pubkey);
+        if vote_pubkeys.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, initialized_vote_state},
+    };
+
+    fn vote_state(node: u8, commission: u8) -> VoteState {
+        let mut vote_state = initialized_vote_state();
+        vote_state.node_pubkey = fixture_pubkey(node);
+        vote_state.commission = commission;
+        vote_state
+    }
+
+    fn for_node(registry: &VoteRegistry, node: u8) -> Vec<Pubkey> {
+        registry
+            .vote_accounts_for_node(&fixture_pubkey(node))
+            .copied()
+            .collect()
+    }
+
+    fn with_commission<R: RangeBounds<u8>>(registry: &VoteRegistry, commission: R) -> Vec<Pubkey> {
+        registry
+            .vote_accounts_with_commission(commission)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_upsert_moves_indexes() {
+        let vote_pubkey = fixture_pubkey(20);
+        let mut registry = VoteRegistry::new();
+        assert_eq!(registry.upsert(vote_pubkey, vote_state(1, 5)), None);
+        assert_eq!(
+            registry.upsert(vote_pubkey, vote_state(2, 7)),
+            Some(vote_state(1, 5))
+        );
+        assert_eq!(registry.len(), 1);
+        assert_eq!(for_node(&registry, 1), []);
+        assert_eq!(for_node(&registry, 2), [vote_pubkey]);
+        assert_eq!(with_commission(&registry, 5..=5), []);
+        assert_eq!(with_commission(&registry, 7..=7), [vote_pubkey]);
+        // The old buckets are gone, not left empty.
+        assert!(!registry.by_node.contains_key(&fixture_pubkey(1)));
+        assert!(!registry.by_commission.contains_key(&5));
+    }
+
+    #[test]
+    fn test_remove_prunes_empty_buckets() {
+        let mut registry: VoteRegistry = [
+            (fixture_pubkey(20), vote_state(1, 5)),
+            (fixture_pubkey(21), vote_state(1, 6)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(registry.remove(&fixture_pubkey(20)), Some(vote_state(1, 5)));
+        assert_eq!(for_node(&registry, 1), [fixture_pubkey(21)]);
+        assert!(!registry.by_commission.contains_key(&5));
+        assert_eq!(registry.remove(&fixture_pubkey(20)), None);
+        registry.remove(&fixture_pubkey(21));
+        assert!(registry.is_empty());
+        assert!(registry.by_node.is_empty());
+        assert!(registry.by_commission.is_empty());
+    }
+
+    #[test]
+    fn test_commission_range_order() {
+        let registry: VoteRegistry = [
+            (fixture_pubkey(20), vote_state(1, 100)),
+            (fixture_pubkey(21), vote_state(1, 0)),
+            (fixture_pubkey(22), vote_state(2, 10)),
+            (fixture_pubkey(23), vote_state(3, 0)),
+        ]
+        .into_iter()
+        .collect();
+        let mut zero = vec![fixture_pubkey(21), fixture_pubkey(23)];
+        zero.sort();
+        assert_eq!(
+            with_commission(&registry, ..),
+            [zero.clone(), vec![fixture_pubkey(22), fixture_pubkey(20)]].concat()
+        );
+        assert_eq!(
+            with_commission(&registry, ..=10),
+            [zero, vec![fixture_pubkey(22)]].concat()
+        );
+        assert_eq!(with_commission(&registry, 11..100), []);
+        assert_eq!(
+            with_commission(&registry, 10..),
+            [fixture_pubkey(22), fixture_pubkey(20)]
+        );
+    }
+}
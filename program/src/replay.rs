@@ -0,0 +1,359 @@
+//! Reconstruction of a vote state from its instruction history.
+//!
+//! Instructions are assumed to have succeeded on chain, so signatures are not
+//! checked. Lamport balances are not tracked; `Withdraw` leaves the state
+//! unchanged.
+//!
+//! The program drops voted slots older than `SlotHashes` before applying a
+//! vote, which changes the stored tower of a validator catching up after
+//! downtime. Steps replay that filtering when given the `SlotHashes` of their
+//! slot; without them, votes are applied as proposed.
+
+use {
+    crate::{
+        authority::AuthorityKind,
+        delta::{apply_update, apply_vote},
+        simulate::{check_and_filter_proposed_vote_state, filter_vote_slots},
+    },
+    solana_program::{
+        clock::{Clock, Slot},
+        hash::Hash,
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        vote::{
+            instruction::VoteInstruction,
+            state::{VoteAuthorize, VoteState},
+        },
+    },
+    thiserror::Error,
+};
+
+/// One landed instruction against the replayed vote account.
+#[derive(Clone, Copy, Debug)]
+pub struct ReplayStep<'a> {
+    /// Clock of the slot the instruction landed in.
+    pub clock: &'a Clock,
+    pub instruction: &'a VoteInstruction,
+    /// Instruction account keys, in order.
+    pub accounts: &'a [Pubkey],
+    /// `SlotHashes` as of the slot, newest first, to filter votes against.
+    pub slot_hashes: Option<&'a [(Slot, Hash)]>,
+}
+
+/// Reasons a replay diverges from the chain.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ReplayError {
+    #[error("instruction landed in slot {slot} failed to replay: {err}")]
+    Instruction { slot: Slot, err: InstructionError },
+    #[error("replayed state differs from the snapshot at slot {0}")]
+    SnapshotMismatch(Slot),
+}
+
+/// Folds landed instructions into a vote state.
+#[derive(Clone, Debug, Default)]
+pub struct VoteStateReplayer {
+    vote_state: VoteState,
+    last_slot: Slot,
+}
+
+impl VoteStateReplayer {
+    /// Starts from `vote_state` as of `slot`. Use `VoteState::default()` and
+    /// slot 0 to replay from the account's `InitializeAccount`.
+    pub fn new(vote_state: VoteState, slot: Slot) -> Self {
+        Self {
+            vote_state,
+            last_slot: slot,
+        }
+    }
+
+    pub fn vote_state(&self) -> &VoteState {
+        &self.vote_state
+    }
+
+    pub fn into_vote_state(self) -> VoteState {
+        self.vote_state
+    }
+
+    /// Slot of the last applied instruction, or the starting slot.
+    pub fn last_slot(&self) -> Slot {
+        self.last_slot
+    }
+
+    /// Applies one landed instruction.
+    pub fn apply(&mut self, step: ReplayStep) -> Result<(), ReplayError> {
+        let slot = step.clock.slot;
+        apply_instruction(&mut self.vote_state, step)
+            .map_err(|err| ReplayError::Instruction { slot, err })?;
+        self.last_slot = slot;
+        Ok(())
+    }
+
+    /// Checks the replayed state against an on-chain `snapshot` taken at
+    /// `slot`, after every instruction up to `slot` has been applied.
+    pub fn check_snapshot(&self, snapshot: &VoteState, slot: Slot) -> Result<(), ReplayError> {
+        if self.vote_state != *snapshot {
+            return Err(ReplayError::SnapshotMismatch(slot));
+        }
+        Ok(())
+    }
+}
+
+/// Replays `steps` in order on top of `vote_state`.
+pub fn replay<'a>(
+    vote_state: VoteState,
+    steps: impl IntoIterator<Item = ReplayStep<'a>>,
+) -> Result<VoteState, ReplayError> {
+    let mut replayer = VoteStateReplayer::new(vote_state, 0);
+    for step in steps {
+        replayer.apply(step)?;
+    }
+    Ok(replayer.into_vote_state())
+}
+
+fn apply_instruction(vote_state: &mut VoteState, step: ReplayStep) -> Result<(), InstructionError> {
+    let ReplayStep {
+        clock,
+        instruction,
+        accounts,
+        slot_hashes,
+    } = step;
+    let account_key = |index: usize| {
+        accounts
+            .get(index)
+            .copied()
+            .ok_or(InstructionError::NotEnoughAccountKeys)
+    };
+    match instruction {
+        VoteInstruction::InitializeAccount(vote_init) => {
+            *vote_state = VoteState::new(vote_init, clock);
+        }
+        VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => match slot_hashes {
+            Some(slot_hashes) => {
+                let vote = filter_vote_slots(vote, slot_hashes)?;
+                apply_vote(vote_state, &vote, clock.epoch, clock.slot)?;
+            }
+            None => {
+                apply_vote(vote_state, vote, clock.epoch, clock.slot)?;
+            }
+        },
+        VoteInstruction::UpdateVoteState(vote_state_update)
+        | VoteInstruction::UpdateVoteStateSwitch(vote_state_update, _)
+        | VoteInstruction::CompactUpdateVoteState(vote_state_update)
+        | VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, _) => {
+            match slot_hashes {
+                Some(slot_hashes) => {
+                    let mut vote_state_update = vote_state_update.clone();
+                    check_and_filter_proposed_vote_state(
+                        vote_state,
+                        &mut vote_state_update.lockouts,
+                        &mut vote_state_update.root,
+                        vote_state_update.hash,
+                        slot_hashes,
+                    )?;
+                    apply_update(vote_state, &vote_state_update, clock.epoch, clock.slot)?;
+                }
+                None => {
+                    apply_update(vote_state, vote_state_update, clock.epoch, clock.slot)?;
+                }
+            }
+        }
+        VoteInstruction::Authorize(new_authority, vote_authorize) => {
+            authorize(vote_state, new_authority, *vote_authorize, clock)?;
+        }
+        VoteInstruction::AuthorizeChecked(vote_authorize) => {
+            authorize(vote_state, &account_key(3)?, *vote_authorize, clock)?;
+        }
+        VoteInstruction::AuthorizeWithSeed(args) => {
+            authorize(
+                vote_state,
+                &args.new_authority,
+                args.authorization_type,
+                clock,
+            )?;
+        }
+        VoteInstruction::AuthorizeCheckedWithSeed(args) => {
+            authorize(vote_state, &account_key(3)?, args.authorization_type, clock)?;
+        }
+        VoteInstruction::UpdateValidatorIdentity => {
+            vote_state.node_pubkey = account_key(1)?;
+        }
+        VoteInstruction::UpdateCommission(commission) => {
+            vote_state.commission = *commission;
+        }
+        VoteInstruction::Withdraw(_) => {}
+    }
+    Ok(())
+}
+
+fn authorize(
+    vote_state: &mut VoteState,
+    new_authority: &Pubkey,
+    vote_authorize: VoteAuthorize,
+    clock: &Clock,
+) -> Result<(), InstructionError> {
+    AuthorityKind::from(vote_authorize).set(vote_state, new_authority, clock, |_| Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            delta::{DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING, TIMELY_VOTE_CREDITS},
+            test_support::{
+                fixture_bank_hash, fixture_pubkey, fixture_slot_hashes, initialized_vote_state,
+                VoteStateBuilder,
+            },
+        },
+        solana_program::vote::{
+            error::VoteError,
+            state::{Lockout, Vote, VoteInit, VoteStateUpdate},
+        },
+    };
+
+    fn clock(slot: Slot) -> Clock {
+        Clock {
+            slot,
+            ..Clock::default()
+        }
+    }
+
+    /// `vote_state` after the SDK votes on `slots`, landing at `current_slot`.
+    fn sdk_vote(vote_state: &VoteState, slots: &[Slot], current_slot: Slot) -> VoteState {
+        let mut vote_state = vote_state.clone();
+        for &slot in slots {
+            vote_state.process_next_vote_slot(
+                slot,
+                0,
+                current_slot,
+                TIMELY_VOTE_CREDITS,
+                DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+            );
+        }
+        vote_state
+    }
+
+    fn apply(
+        vote_state: &VoteState,
+        instruction: &VoteInstruction,
+        slot_hashes: Option<&[(Slot, Hash)]>,
+        slot: Slot,
+    ) -> Result<VoteStateReplayer, ReplayError> {
+        let mut replayer = VoteStateReplayer::new(vote_state.clone(), 0);
+        replayer.apply(ReplayStep {
+            clock: &clock(slot),
+            instruction,
+            accounts: &[fixture_pubkey(0), fixture_pubkey(9)],
+            slot_hashes,
+        })?;
+        Ok(replayer)
+    }
+
+    #[test]
+    fn test_replay_from_initialize() {
+        let vote_init = VoteInit {
+            node_pubkey: fixture_pubkey(1),
+            authorized_voter: fixture_pubkey(2),
+            authorized_withdrawer: fixture_pubkey(3),
+            commission: 10,
+        };
+        let instructions = [
+            VoteInstruction::InitializeAccount(vote_init),
+            VoteInstruction::Vote(Vote::new(vec![1, 2], fixture_bank_hash(2))),
+            VoteInstruction::UpdateCommission(5),
+            VoteInstruction::UpdateValidatorIdentity,
+            VoteInstruction::Authorize(fixture_pubkey(4), VoteAuthorize::Withdrawer),
+            VoteInstruction::Withdraw(1),
+        ];
+        let clock = clock(3);
+        let accounts = [fixture_pubkey(0), fixture_pubkey(9)];
+        let vote_state = replay(
+            VoteState::default(),
+            instructions.iter().map(|instruction| ReplayStep {
+                clock: &clock,
+                instruction,
+                accounts: &accounts,
+                slot_hashes: None,
+            }),
+        )
+        .unwrap();
+
+        let mut expected = sdk_vote(&VoteState::new(&vote_init, &clock), &[1, 2], 3);
+        expected.commission = 5;
+        expected.node_pubkey = fixture_pubkey(9);
+        expected.authorized_withdrawer = fixture_pubkey(4);
+        assert_eq!(vote_state, expected);
+    }
+
+    #[test]
+    fn test_failed_step() {
+        let instruction = VoteInstruction::Vote(Vote::new(vec![], fixture_bank_hash(0)));
+        assert_eq!(
+            apply(&initialized_vote_state(), &instruction, None, 7).unwrap_err(),
+            ReplayError::Instruction {
+                slot: 7,
+                err: VoteError::EmptySlots.into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_check_snapshot() {
+        let vote_state = initialized_vote_state();
+        let instruction = VoteInstruction::Vote(Vote::new(vec![1], fixture_bank_hash(1)));
+        let replayer = apply(&vote_state, &instruction, None, 2).unwrap();
+        let expected = sdk_vote(&vote_state, &[1], 2);
+        assert_eq!(replayer.check_snapshot(&expected, 2), Ok(()));
+        assert_eq!(
+            replayer.check_snapshot(&vote_state, 2),
+            Err(ReplayError::SnapshotMismatch(2))
+        );
+    }
+
+    #[test]
+    fn test_vote_drops_slots_before_slot_hashes() {
+        let vote_state = VoteStateBuilder::new().vote(10, 1).build();
+        let instruction = VoteInstruction::Vote(Vote::new(vec![48, 49, 50], fixture_bank_hash(50)));
+        let slot_hashes = fixture_slot_hashes(49..=50);
+        let snapshot = sdk_vote(&vote_state, &[49, 50], 51);
+
+        let replayer = apply(&vote_state, &instruction, Some(&slot_hashes), 51).unwrap();
+        assert_eq!(replayer.check_snapshot(&snapshot, 51), Ok(()));
+        let unfiltered = apply(&vote_state, &instruction, None, 51).unwrap();
+        assert_eq!(
+            unfiltered.check_snapshot(&snapshot, 51),
+            Err(ReplayError::SnapshotMismatch(51))
+        );
+
+        let slot_hashes = fixture_slot_hashes(51..=60);
+        assert_eq!(
+            apply(&vote_state, &instruction, Some(&slot_hashes), 61).unwrap_err(),
+            ReplayError::Instruction {
+                slot: 61,
+                err: VoteError::VotesTooOldAllFiltered.into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_update_filtered_against_slot_hashes() {
+        // Voted on every even slot from 2 through 80, rooted at 18.
+        let vote_state = (1..=40)
+            .fold(VoteStateBuilder::new(), |builder, n| builder.vote(2 * n, 1))
+            .build();
+        let snapshot = sdk_vote(&vote_state, &[82], 83);
+        let mut vote_state_update = VoteStateUpdate::new(
+            snapshot.votes.iter().map(|vote| vote.lockout).collect(),
+            Some(21),
+            fixture_bank_hash(82),
+        );
+        // Slot 25 was never voted on and predates `SlotHashes`.
+        vote_state_update.lockouts.insert(2, Lockout::new(25));
+        let instruction = VoteInstruction::UpdateVoteState(vote_state_update);
+        let slot_hashes = fixture_slot_hashes(30..=82);
+
+        let replayer = apply(&vote_state, &instruction, Some(&slot_hashes), 83).unwrap();
+        assert_eq!(replayer.check_snapshot(&snapshot, 83), Ok(()));
+        assert!(apply(&vote_state, &instruction, None, 83).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Epoch rewards inputs.
+
+use solana_program::{clock::Epoch, vote::state::VoteState};
+
+/// The vote-account inputs to the epoch rewards calculation for one epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochRewardInputs {
+    pub epoch: Epoch,
+    /// Total credits observed before the epoch began.
+    pub prev_credits: u64,
+    /// Total credits observed by the end of the epoch.
+    pub credits: u64,
+    /// Commission applied when splitting the epoch's rewards.
+    pub commission: u8,
+}
+
+impl EpochRewardInputs {
+    /// Credits earned during the epoch.
+    pub fn earned_credits(&self) -> u64 {
+        self.credits.saturating_sub(self.prev_credits)
+    }
+
+    /// Points earned by `stake` delegated for the whole epoch, as computed
+    /// by the runtime before converting points to lamports.
+    pub fn points(&self, stake: u64) -> u128 {
+        u128::from(stake).saturating_mul(u128::from(self.earned_credits()))
+    }
+}
+
+/// Returns the rewards inputs for `epoch`, or `None` if the vote account has
+/// no credits history entry for that epoch.
+///
+/// The commission is read from `vote_state` as-is, so callers reproducing a
+/// past payout must pass the state as it was at the rewarded epoch boundary.
+pub fn epoch_reward_inputs(vote_state: &VoteState, epoch: Epoch) -> Option<EpochRewardInputs> {
+    vote_state
+        .epoch_credits()
+        .iter()
+        .find(|(credits_epoch, _, _)| *credits_epoch == epoch)
+        .map(|(epoch, credits, prev_credits)| EpochRewardInputs {
+            epoch: *epoch,
+            prev_credits: *prev_credits,
+            credits: *credits,
+            commission: vote_state.commission,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test_support::VoteStateBuilder};
+
+    #[test]
+    fn test_epoch_reward_inputs() {
+        let vote_state = VoteStateBuilder::new()
+            .commission(8)
+            .epoch_credits(vec![(3, 40, 10), (5, 100, 40)])
+            .build();
+        let inputs = epoch_reward_inputs(&vote_state, 5).unwrap();
+        assert_eq!(
+            inputs,
+            EpochRewardInputs {
+                epoch: 5,
+                prev_credits: 40,
+                credits: 100,
+                commission: 8,
+            }
+        );
+        assert_eq!(inputs.earned_credits(), 60);
+        assert_eq!(inputs.points(1_000), 60_000);
+        assert_eq!(
+            epoch_reward_inputs(&vote_state, 3)
+                .unwrap()
+                .earned_credits(),
+            30
+        );
+        // No entry for an epoch without votes.
+        assert_eq!(epoch_reward_inputs(&vote_state, 4), None);
+        assert_eq!(epoch_reward_inputs(&vote_state, 6), None);
+    }
+
+    #[test]
+    fn test_points_do_not_overflow() {
+        let inputs = EpochRewardInputs {
+            epoch: 0,
+            prev_credits: 0,
+            credits: u64::MAX,
+            commission: 0,
+        };
+        assert_eq!(
+            inputs.points(u64::MAX),
+            u128::from(u64::MAX) * u128::from(u64::MAX)
+        );
+        let regressed = EpochRewardInputs {
+            prev_credits: 10,
+            credits: 5,
+            ..inputs
+        };
+        assert_eq!(regressed.earned_credits(), 0);
+    }
+}
@@ -0,0 +1,221 @@
+//! When authorized voter changes take effect.
+
+use {
+    crate::packet::{vote_transaction_message, OversizeBy},
+    solana_program::{
+        clock::{Clock, Epoch, Slot},
+        epoch_schedule::EpochSchedule,
+        hash::Hash,
+        instruction::Instruction,
+        pubkey::Pubkey,
+        vote::{
+            instruction as vote_instruction,
+            state::{VoteAuthorize, VoteState},
+        },
+    },
+    thiserror::Error,
+};
+
+/// When an `Authorize(Voter)` submitted now would take effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoterRotation {
+    /// First epoch the new voter signs for, the one after the upcoming
+    /// leader schedule epoch.
+    pub activation_epoch: Epoch,
+    /// First slot of `activation_epoch`.
+    pub activation_slot: Slot,
+    /// Slots from now until `activation_slot`.
+    pub slots_until_active: u64,
+}
+
+/// Reasons an `Authorize(Voter)` submitted now would fail.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum VoterRotationError {
+    #[error("a voter change is already pending for epoch {0}")]
+    TooSoonToReauthorize(Epoch),
+}
+
+/// Computes when an `Authorize(Voter)` landing at `clock` takes effect.
+///
+/// The program targets the epoch after `clock.leader_schedule_epoch`, which
+/// is usually two epochs out.
+pub fn voter_rotation(
+    vote_state: &VoteState,
+    clock: &Clock,
+    epoch_schedule: &EpochSchedule,
+) -> Result<VoterRotation, VoterRotationError> {
+    let activation_epoch = clock.leader_schedule_epoch.saturating_add(1);
+    if vote_state.authorized_voters().contains(activation_epoch) {
+        return Err(VoterRotationError::TooSoonToReauthorize(activation_epoch));
+    }
+    let activation_slot = epoch_schedule.get_first_slot_in_epoch(activation_epoch);
+    Ok(VoterRotation {
+        activation_epoch,
+        activation_slot,
+        slots_until_active: activation_slot.saturating_sub(clock.slot),
+    })
+}
+
+/// A new authorized voter for one vote account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoterRotationRequest {
+    pub vote_pubkey: Pubkey,
+    pub new_authorized_voter: Pubkey,
+}
+
+/// Builds `Authorize(Voter)` instructions signed by `authorized_withdrawer`
+/// for every request, grouped into transactions paid by `fee_payer` that
+/// each fit in one packet.
+///
+/// With `checked`, `AuthorizeChecked` is used instead, so each new voter
+/// also signs and fewer rotations fit per transaction.
+pub fn batch_voter_rotations(
+    authorized_withdrawer: &Pubkey,
+    fee_payer: &Pubkey,
+    requests: &[VoterRotationRequest],
+    checked: bool,
+) -> Result<Vec<Vec<Instruction>>, OversizeBy> {
+    let mut batches: Vec<Vec<Instruction>> = vec![];
+    let mut batch: Vec<Instruction> = vec![];
+    for request in requests {
+        let instruction = if checked {
+            vote_instruction::authorize_checked(
+                &request.vote_pubkey,
+                authorized_withdrawer,
+                &request.new_authorized_voter,
+                VoteAuthorize::Voter,
+            )
+        } else {
+            vote_instruction::authorize(
+                &request.vote_pubkey,
+                authorized_withdrawer,
+                &request.new_authorized_voter,
+                VoteAuthorize::Voter,
+            )
+        };
+        batch.push(instruction);
+        if let Err(oversize) = check_batch(&batch, fee_payer) {
+            if batch.len() == 1 {
+                return Err(oversize);
+            }
+            let instruction = batch.pop().expect("batch is not empty");
+            batches.push(std::mem::take(&mut batch));
+            batch.push(instruction);
+            check_batch(&batch, fee_payer)?;
+        }
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
+
+fn check_batch(batch: &[Instruction], fee_payer: &Pubkey) -> Result<(), OversizeBy> {
+    // The blockhash does not affect the message size.
+    vote_transaction_message(batch, fee_payer, &Hash::default()).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            packet::fits_in_packet,
+            test_support::{fixture_clock, initialized_vote_state, FIXTURE_SLOTS_PER_EPOCH},
+        },
+        solana_program::message::{Message, VersionedMessage},
+    };
+
+    #[test]
+    fn test_voter_rotation_targets_epoch_after_leader_schedule() {
+        let epoch_schedule =
+            EpochSchedule::custom(FIXTURE_SLOTS_PER_EPOCH, FIXTURE_SLOTS_PER_EPOCH, false);
+        let clock = fixture_clock(5);
+        let rotation = voter_rotation(&initialized_vote_state(), &clock, &epoch_schedule).unwrap();
+        assert_eq!(rotation.activation_epoch, clock.leader_schedule_epoch + 1);
+        assert_eq!(
+            rotation.activation_slot,
+            rotation.activation_epoch * FIXTURE_SLOTS_PER_EPOCH
+        );
+        assert_eq!(
+            rotation.slots_until_active,
+            rotation.activation_slot - clock.slot
+        );
+    }
+
+    #[test]
+    fn test_voter_rotation_already_pending() {
+        let mut vote_state = initialized_vote_state();
+        let clock = fixture_clock(5);
+        let activation_epoch = clock.leader_schedule_epoch + 1;
+        vote_state
+            .set_new_authorized_voter(&Pubkey::new_unique(), clock.epoch, activation_epoch, |_| {
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            voter_rotation(&vote_state, &clock, &EpochSchedule::default()),
+            Err(VoterRotationError::TooSoonToReauthorize(activation_epoch))
+        );
+    }
+
+    fn message(batch: &[Instruction], fee_payer: &Pubkey) -> VersionedMessage {
+        VersionedMessage::Legacy(Message::new(batch, Some(fee_payer)))
+    }
+
+    #[test]
+    fn test_batch_voter_rotations_split_at_packet_limit() {
+        let authorized_withdrawer = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let requests: Vec<_> = (0..64)
+            .map(|_| VoterRotationRequest {
+                vote_pubkey: Pubkey::new_unique(),
+                new_authorized_voter: Pubkey::new_unique(),
+            })
+            .collect();
+        for checked in [false, true] {
+            let batches =
+                batch_voter_rotations(&authorized_withdrawer, &fee_payer, &requests, checked)
+                    .unwrap();
+            assert!(batches.len() > 1, "checked: {checked}");
+            // Every rotation is present, in request order.
+            let vote_pubkeys: Vec<_> = batches
+                .iter()
+                .flatten()
+                .map(|instruction| instruction.accounts[0].pubkey)
+                .collect();
+            assert_eq!(
+                vote_pubkeys,
+                requests
+                    .iter()
+                    .map(|request| request.vote_pubkey)
+                    .collect::<Vec<_>>()
+            );
+            for (batch, next) in batches.iter().zip(batches.iter().skip(1)) {
+                assert_eq!(fits_in_packet(&message(batch, &fee_payer)), Ok(()));
+                // Each batch is full: one more rotation would not fit.
+                let mut overfull = batch.clone();
+                overfull.push(next[0].clone());
+                assert!(fits_in_packet(&message(&overfull, &fee_payer)).is_err());
+            }
+            assert_eq!(
+                fits_in_packet(&message(batches.last().unwrap(), &fee_payer)),
+                Ok(())
+            );
+        }
+        // New voters sign checked rotations, so fewer fit per batch.
+        let unchecked =
+            batch_voter_rotations(&authorized_withdrawer, &fee_payer, &requests, false).unwrap();
+        let checked =
+            batch_voter_rotations(&authorized_withdrawer, &fee_payer, &requests, true).unwrap();
+        assert!(checked[0].len() < unchecked[0].len());
+    }
+
+    #[test]
+    fn test_batch_voter_rotations_empty() {
+        assert_eq!(
+            batch_voter_rotations(&Pubkey::new_unique(), &Pubkey::new_unique(), &[], true),
+            Ok(vec![])
+        );
+    }
+}
@@ -0,0 +1,540 @@
+//! Flat rows for indexers, such as Geyser Postgres plugins.
+//!
+//! Each row type is one table. Column names are the field names, in
+//! field order, and are listed in [`Row::COLUMNS`]; they only ever gain
+//! columns at the end. Nested vote state fields get their own tables keyed
+//! by vote account and slot.
+
+use {
+    crate::instruction::InstructionMeta,
+    solana_program::{
+        clock::{Epoch, Slot, UnixTimestamp},
+        hash::Hash,
+        pubkey::Pubkey,
+        vote::{
+            instruction::VoteInstruction,
+            state::{VoteAuthorize, VoteState, VoteStateUpdate},
+        },
+    },
+};
+
+/// Table name and columns of a row type.
+pub trait Row {
+    const TABLE: &'static str;
+    const COLUMNS: &'static [&'static str];
+}
+
+/// One vote account as of `slot`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteAccountRow {
+    pub vote_pubkey: Pubkey,
+    pub slot: Slot,
+    pub node_pubkey: Pubkey,
+    pub authorized_withdrawer: Pubkey,
+    pub commission: u8,
+    pub root_slot: Option<Slot>,
+    pub last_voted_slot: Option<Slot>,
+    pub tower_depth: u8,
+    pub credits: u64,
+    pub last_timestamp_slot: Slot,
+    pub last_timestamp: UnixTimestamp,
+}
+
+impl Row for VoteAccountRow {
+    const TABLE: &'static str = "vote_account";
+    const COLUMNS: &'static [&'static str] = &[
+        "vote_pubkey",
+        "slot",
+        "node_pubkey",
+        "authorized_withdrawer",
+        "commission",
+        "root_slot",
+        "last_voted_slot",
+        "tower_depth",
+        "credits",
+        "last_timestamp_slot",
+        "last_timestamp",
+    ];
+}
+
+/// One authorized voter entry, including pending ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuthorizedVoterRow {
+    pub vote_pubkey: Pubkey,
+    pub slot: Slot,
+    /// First epoch `authorized_voter` votes in.
+    pub epoch: Epoch,
+    pub authorized_voter: Pubkey,
+}
+
+impl Row for AuthorizedVoterRow {
+    const TABLE: &'static str = "vote_authorized_voter";
+    const COLUMNS: &'static [&'static str] = &["vote_pubkey", "slot", "epoch", "authorized_voter"];
+}
+
+/// One epoch of credit history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochCreditsRow {
+    pub vote_pubkey: Pubkey,
+    pub slot: Slot,
+    pub epoch: Epoch,
+    pub credits: u64,
+    pub prev_credits: u64,
+}
+
+impl Row for EpochCreditsRow {
+    const TABLE: &'static str = "vote_epoch_credits";
+    const COLUMNS: &'static [&'static str] =
+        &["vote_pubkey", "slot", "epoch", "credits", "prev_credits"];
+}
+
+/// One tower entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockoutRow {
+    pub vote_pubkey: Pubkey,
+    pub slot: Slot,
+    /// Position in the tower, 0 for the oldest vote.
+    pub position: u8,
+    pub voted_slot: Slot,
+    pub confirmation_count: u32,
+    pub latency: u8,
+}
+
+impl Row for LockoutRow {
+    const TABLE: &'static str = "vote_lockout";
+    const COLUMNS: &'static [&'static str] = &[
+        "vote_pubkey",
+        "slot",
+        "position",
+        "voted_slot",
+        "confirmation_count",
+        "latency",
+    ];
+}
+
+/// Rows for one vote account as of a slot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteStateRows {
+    pub account: VoteAccountRow,
+    pub authorized_voters: Vec<AuthorizedVoterRow>,
+    pub epoch_credits: Vec<EpochCreditsRow>,
+    pub lockouts: Vec<LockoutRow>,
+}
+
+/// Flattens `vote_state`, observed at `slot`, into rows.
+pub fn vote_state_rows(vote_pubkey: &Pubkey, slot: Slot, vote_state: &VoteState) -> VoteStateRows {
+    let vote_pubkey = *vote_pubkey;
+    VoteStateRows {
+        account: VoteAccountRow {
+            vote_pubkey,
+            slot,
+            node_pubkey: vote_state.node_pubkey,
+            authorized_withdrawer: vote_state.authorized_withdrawer,
+            commission: vote_state.commission,
+            root_slot: vote_state.root_slot,
+            last_voted_slot: vote_state.last_voted_slot(),
+            tower_depth: u8::try_from(vote_state.votes.len()).unwrap_or(u8::MAX),
+            credits: vote_state.credits(),
+            last_timestamp_slot: vote_state.last_timestamp.slot,
+            last_timestamp: vote_state.last_timestamp.timestamp,
+        },
+        authorized_voters: vote_state
+            .authorized_voters()
+            .iter()
+            .map(|(epoch, authorized_voter)| AuthorizedVoterRow {
+                vote_pubkey,
+                slot,
+                epoch: *epoch,
+                authorized_voter: *authorized_voter,
+            })
+            .collect(),
+        epoch_credits: vote_state
+            .epoch_credits
+            .iter()
+            .map(|(epoch, credits, prev_credits)| EpochCreditsRow {
+                vote_pubkey,
+                slot,
+                epoch: *epoch,
+                credits: *credits,
+                prev_credits: *prev_credits,
+            })
+            .collect(),
+        lockouts: vote_state
+            .votes
+            .iter()
+            .zip(0..=u8::MAX)
+            .map(|(landed_vote, position)| LockoutRow {
+                vote_pubkey,
+                slot,
+                position,
+                voted_slot: landed_vote.slot(),
+                confirmation_count: landed_vote.confirmation_count(),
+                latency: landed_vote.latency,
+            })
+            .collect(),
+    }
+}
+
+/// One vote instruction, with the columns its variant does not use left
+/// empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteInstructionRow {
+    pub vote_pubkey: Pubkey,
+    /// Slot the instruction landed in.
+    pub slot: Slot,
+    /// Variant name, as in [`InstructionMeta::name`].
+    pub instruction: &'static str,
+    /// Newest slot voted on.
+    pub last_voted_slot: Option<Slot>,
+    pub root_slot: Option<Slot>,
+    pub bank_hash: Option<Hash>,
+    pub switch_proof_hash: Option<Hash>,
+    pub timestamp: Option<UnixTimestamp>,
+    /// `voter` or `withdrawer`.
+    pub authority_kind: Option<&'static str>,
+    pub new_authority: Option<Pubkey>,
+    pub commission: Option<u8>,
+    pub lamports: Option<u64>,
+    /// Validator identity set by `InitializeAccount` or
+    /// `UpdateValidatorIdentity`.
+    pub node_pubkey: Option<Pubkey>,
+}
+
+impl Row for VoteInstructionRow {
+    const TABLE: &'static str = "vote_instruction";
+    const COLUMNS: &'static [&'static str] = &[
+        "vote_pubkey",
+        "slot",
+        "instruction",
+        "last_voted_slot",
+        "root_slot",
+        "bank_hash",
+        "switch_proof_hash",
+        "timestamp",
+        "authority_kind",
+        "new_authority",
+        "commission",
+        "lamports",
+        "node_pubkey",
+    ];
+}
+
+/// Flattens `instruction`, landed in `slot` with instruction account keys
+/// `accounts`, into a row. Returns `None` if `accounts` is empty.
+///
+/// New authorities and identities passed as accounts, as in
+/// `AuthorizeChecked` and `UpdateValidatorIdentity`, are read from
+/// `accounts`. A new identity goes in `node_pubkey`, not `new_authority`.
+pub fn vote_instruction_row(
+    slot: Slot,
+    instruction: &VoteInstruction,
+    accounts: &[Pubkey],
+) -> Option<VoteInstructionRow> {
+    let mut row = VoteInstructionRow {
+        vote_pubkey: *accounts.first()?,
+        slot,
+        instruction: InstructionMeta::of(instruction).name,
+        last_voted_slot: None,
+        root_slot: None,
+        bank_hash: None,
+        switch_proof_hash: None,
+        timestamp: None,
+        authority_kind: None,
+        new_authority: None,
+        commission: None,
+        lamports: None,
+        node_pubkey: None,
+    };
+    let account = |index: usize| accounts.get(index).copied();
+    let mut authorize = |vote_authorize: VoteAuthorize, new_authority: Option<Pubkey>| {
+        row.authority_kind = Some(match vote_authorize {
+            VoteAuthorize::Voter => "voter",
+            VoteAuthorize::Withdrawer => "withdrawer",
+        });
+        row.new_authority = new_authority;
+    };
+    match instruction {
+        VoteInstruction::InitializeAccount(vote_init) => {
+            row.commission = Some(vote_init.commission);
+            row.node_pubkey = Some(vote_init.node_pubkey);
+        }
+        VoteInstruction::Authorize(new_authority, vote_authorize) => {
+            authorize(*vote_authorize, Some(*new_authority));
+        }
+        VoteInstruction::AuthorizeChecked(vote_authorize) => {
+            authorize(*vote_authorize, account(3));
+        }
+        VoteInstruction::AuthorizeWithSeed(args) => {
+            authorize(args.authorization_type, Some(args.new_authority));
+        }
+        VoteInstruction::AuthorizeCheckedWithSeed(args) => {
+            authorize(args.authorization_type, account(3));
+        }
+        VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => {
+            row.last_voted_slot = vote.slots.last().copied();
+            row.bank_hash = Some(vote.hash);
+            row.timestamp = vote.timestamp;
+        }
+        VoteInstruction::UpdateVoteState(vote_state_update)
+        | VoteInstruction::UpdateVoteStateSwitch(vote_state_update, _)
+        | VoteInstruction::CompactUpdateVoteState(vote_state_update)
+        | VoteInstruction::CompactUpdateVoteStateSwitch(vote_state_update, _) => {
+            fill_update(&mut row, vote_state_update);
+        }
+        VoteInstruction::UpdateValidatorIdentity => row.node_pubkey = account(1),
+        VoteInstruction::UpdateCommission(commission) => row.commission = Some(*commission),
+        VoteInstruction::Withdraw(lamports) => row.lamports = Some(*lamports),
+    }
+    if let VoteInstruction::VoteSwitch(_, proof_hash)
+    | VoteInstruction::UpdateVoteStateSwitch(_, proof_hash)
+    | VoteInstruction::CompactUpdateVoteStateSwitch(_, proof_hash) = instruction
+    {
+        row.switch_proof_hash = Some(*proof_hash);
+    }
+    Some(row)
+}
+
+fn fill_update(row: &mut VoteInstructionRow, vote_state_update: &VoteStateUpdate) {
+    row.last_voted_slot = vote_state_update.last_voted_slot();
+    row.root_slot = vote_state_update.root;
+    row.bank_hash = Some(vote_state_update.hash);
+    row.timestamp = vote_state_update.timestamp;
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, populated_vote_state},
+        solana_program::{
+            hash::hash,
+            vote::state::{
+                Vote, VoteAuthorizeCheckedWithSeedArgs, VoteAuthorizeWithSeedArgs, VoteInit,
+            },
+        },
+    };
+
+    /// Field names of `row`, in declaration order, from its pretty `Debug`
+    /// output.
+    fn field_names(row: &impl std::fmt::Debug) -> Vec<String> {
+        format!("{row:#?}")
+            .lines()
+            .filter_map(|line| {
+                let field = line.strip_prefix("    ")?;
+                if field.starts_with(' ') {
+                    return None;
+                }
+                let (name, _) = field.split_once(": ")?;
+                Some(name.to_string())
+            })
+            .collect()
+    }
+
+    fn assert_columns<R: Row + std::fmt::Debug>(row: &R) {
+        assert_eq!(field_names(row), R::COLUMNS);
+    }
+
+    #[test]
+    fn test_columns_match_fields() {
+        let rows = vote_state_rows(&fixture_pubkey(0), 100, &populated_vote_state(3, 2, 0));
+        assert_columns(&rows.account);
+        assert_columns(&rows.authorized_voters[0]);
+        assert_columns(&rows.epoch_credits[0]);
+        assert_columns(&rows.lockouts[0]);
+        let row =
+            vote_instruction_row(100, &VoteInstruction::Withdraw(1), &[fixture_pubkey(0)]).unwrap();
+        assert_columns(&row);
+    }
+
+    fn row(instruction: VoteInstruction) -> VoteInstructionRow {
+        let accounts: Vec<_> = (0..4).map(fixture_pubkey).collect();
+        vote_instruction_row(7, &instruction, &accounts).unwrap()
+    }
+
+    fn empty_row(instruction: &'static str) -> VoteInstructionRow {
+        VoteInstructionRow {
+            vote_pubkey: fixture_pubkey(0),
+            slot: 7,
+            instruction,
+            last_voted_slot: None,
+            root_slot: None,
+            bank_hash: None,
+            switch_proof_hash: None,
+            timestamp: None,
+            authority_kind: None,
+            new_authority: None,
+            commission: None,
+            lamports: None,
+            node_pubkey: None,
+        }
+    }
+
+    fn named(instruction: &VoteInstruction) -> &'static str {
+        InstructionMeta::of(instruction).name
+    }
+
+    #[test]
+    fn test_no_accounts() {
+        assert_eq!(
+            vote_instruction_row(7, &VoteInstruction::Withdraw(1), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_initialize_account() {
+        let instruction = VoteInstruction::InitializeAccount(VoteInit {
+            node_pubkey: fixture_pubkey(5),
+            authorized_voter: fixture_pubkey(6),
+            authorized_withdrawer: fixture_pubkey(7),
+            commission: 10,
+        });
+        assert_eq!(
+            row(instruction.clone()),
+            VoteInstructionRow {
+                commission: Some(10),
+                node_pubkey: Some(fixture_pubkey(5)),
+                ..empty_row(named(&instruction))
+            }
+        );
+    }
+
+    #[test]
+    fn test_authorize() {
+        let seed_args = VoteAuthorizeWithSeedArgs {
+            authorization_type: VoteAuthorize::Voter,
+            current_authority_derived_key_owner: fixture_pubkey(8),
+            current_authority_derived_key_seed: "seed".to_string(),
+            new_authority: fixture_pubkey(5),
+        };
+        let checked_seed_args = VoteAuthorizeCheckedWithSeedArgs {
+            authorization_type: VoteAuthorize::Withdrawer,
+            current_authority_derived_key_owner: fixture_pubkey(8),
+            current_authority_derived_key_seed: "seed".to_string(),
+        };
+        for (instruction, authority_kind, new_authority) in [
+            (
+                VoteInstruction::Authorize(fixture_pubkey(5), VoteAuthorize::Voter),
+                "voter",
+                fixture_pubkey(5),
+            ),
+            (
+                VoteInstruction::AuthorizeChecked(VoteAuthorize::Withdrawer),
+                "withdrawer",
+                fixture_pubkey(3),
+            ),
+            (
+                VoteInstruction::AuthorizeWithSeed(seed_args),
+                "voter",
+                fixture_pubkey(5),
+            ),
+            (
+                VoteInstruction::AuthorizeCheckedWithSeed(checked_seed_args),
+                "withdrawer",
+                fixture_pubkey(3),
+            ),
+        ] {
+            assert_eq!(
+                row(instruction.clone()),
+                VoteInstructionRow {
+                    authority_kind: Some(authority_kind),
+                    new_authority: Some(new_authority),
+                    ..empty_row(named(&instruction))
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_vote() {
+        let mut vote = Vote::new(vec![10, 11, 12], hash(b"bank"));
+        vote.timestamp = Some(1_600_000_000);
+        let proof_hash = hash(b"proof");
+        for (instruction, switch_proof_hash) in [
+            (VoteInstruction::Vote(vote.clone()), None),
+            (
+                VoteInstruction::VoteSwitch(vote.clone(), proof_hash),
+                Some(proof_hash),
+            ),
+        ] {
+            assert_eq!(
+                row(instruction.clone()),
+                VoteInstructionRow {
+                    last_voted_slot: Some(12),
+                    bank_hash: Some(vote.hash),
+                    switch_proof_hash,
+                    timestamp: vote.timestamp,
+                    ..empty_row(named(&instruction))
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_vote_state() {
+        let mut update = VoteStateUpdate::from(vec![(10, 3), (11, 2), (12, 1)]);
+        update.root = Some(5);
+        update.hash = hash(b"bank");
+        update.timestamp = Some(1_600_000_000);
+        let proof_hash = hash(b"proof");
+        for (instruction, switch_proof_hash) in [
+            (VoteInstruction::UpdateVoteState(update.clone()), None),
+            (
+                VoteInstruction::UpdateVoteStateSwitch(update.clone(), proof_hash),
+                Some(proof_hash),
+            ),
+            (
+                VoteInstruction::CompactUpdateVoteState(update.clone()),
+                None,
+            ),
+            (
+                VoteInstruction::CompactUpdateVoteStateSwitch(update.clone(), proof_hash),
+                Some(proof_hash),
+            ),
+        ] {
+            assert_eq!(
+                row(instruction.clone()),
+                VoteInstructionRow {
+                    last_voted_slot: Some(12),
+                    root_slot: Some(5),
+                    bank_hash: Some(update.hash),
+                    switch_proof_hash,
+                    timestamp: update.timestamp,
+                    ..empty_row(named(&instruction))
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_validator_identity() {
+        let instruction = VoteInstruction::UpdateValidatorIdentity;
+        assert_eq!(
+            row(instruction.clone()),
+            VoteInstructionRow {
+                node_pubkey: Some(fixture_pubkey(1)),
+                ..empty_row(named(&instruction))
+            }
+        );
+    }
+
+    #[test]
+    fn test_update_commission_and_withdraw() {
+        let instruction = VoteInstruction::UpdateCommission(42);
+        assert_eq!(
+            row(instruction.clone()),
+            VoteInstructionRow {
+                commission: Some(42),
+                ..empty_row(named(&instruction))
+            }
+        );
+        let instruction = VoteInstruction::Withdraw(1_000);
+        assert_eq!(
+            row(instruction.clone()),
+            VoteInstructionRow {
+                lamports: Some(1_000),
+                ..empty_row(named(&instruction))
+            }
+        );
+    }
+}
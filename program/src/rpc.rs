@@ -0,0 +1,226 @@
+//! `getVoteAccounts` response types.
+//!
+//! These mirror the JSON returned by the RPC `getVoteAccounts` method so that
+//! alternative RPC implementations can produce identical output.
+
+use {
+    serde::{Deserialize, Serialize},
+    solana_program::{
+        clock::{Epoch, Slot},
+        pubkey::Pubkey,
+        vote::state::VoteState,
+    },
+};
+
+/// Number of most recent epoch credits entries RPC returns per account.
+pub const MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY: usize = 5;
+
+/// Default slot distance after which a validator is reported as delinquent.
+pub const DELINQUENT_VALIDATOR_SLOT_DISTANCE: u64 = 128;
+
+/// One vote account entry of a `getVoteAccounts` response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteAccountInfo {
+    /// Vote account address, as a base-58 string.
+    pub vote_pubkey: String,
+    /// Validator identity, as a base-58 string.
+    pub node_pubkey: String,
+    /// Stake, in lamports, delegated to this vote account.
+    pub activated_stake: u64,
+    pub commission: u8,
+    /// Whether the account is staked for the current epoch.
+    pub epoch_vote_account: bool,
+    /// Most recent `(epoch, credits, prev_credits)` entries.
+    pub epoch_credits: Vec<(Epoch, u64, u64)>,
+    /// Most recent voted slot, or 0 if the tower is empty.
+    pub last_vote: Slot,
+    /// Root slot, or 0 if there is none.
+    pub root_slot: Slot,
+}
+
+impl RpcVoteAccountInfo {
+    pub fn new(
+        vote_pubkey: &Pubkey,
+        vote_state: &VoteState,
+        activated_stake: u64,
+        epoch_vote_account: bool,
+    ) -> Self {
+        let epoch_credits = vote_state.epoch_credits();
+        let epoch_credits = epoch_credits[epoch_credits
+            .len()
+            .saturating_sub(MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY)..]
+            .to_vec();
+        Self {
+            vote_pubkey: vote_pubkey.to_string(),
+            node_pubkey: vote_state.node_pubkey.to_string(),
+            activated_stake,
+            commission: vote_state.commission,
+            epoch_vote_account,
+            epoch_credits,
+            last_vote: vote_state.last_voted_slot().unwrap_or_default(),
+            root_slot: vote_state.root_slot.unwrap_or_default(),
+        }
+    }
+}
+
+/// A full `getVoteAccounts` response.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteAccountStatus {
+    pub current: Vec<RpcVoteAccountInfo>,
+    pub delinquent: Vec<RpcVoteAccountInfo>,
+}
+
+impl RpcVoteAccountStatus {
+    /// Splits `vote_accounts` into current and delinquent as of `current_slot`,
+    /// the way RPC does.
+    ///
+    /// Delinquent accounts without stake are dropped unless
+    /// `keep_unstaked_delinquents` is set.
+    pub fn new(
+        vote_accounts: impl IntoIterator<Item = RpcVoteAccountInfo>,
+        current_slot: Slot,
+        delinquent_slot_distance: u64,
+        keep_unstaked_delinquents: bool,
+    ) -> Self {
+        let (current, delinquent): (Vec<_>, Vec<_>) =
+            vote_accounts.into_iter().partition(|vote_account_info| {
+                !is_last_vote_delinquent(
+                    vote_account_info.last_vote,
+                    current_slot,
+                    delinquent_slot_distance,
+                )
+            });
+        let delinquent = delinquent
+            .into_iter()
+            .filter(|vote_account_info| {
+                keep_unstaked_delinquents || vote_account_info.activated_stake > 0
+            })
+            .collect();
+        Self {
+            current,
+            delinquent,
+        }
+    }
+}
+
+/// Returns whether RPC would report the account holding `vote_state` as
+/// delinquent at `current_slot`.
+///
+/// Pass [`DELINQUENT_VALIDATOR_SLOT_DISTANCE`] to match the RPC default.
+pub fn is_delinquent(
+    vote_state: &VoteState,
+    current_slot: Slot,
+    delinquent_slot_distance: u64,
+) -> bool {
+    is_last_vote_delinquent(
+        vote_state.last_voted_slot().unwrap_or_default(),
+        current_slot,
+        delinquent_slot_distance,
+    )
+}
+
+fn is_last_vote_delinquent(
+    last_vote: Slot,
+    current_slot: Slot,
+    delinquent_slot_distance: u64,
+) -> bool {
+    if current_slot >= delinquent_slot_distance {
+        last_vote <= current_slot - delinquent_slot_distance
+    } else {
+        last_vote == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, VoteStateBuilder},
+    };
+
+    fn info(n: u8, last_vote: Slot, activated_stake: u64) -> RpcVoteAccountInfo {
+        let vote_state = VoteStateBuilder::new().vote(last_vote, 1).build();
+        RpcVoteAccountInfo::new(&fixture_pubkey(n), &vote_state, activated_stake, true)
+    }
+
+    #[test]
+    fn test_delinquent_boundary() {
+        let distance = DELINQUENT_VALIDATOR_SLOT_DISTANCE;
+        let current_slot = 1_000;
+        assert!(is_last_vote_delinquent(
+            current_slot - distance,
+            current_slot,
+            distance
+        ));
+        assert!(!is_last_vote_delinquent(
+            current_slot - distance + 1,
+            current_slot,
+            distance
+        ));
+        assert!(is_last_vote_delinquent(0, distance, distance));
+    }
+
+    #[test]
+    fn test_delinquent_before_distance() {
+        // Only accounts that never voted are delinquent this early.
+        let distance = DELINQUENT_VALIDATOR_SLOT_DISTANCE;
+        assert!(is_last_vote_delinquent(0, distance - 1, distance));
+        assert!(!is_last_vote_delinquent(1, distance - 1, distance));
+        assert!(is_delinquent(&VoteState::default(), 5, distance));
+        assert!(!is_delinquent(
+            &VoteStateBuilder::new().vote(3, 1).build(),
+            5,
+            distance
+        ));
+    }
+
+    #[test]
+    fn test_status_partition() {
+        let current_slot = 1_000;
+        let distance = DELINQUENT_VALIDATOR_SLOT_DISTANCE;
+        let accounts = [
+            info(1, current_slot - distance + 1, 10),
+            info(2, current_slot - distance, 10),
+            info(3, current_slot - distance, 0),
+        ];
+        assert_eq!(
+            RpcVoteAccountStatus::new(accounts.clone(), current_slot, distance, false),
+            RpcVoteAccountStatus {
+                current: vec![accounts[0].clone()],
+                delinquent: vec![accounts[1].clone()],
+            }
+        );
+        assert_eq!(
+            RpcVoteAccountStatus::new(accounts.clone(), current_slot, distance, true),
+            RpcVoteAccountStatus {
+                current: vec![accounts[0].clone()],
+                delinquent: vec![accounts[1].clone(), accounts[2].clone()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_epoch_credits_truncated() {
+        let epoch_credits: Vec<_> = (0..8)
+            .map(|epoch| (epoch, epoch * 10 + 10, epoch * 10))
+            .collect();
+        let vote_state = VoteStateBuilder::new()
+            .epoch_credits(epoch_credits.clone())
+            .build();
+        let info = RpcVoteAccountInfo::new(&fixture_pubkey(1), &vote_state, 0, false);
+        assert_eq!(
+            info.epoch_credits,
+            epoch_credits[8 - MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY..]
+        );
+
+        let short = VoteStateBuilder::new()
+            .epoch_credits(epoch_credits[..2].to_vec())
+            .build();
+        let info = RpcVoteAccountInfo::new(&fixture_pubkey(1), &short, 0, false);
+        assert_eq!(info.epoch_credits, epoch_credits[..2]);
+    }
+}
@@ -0,0 +1,464 @@
+//! Bounds-checked decoding of untrusted vote account and instruction data.
+//!
+//! The raw bytes are walked once to check every length prefix before any
+//! owned type is built, so crafted data cannot force large allocations.
+
+use {
+    crate::{
+        filters::VERSION_TAG_LEN,
+        instruction::InstructionMeta,
+        layout::PRIOR_VOTERS_LEN,
+        varint::{self, VarintError},
+        verify::PACKET_DATA_SIZE,
+        versioned::decode_current,
+        wire,
+    },
+    solana_program::{
+        hash::HASH_BYTES,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+        vote::{
+            instruction::VoteInstruction,
+            state::{VoteState, VoteStateVersions, MAX_EPOCH_CREDITS_HISTORY, MAX_LOCKOUT_HISTORY},
+        },
+    },
+    std::ops::Deref,
+    thiserror::Error,
+};
+
+/// Most authorized voter entries a vote account is accepted with.
+pub const MAX_AUTHORIZED_VOTERS: usize = 32;
+
+const EPOCH_BYTES: usize = 8;
+const SLOT_BYTES: usize = 8;
+/// Serialized `Lockout`: slot and confirmation count.
+const LOCKOUT_BYTES: usize = SLOT_BYTES + 4;
+/// Serialized `LandedVote`: latency and lockout.
+const LANDED_VOTE_BYTES: usize = 1 + LOCKOUT_BYTES;
+/// Serialized 0.23.5 `prior_voters`: `(Pubkey, Epoch, Epoch, Slot)` entries
+/// and idx.
+const PRIOR_VOTERS_0_23_5_BYTES: usize =
+    PRIOR_VOTERS_LEN * (PUBKEY_BYTES + 2 * EPOCH_BYTES + SLOT_BYTES) + 8;
+/// Serialized `prior_voters` entries, `(Pubkey, Epoch, Epoch)` each. The idx
+/// and is_empty follow.
+const PRIOR_VOTERS_BUF_BYTES: usize = PRIOR_VOTERS_LEN * (PUBKEY_BYTES + 2 * EPOCH_BYTES);
+/// Serialized `(Epoch, u64, u64)`.
+const EPOCH_CREDITS_BYTES: usize = EPOCH_BYTES + 16;
+/// Most legacy `Vote` slots that fit in a packet. The runtime does not cap
+/// them at `MAX_LOCKOUT_HISTORY`.
+const MAX_VOTE_SLOTS: usize = PACKET_DATA_SIZE as usize / SLOT_BYTES;
+
+/// Reasons untrusted vote account or instruction data is rejected.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SanitizeError {
+    #[error("data truncated at byte {0}")]
+    Truncated(usize),
+    #[error("unknown vote state version {0}")]
+    UnknownVersion(u32),
+    #[error("{0} votes, at most {MAX_LOCKOUT_HISTORY} allowed")]
+    TooManyVotes(u64),
+    #[error("{0} vote slots do not fit in a packet")]
+    TooManyVoteSlots(u64),
+    #[error("prior voters index {0} is out of range")]
+    PriorVotersIndex(u64),
+    #[error("confirmation count {0} exceeds {MAX_LOCKOUT_HISTORY}")]
+    ConfirmationTooLarge(u32),
+    #[error("{0} epoch credits entries, at most {MAX_EPOCH_CREDITS_HISTORY} allowed")]
+    TooManyEpochCredits(u64),
+    #[error("{0} authorized voters, at most {MAX_AUTHORIZED_VOTERS} allowed")]
+    TooManyAuthorizedVoters(u64),
+    #[error("vote account is not initialized")]
+    Uninitialized,
+    #[error("data is malformed")]
+    InvalidData,
+}
+
+/// A `VoteState` decoded from data that passed [`sanitize_vote_account_data`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SanitizedVoteState(VoteState);
+
+impl SanitizedVoteState {
+    pub fn into_inner(self) -> VoteState {
+        self.0
+    }
+}
+
+impl Deref for SanitizedVoteState {
+    type Target = VoteState;
+
+    fn deref(&self) -> &VoteState {
+        &self.0
+    }
+}
+
+/// Checks the structural bounds of `data`, including confirmation counts and
+/// the prior voters index, then decodes it into the current `VoteState`.
+pub fn sanitize_vote_account_data(data: &[u8]) -> Result<SanitizedVoteState, SanitizeError> {
+    check_bounds(data)?;
+    decode_current::<VoteStateVersions>(data)
+        .map_err(|_| SanitizeError::InvalidData)?
+        .map(SanitizedVoteState)
+        .ok_or(SanitizeError::Uninitialized)
+}
+
+/// Part of vote account data that [`parse_vote_account_lenient`] reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoteAccountSection {
+    Version,
+    NodePubkey,
+    AuthorizedWithdrawer,
+    Commission,
+    /// Everything else, decoded only when the whole account is sound.
+    VoteState,
+}
+
+/// Whatever could be recovered from possibly damaged vote account data.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LenientVoteAccount {
+    pub version: Option<u32>,
+    pub node_pubkey: Option<Pubkey>,
+    pub authorized_withdrawer: Option<Pubkey>,
+    pub commission: Option<u8>,
+    pub vote_state: Option<VoteState>,
+    /// Sections that could not be read, and why.
+    pub failures: Vec<(VoteAccountSection, SanitizeError)>,
+}
+
+/// Reads the version tag, node pubkey, authorized withdrawer and commission
+/// from `data` independently, so that truncated or corrupted accounts still
+/// yield the fields that are intact.
+///
+/// The full vote state is included only if [`sanitize_vote_account_data`]
+/// accepts `data`.
+pub fn parse_vote_account_lenient(data: &[u8]) -> LenientVoteAccount {
+    let mut parsed = LenientVoteAccount::default();
+    let version = match Reader::new(data).read_u32() {
+        Ok(version @ 0..=2) => version,
+        Ok(version) => {
+            parsed.failures.push((
+                VoteAccountSection::Version,
+                SanitizeError::UnknownVersion(version),
+            ));
+            return parsed;
+        }
+        Err(err) => {
+            parsed.failures.push((VoteAccountSection::Version, err));
+            return parsed;
+        }
+    };
+    parsed.version = Some(version);
+
+    let node_pubkey_offset = VERSION_TAG_LEN;
+    let authorized_withdrawer_offset = if version == 0 {
+        // node_pubkey, authorized_voter, authorized_voter_epoch, prior_voters
+        node_pubkey_offset + 2 * PUBKEY_BYTES + EPOCH_BYTES + PRIOR_VOTERS_0_23_5_BYTES
+    } else {
+        node_pubkey_offset + PUBKEY_BYTES
+    };
+    let commission_offset = authorized_withdrawer_offset + PUBKEY_BYTES;
+
+    match Reader::at(data, node_pubkey_offset).read_pubkey() {
+        Ok(node_pubkey) => parsed.node_pubkey = Some(node_pubkey),
+        Err(err) => parsed.failures.push((VoteAccountSection::NodePubkey, err)),
+    }
+    match Reader::at(data, authorized_withdrawer_offset).read_pubkey() {
+        Ok(authorized_withdrawer) => parsed.authorized_withdrawer = Some(authorized_withdrawer),
+        Err(err) => parsed
+            .failures
+            .push((VoteAccountSection::AuthorizedWithdrawer, err)),
+    }
+    match Reader::at(data, commission_offset).read_u8() {
+        Ok(commission) => parsed.commission = Some(commission),
+        Err(err) => parsed.failures.push((VoteAccountSection::Commission, err)),
+    }
+    match sanitize_vote_account_data(data) {
+        Ok(vote_state) => parsed.vote_state = Some(vote_state.into_inner()),
+        Err(err) => parsed.failures.push((VoteAccountSection::VoteState, err)),
+    }
+    parsed
+}
+
+/// Checks vote payload bounds in `data`, then decodes it as a
+/// `VoteInstruction`.
+///
+/// Tower-sync lockouts are limited to `MAX_LOCKOUT_HISTORY` entries and
+/// confirmation counts to `MAX_LOCKOUT_HISTORY`, whether the payload uses the
+/// full or compact encoding. Legacy `Vote` slots are only bounded by the
+/// packet size.
+pub fn sanitize_vote_instruction_data(data: &[u8]) -> Result<VoteInstruction, SanitizeError> {
+    check_instruction_bounds(data)?;
+    wire::deserialize(data, PACKET_DATA_SIZE).map_err(|_| SanitizeError::InvalidData)
+}
+
+/// Instruction data decoded by [`sanitize_vote_instruction_data_tolerant`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum TolerantVoteInstruction {
+    Known(VoteInstruction),
+    /// A variant added after this crate was built.
+    Unknown {
+        tag: u32,
+        /// Instruction data following the tag.
+        data: Vec<u8>,
+    },
+}
+
+/// Like [`sanitize_vote_instruction_data`], but returns instructions with a
+/// tag this crate does not know as `Unknown` instead of failing.
+///
+/// Known variants that fail to decode are still errors.
+pub fn sanitize_vote_instruction_data_tolerant(
+    data: &[u8],
+) -> Result<TolerantVoteInstruction, SanitizeError> {
+    let tag = Reader::new(data).read_u32()?;
+    if InstructionMeta::from_tag(tag).is_none() {
+        return Ok(TolerantVoteInstruction::Unknown {
+            tag,
+            data: data[4..].to_vec(),
+        });
+    }
+    sanitize_vote_instruction_data(data).map(TolerantVoteInstruction::Known)
+}
+
+/// Walks the vote-casting `VoteInstruction` variants checking every length
+/// prefix and confirmation count. Other variants are left to bincode.
+fn check_instruction_bounds(data: &[u8]) -> Result<(), SanitizeError> {
+    let mut reader = Reader::new(data);
+    match reader.read_u32()? {
+        // Vote, VoteSwitch
+        2 | 6 => {
+            let slots = reader.read_len(MAX_VOTE_SLOTS, SanitizeError::TooManyVoteSlots)?;
+            reader.skip(slots * SLOT_BYTES + HASH_BYTES)?;
+        }
+        // UpdateVoteState, UpdateVoteStateSwitch
+        8 | 9 => {
+            let lockouts = reader.read_len(MAX_LOCKOUT_HISTORY, SanitizeError::TooManyVotes)?;
+            for _ in 0..lockouts {
+                reader.skip(SLOT_BYTES)?;
+                let confirmation_count = reader.read_u32()?;
+                check_confirmation_count(confirmation_count)?;
+            }
+            reader.skip_root_slot()?;
+            reader.skip(HASH_BYTES)?;
+        }
+        // CompactUpdateVoteState, CompactUpdateVoteStateSwitch
+        12 | 13 => {
+            // root
+            reader.skip(SLOT_BYTES)?;
+            let lockout_offsets = reader.read_short_len(MAX_LOCKOUT_HISTORY)?;
+            for _ in 0..lockout_offsets {
+                reader.skip_varint()?;
+                check_confirmation_count(u32::from(reader.read_u8()?))?;
+            }
+            reader.skip(HASH_BYTES)?;
+        }
+        _ => return Ok(()),
+    }
+    // timestamp, then the switch proof hash for the *Switch variants, are
+    // fixed size and checked by bincode.
+    Ok(())
+}
+
+fn check_confirmation_count(confirmation_count: u32) -> Result<(), SanitizeError> {
+    if confirmation_count as usize > MAX_LOCKOUT_HISTORY {
+        return Err(SanitizeError::ConfirmationTooLarge(confirmation_count));
+    }
+    Ok(())
+}
+
+/// Walks the serialized `VoteStateVersions` checking every length prefix.
+fn check_bounds(data: &[u8]) -> Result<(), SanitizeError> {
+    let mut reader = Reader::new(data);
+    match reader.read_u32()? {
+        0 => {
+            // node_pubkey, authorized_voter, authorized_voter_epoch
+            reader.skip(2 * PUBKEY_BYTES + EPOCH_BYTES)?;
+            reader.skip(PRIOR_VOTERS_0_23_5_BYTES)?;
+            // authorized_withdrawer, commission
+            reader.skip(PUBKEY_BYTES + 1)?;
+            reader.skip_votes(LOCKOUT_BYTES)?;
+            reader.skip_root_slot()?;
+        }
+        version @ (1 | 2) => {
+            // node_pubkey, authorized_withdrawer, commission
+            reader.skip(2 * PUBKEY_BYTES + 1)?;
+            reader.skip_votes(if version == 1 {
+                LOCKOUT_BYTES
+            } else {
+                LANDED_VOTE_BYTES
+            })?;
+            reader.skip_root_slot()?;
+            let authorized_voters = reader.read_len(
+                MAX_AUTHORIZED_VOTERS,
+                SanitizeError::TooManyAuthorizedVoters,
+            )?;
+            reader.skip(authorized_voters * (EPOCH_BYTES + PUBKEY_BYTES))?;
+            reader.skip(PRIOR_VOTERS_BUF_BYTES)?;
+            let prior_voters_idx = reader.read_u64()?;
+            if prior_voters_idx >= PRIOR_VOTERS_LEN as u64 {
+                return Err(SanitizeError::PriorVotersIndex(prior_voters_idx));
+            }
+            // is_empty, checked by bincode.
+            reader.skip(1)?;
+        }
+        version => return Err(SanitizeError::UnknownVersion(version)),
+    }
+    let epoch_credits = reader.read_len(
+        MAX_EPOCH_CREDITS_HISTORY,
+        SanitizeError::TooManyEpochCredits,
+    )?;
+    reader.skip(epoch_credits * EPOCH_CREDITS_BYTES)?;
+    // last_timestamp: slot and timestamp
+    reader.skip(SLOT_BYTES + 8)
+}
+
+/// Cursor over serialized bytes that never reads past the end.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self::at(data, 0)
+    }
+
+    fn at(data: &'a [u8], offset: usize) -> Self {
+        Self { data, offset }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SanitizeError> {
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or(SanitizeError::Truncated(self.offset))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), SanitizeError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn read_u8(&mut self) -> Result<u8, SanitizeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, SanitizeError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_pubkey(&mut self) -> Result<Pubkey, SanitizeError> {
+        let bytes = self.take(PUBKEY_BYTES)?;
+        Ok(Pubkey::try_from(bytes).unwrap())
+    }
+
+    fn read_u64(&mut self) -> Result<u64, SanitizeError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads a collection length, rejecting it with `err` if above `max`.
+    fn read_len(
+        &mut self,
+        max: usize,
+        err: impl FnOnce(u64) -> SanitizeError,
+    ) -> Result<usize, SanitizeError> {
+        let len = self.read_u64()?;
+        if len > max as u64 {
+            return Err(err(len));
+        }
+        Ok(len as usize)
+    }
+
+    /// Reads a `short_vec` length prefix, rejecting it if above `max`.
+    fn read_short_len(&mut self, max: usize) -> Result<usize, SanitizeError> {
+        let (len, bytes) = varint::decode_short_u16_len(self.rest()?, max)
+            .map_err(|err| self.varint_error(err))?;
+        self.offset += bytes;
+        Ok(len)
+    }
+
+    /// Skips a LEB128-encoded `u64`.
+    fn skip_varint(&mut self) -> Result<(), SanitizeError> {
+        let (_, bytes) = varint::decode_u64(self.rest()?).map_err(|err| self.varint_error(err))?;
+        self.offset += bytes;
+        Ok(())
+    }
+
+    fn rest(&self) -> Result<&'a [u8], SanitizeError> {
+        self.data
+            .get(self.offset..)
+            .ok_or(SanitizeError::Truncated(self.offset))
+    }
+
+    fn varint_error(&self, err: VarintError) -> SanitizeError {
+        match err {
+            VarintError::Truncated => SanitizeError::Truncated(self.offset),
+            VarintError::Invalid => SanitizeError::InvalidData,
+            VarintError::LengthExceeded(len) => SanitizeError::TooManyVotes(len as u64),
+        }
+    }
+
+    /// Skips a tower of `vote_bytes`-sized votes, each ending in its
+    /// confirmation count.
+    fn skip_votes(&mut self, vote_bytes: usize) -> Result<(), SanitizeError> {
+        let votes = self.read_len(MAX_LOCKOUT_HISTORY, SanitizeError::TooManyVotes)?;
+        for _ in 0..votes {
+            self.skip(vote_bytes - 4)?;
+            check_confirmation_count(self.read_u32()?)?;
+        }
+        Ok(())
+    }
+
+    fn skip_root_slot(&mut self) -> Result<(), SanitizeError> {
+        match self.read_u8()? {
+            0 => Ok(()),
+            1 => self.skip(SLOT_BYTES),
+            _ => Err(SanitizeError::InvalidData),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_program::{
+            hash::Hash,
+            vote::state::{Lockout, Vote, VoteStateUpdate},
+        },
+    };
+
+    #[test]
+    fn test_legacy_vote_slots_not_capped_by_lockout_history() {
+        let slots = (0..2 * MAX_LOCKOUT_HISTORY as u64).collect();
+        let instruction = VoteInstruction::Vote(Vote::new(slots, Hash::default()));
+        let data = bincode::serialize(&instruction).unwrap();
+        assert_eq!(sanitize_vote_instruction_data(&data), Ok(instruction));
+    }
+
+    #[test]
+    fn test_legacy_vote_slots_bounded_by_packet() {
+        let slots = (0..MAX_VOTE_SLOTS as u64 + 1).collect();
+        let data =
+            bincode::serialize(&VoteInstruction::Vote(Vote::new(slots, Hash::default()))).unwrap();
+        assert_eq!(
+            sanitize_vote_instruction_data(&data),
+            Err(SanitizeError::TooManyVoteSlots(MAX_VOTE_SLOTS as u64 + 1))
+        );
+    }
+
+    #[test]
+    fn test_vote_state_update_capped_by_lockout_history() {
+        let lockouts = (0..MAX_LOCKOUT_HISTORY as u64 + 1)
+            .map(|slot| Lockout::new_with_confirmation_count(slot, 1))
+            .collect();
+        let update = VoteStateUpdate::new(lockouts, None, Hash::default());
+        let data = bincode::serialize(&VoteInstruction::UpdateVoteState(update)).unwrap();
+        assert_eq!(
+            sanitize_vote_instruction_data(&data),
+            Err(SanitizeError::TooManyVotes(MAX_LOCKOUT_HISTORY as u64 + 1))
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! JSON schemas of the serializable response and parsed instruction types.
+
+use {
+    crate::{
+        credits::EpochCredit,
+        lockouts::TowerSummary,
+        parsed::ParsedVoteInstruction,
+        rpc::{RpcVoteAccountInfo, RpcVoteAccountStatus},
+    },
+    schemars::{schema::RootSchema, schema_for},
+};
+
+/// Schema of every serializable type, keyed by type name.
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("EpochCredit", schema_for!(EpochCredit)),
+        ("ParsedVoteInstruction", schema_for!(ParsedVoteInstruction)),
+        ("RpcVoteAccountInfo", schema_for!(RpcVoteAccountInfo)),
+        ("RpcVoteAccountStatus", schema_for!(RpcVoteAccountStatus)),
+        ("TowerSummary", schema_for!(TowerSummary)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::path::PathBuf};
+
+    fn snapshot_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schemas")
+    }
+
+    /// Fails when a published schema changes. Rerun with `UPDATE_SCHEMAS=1`
+    /// to accept the change.
+    #[test]
+    fn test_schema_snapshots() {
+        let update = std::env::var_os("UPDATE_SCHEMAS").is_some();
+        for (name, schema) in schemas() {
+            let path = snapshot_dir().join(format!("{name}.json"));
+            let actual = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+            if update {
+                std::fs::write(&path, actual).unwrap();
+                continue;
+            }
+            let expected = std::fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                actual == expected,
+                "schema of {name} changed; rerun with UPDATE_SCHEMAS=1 to accept it"
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_snapshot_has_a_schema() {
+        let names: Vec<String> = schemas()
+            .into_iter()
+            .map(|(name, _)| format!("{name}.json"))
+            .collect();
+        for entry in std::fs::read_dir(snapshot_dir()).unwrap() {
+            let file_name = entry.unwrap().file_name().into_string().unwrap();
+            assert!(names.contains(&file_name), "stale snapshot {file_name}");
+        }
+    }
+}
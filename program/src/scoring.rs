@@ -0,0 +1,213 @@
+//! Validator scoring over many vote accounts.
+
+use {
+    crate::rewards::epoch_reward_inputs,
+    solana_program::{clock::Epoch, pubkey::Pubkey, vote::state::VoteState},
+    std::cmp::Ordering,
+};
+
+/// Latency statistics over the votes currently in a tower.
+///
+/// Votes recorded before latencies were tracked carry a latency of zero and
+/// are excluded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    pub samples: u8,
+    pub min: u8,
+    pub max: u8,
+    /// Sum of all sampled latencies; divide by `samples` for the mean.
+    pub total: u32,
+}
+
+impl LatencyStats {
+    pub fn from_vote_state(vote_state: &VoteState) -> Self {
+        vote_state
+            .votes
+            .iter()
+            .map(|vote| vote.latency)
+            .filter(|latency| *latency > 0)
+            .fold(Self::default(), |stats, latency| Self {
+                samples: stats.samples.saturating_add(1),
+                min: if stats.samples == 0 {
+                    latency
+                } else {
+                    stats.min.min(latency)
+                },
+                max: stats.max.max(latency),
+                total: stats.total.saturating_add(u32::from(latency)),
+            })
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.samples > 0).then(|| f64::from(self.total) / f64::from(self.samples))
+    }
+}
+
+/// One row of a validator ranking report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorScore {
+    pub vote_pubkey: Pubkey,
+    pub node_pubkey: Pubkey,
+    pub commission: u8,
+    /// Credits earned in the scored epoch; zero if the account has no entry.
+    pub credits: u64,
+    pub latency: LatencyStats,
+    /// Slots between the root and the last voted slot, if both are set.
+    pub root_distance: Option<u64>,
+}
+
+impl ValidatorScore {
+    pub fn new(vote_pubkey: &Pubkey, vote_state: &VoteState, epoch: Epoch) -> Self {
+        Self {
+            vote_pubkey: *vote_pubkey,
+            node_pubkey: vote_state.node_pubkey,
+            commission: vote_state.commission,
+            credits: epoch_reward_inputs(vote_state, epoch)
+                .map(|inputs| inputs.earned_credits())
+                .unwrap_or_default(),
+            latency: LatencyStats::from_vote_state(vote_state),
+            root_distance: vote_state
+                .last_voted_slot()
+                .zip(vote_state.root_slot)
+                .map(|(last_voted_slot, root_slot)| last_voted_slot.saturating_sub(root_slot)),
+        }
+    }
+
+    /// Ranking order: most credits first, then lowest commission, then
+    /// smallest root distance. Ties fall back to the vote pubkey so the
+    /// report is deterministic.
+    pub fn rank_cmp(&self, other: &Self) -> Ordering {
+        other
+            .credits
+            .cmp(&self.credits)
+            .then(self.commission.cmp(&other.commission))
+            .then(
+                self.root_distance
+                    .unwrap_or(u64::MAX)
+                    .cmp(&other.root_distance.unwrap_or(u64::MAX)),
+            )
+            .then(self.vote_pubkey.cmp(&other.vote_pubkey))
+    }
+}
+
+/// Scores every account for `epoch` and returns them best-first.
+///
+/// Allocates a single vector sized from the iterator's lower bound; the vote
+/// states themselves are only borrowed.
+pub fn rank_vote_accounts<'a, I>(vote_accounts: I, epoch: Epoch) -> Vec<ValidatorScore>
+where
+    I: IntoIterator<Item = (&'a Pubkey, &'a VoteState)>,
+{
+    let mut scores: Vec<_> = vote_accounts
+        .into_iter()
+        .map(|(vote_pubkey, vote_state)| ValidatorScore::new(vote_pubkey, vote_state, epoch))
+        .collect();
+    scores.sort_unstable_by(ValidatorScore::rank_cmp);
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, VoteStateBuilder},
+        solana_program::vote::state::MAX_LOCKOUT_HISTORY,
+    };
+
+    #[test]
+    fn test_latency_stats() {
+        let vote_state = VoteStateBuilder::new()
+            .vote(1, 0)
+            .vote(2, 3)
+            .vote(3, 1)
+            .vote(4, 8)
+            .build();
+        let stats = LatencyStats::from_vote_state(&vote_state);
+        assert_eq!(
+            stats,
+            LatencyStats {
+                samples: 3,
+                min: 1,
+                max: 8,
+                total: 12,
+            }
+        );
+        assert_eq!(stats.mean(), Some(4.0));
+        let untracked = VoteStateBuilder::new().vote(1, 0).build();
+        assert_eq!(LatencyStats::from_vote_state(&untracked).mean(), None);
+    }
+
+    #[test]
+    fn test_root_distance() {
+        let unrooted = VoteStateBuilder::new().vote(1, 1).build();
+        assert_eq!(
+            ValidatorScore::new(&fixture_pubkey(20), &unrooted, 0).root_distance,
+            None
+        );
+        let mut builder = VoteStateBuilder::new();
+        for slot in 1..=MAX_LOCKOUT_HISTORY as u64 + 1 {
+            builder = builder.vote(slot, 1);
+        }
+        let rooted = builder.build();
+        assert_eq!(rooted.root_slot, Some(1));
+        assert_eq!(
+            ValidatorScore::new(&fixture_pubkey(20), &rooted, 0).root_distance,
+            Some(MAX_LOCKOUT_HISTORY as u64)
+        );
+    }
+
+    #[test]
+    fn test_rank_order() {
+        let score = |n: u8, credits, commission, root_distance| ValidatorScore {
+            vote_pubkey: fixture_pubkey(n),
+            node_pubkey: fixture_pubkey(n),
+            commission,
+            credits,
+            latency: LatencyStats::default(),
+            root_distance,
+        };
+        let mut scores = vec![
+            score(1, 10, 5, None),
+            score(2, 20, 5, Some(40)),
+            score(3, 20, 0, Some(40)),
+            score(4, 20, 5, Some(31)),
+            score(5, 20, 5, Some(31)),
+            score(6, 10, 5, Some(100)),
+        ];
+        scores.sort_by(ValidatorScore::rank_cmp);
+        let order: Vec<_> = scores.iter().map(|score| score.vote_pubkey).collect();
+        // Credits, then commission, then root distance with unrooted last,
+        // then pubkey.
+        assert_eq!(order, [3, 4, 5, 2, 6, 1].map(fixture_pubkey), "{scores:?}");
+    }
+
+    #[test]
+    fn test_rank_vote_accounts() {
+        let low = VoteStateBuilder::new()
+            .epoch_credits(vec![(2, 10, 0), (3, 15, 10)])
+            .build();
+        let high = VoteStateBuilder::new()
+            .epoch_credits(vec![(3, 40, 0)])
+            .build();
+        let absent = VoteStateBuilder::new()
+            .epoch_credits(vec![(2, 90, 0)])
+            .build();
+        let accounts = [
+            (fixture_pubkey(20), low),
+            (fixture_pubkey(21), high),
+            (fixture_pubkey(22), absent),
+        ];
+        let scores = rank_vote_accounts(accounts.iter().map(|(pubkey, state)| (pubkey, state)), 3);
+        assert_eq!(
+            scores
+                .iter()
+                .map(|score| (score.vote_pubkey, score.credits))
+                .collect::<Vec<_>>(),
+            [
+                (fixture_pubkey(21), 40),
+                (fixture_pubkey(20), 5),
+                (fixture_pubkey(22), 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Derived authority keys for vote accounts held by other programs.
+//!
+//! Seeded keys (`Pubkey::create_with_seed`) are what `AuthorizeWithSeed` and
+//! `AuthorizeCheckedWithSeed` sign for through their base key. Program
+//! derived withdraw authorities let a program such as a stake pool hold a
+//! vote account's withdrawer and sign for it through `invoke_signed`.
+
+use solana_program::{
+    pubkey::{Pubkey, PubkeyError},
+    vote::state::{VoteAuthorizeCheckedWithSeedArgs, VoteAuthorizeWithSeedArgs},
+};
+
+/// Seed prefix of [`find_withdraw_authority_address`].
+pub const WITHDRAW_AUTHORITY_SEED: &[u8] = b"withdraw_authority";
+
+/// Derives the seeded authority key for `base`, `seed` and `owner`.
+pub fn derive_seeded_authority(
+    base: &Pubkey,
+    seed: &str,
+    owner: &Pubkey,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_with_seed(base, seed, owner)
+}
+
+/// Returns whether `authority` is the seeded key for `base`, `seed` and
+/// `owner`.
+pub fn is_seeded_authority(authority: &Pubkey, base: &Pubkey, seed: &str, owner: &Pubkey) -> bool {
+    derive_seeded_authority(base, seed, owner).is_ok_and(|derived| derived == *authority)
+}
+
+/// The current authority an `AuthorizeWithSeed` signed by `base` acts for.
+pub fn authorize_with_seed_authority(
+    args: &VoteAuthorizeWithSeedArgs,
+    base: &Pubkey,
+) -> Result<Pubkey, PubkeyError> {
+    derive_seeded_authority(
+        base,
+        &args.current_authority_derived_key_seed,
+        &args.current_authority_derived_key_owner,
+    )
+}
+
+/// The current authority an `AuthorizeCheckedWithSeed` signed by `base` acts
+/// for.
+pub fn authorize_checked_with_seed_authority(
+    args: &VoteAuthorizeCheckedWithSeedArgs,
+    base: &Pubkey,
+) -> Result<Pubkey, PubkeyError> {
+    derive_seeded_authority(
+        base,
+        &args.current_authority_derived_key_seed,
+        &args.current_authority_derived_key_owner,
+    )
+}
+
+/// Finds the withdraw authority `program_id` derives for `vote_pubkey`, and
+/// its bump seed.
+pub fn find_withdraw_authority_address(program_id: &Pubkey, vote_pubkey: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WITHDRAW_AUTHORITY_SEED, vote_pubkey.as_ref()], program_id)
+}
+
+/// Recreates the withdraw authority for `vote_pubkey` from a known `bump`.
+pub fn create_withdraw_authority_address(
+    program_id: &Pubkey,
+    vote_pubkey: &Pubkey,
+    bump: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[WITHDRAW_AUTHORITY_SEED, vote_pubkey.as_ref(), &[bump]],
+        program_id,
+    )
+}
+
+/// Signer seeds for `invoke_signed` as the withdraw authority of
+/// `vote_pubkey`.
+pub fn withdraw_authority_signer_seeds<'a>(
+    vote_pubkey: &'a Pubkey,
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 3] {
+    [WITHDRAW_AUTHORITY_SEED, vote_pubkey.as_ref(), bump]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::fixture_pubkey,
+        solana_program::{pubkey::MAX_SEED_LEN, vote::state::VoteAuthorize},
+    };
+
+    #[test]
+    fn test_seeded_authority() {
+        let base = fixture_pubkey(1);
+        let owner = fixture_pubkey(2);
+        let authority = derive_seeded_authority(&base, "vote", &owner).unwrap();
+        assert_eq!(
+            authority,
+            Pubkey::create_with_seed(&base, "vote", &owner).unwrap()
+        );
+        assert!(is_seeded_authority(&authority, &base, "vote", &owner));
+        assert!(!is_seeded_authority(&authority, &base, "stake", &owner));
+        assert!(!is_seeded_authority(&authority, &owner, "vote", &base));
+    }
+
+    #[test]
+    fn test_seed_too_long() {
+        let base = fixture_pubkey(1);
+        let owner = fixture_pubkey(2);
+        let seed = "s".repeat(MAX_SEED_LEN + 1);
+        assert_eq!(
+            derive_seeded_authority(&base, &seed, &owner),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+        // An underivable key never matches, rather than erroring.
+        assert!(!is_seeded_authority(&base, &base, &seed, &owner));
+    }
+
+    #[test]
+    fn test_authorize_with_seed_authority() {
+        let base = fixture_pubkey(1);
+        let owner = fixture_pubkey(2);
+        let expected = derive_seeded_authority(&base, "vote", &owner).unwrap();
+        let args = VoteAuthorizeWithSeedArgs {
+            authorization_type: VoteAuthorize::Withdrawer,
+            current_authority_derived_key_owner: owner,
+            current_authority_derived_key_seed: "vote".to_string(),
+            new_authority: fixture_pubkey(3),
+        };
+        assert_eq!(authorize_with_seed_authority(&args, &base), Ok(expected));
+        let checked_args = VoteAuthorizeCheckedWithSeedArgs {
+            authorization_type: VoteAuthorize::Voter,
+            current_authority_derived_key_owner: owner,
+            current_authority_derived_key_seed: "vote".to_string(),
+        };
+        assert_eq!(
+            authorize_checked_with_seed_authority(&checked_args, &base),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn test_withdraw_authority_address() {
+        let program_id = fixture_pubkey(1);
+        let vote_pubkey = fixture_pubkey(2);
+        let (address, bump) = find_withdraw_authority_address(&program_id, &vote_pubkey);
+        assert!(!address.is_on_curve());
+        assert_eq!(
+            create_withdraw_authority_address(&program_id, &vote_pubkey, bump),
+            Ok(address)
+        );
+        let bump = [bump];
+        let signer_seeds = withdraw_authority_signer_seeds(&vote_pubkey, &bump);
+        assert_eq!(
+            Pubkey::create_program_address(&signer_seeds, &program_id),
+            Ok(address)
+        );
+        // Another vote account gets another authority.
+        assert_ne!(
+            find_withdraw_authority_address(&program_id, &fixture_pubkey(3)).0,
+            address
+        );
+    }
+}
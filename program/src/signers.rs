@@ -0,0 +1,104 @@
+//! Pubkey comparisons for authority checks.
+
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+#[cfg(target_os = "solana")]
+use solana_program::{program_memory::sol_memcmp, pubkey::PUBKEY_BYTES};
+
+/// Returns whether `a` and `b` are the same key.
+///
+/// On-chain this uses the `sol_memcmp` syscall, which costs less than
+/// comparing the two keys byte by byte in BPF.
+#[inline]
+pub fn pubkeys_eq(a: &Pubkey, b: &Pubkey) -> bool {
+    #[cfg(target_os = "solana")]
+    {
+        sol_memcmp(a.as_ref(), b.as_ref(), PUBKEY_BYTES) == 0
+    }
+    #[cfg(not(target_os = "solana"))]
+    {
+        a == b
+    }
+}
+
+/// Returns whether `authority` is among `signers`.
+pub fn is_signer<'a>(authority: &Pubkey, signers: impl IntoIterator<Item = &'a Pubkey>) -> bool {
+    signers
+        .into_iter()
+        .any(|signer| pubkeys_eq(signer, authority))
+}
+
+/// Checks that `authorized` signed, as the program requires of every
+/// authority.
+pub fn verify_authorized_signer(
+    authorized: &Pubkey,
+    signers: &[Pubkey],
+) -> Result<(), InstructionError> {
+    verify_signed_by_all(&[*authorized], signers)
+}
+
+/// Checks that at least one of `authorities` signed, as in "withdrawer or
+/// current authorized voter".
+pub fn verify_signed_by_any(
+    authorities: &[Pubkey],
+    signers: &[Pubkey],
+) -> Result<(), InstructionError> {
+    if authorities
+        .iter()
+        .any(|authority| is_signer(authority, signers))
+    {
+        Ok(())
+    } else {
+        Err(InstructionError::MissingRequiredSignature)
+    }
+}
+
+/// Checks that every one of `authorities` signed, as in "withdrawer and new
+/// validator identity". An empty `authorities` is never satisfied.
+pub fn verify_signed_by_all(
+    authorities: &[Pubkey],
+    signers: &[Pubkey],
+) -> Result<(), InstructionError> {
+    if !authorities.is_empty()
+        && authorities
+            .iter()
+            .all(|authority| is_signer(authority, signers))
+    {
+        Ok(())
+    } else {
+        Err(InstructionError::MissingRequiredSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signed_by_all() {
+        let withdrawer = Pubkey::new_unique();
+        let identity = Pubkey::new_unique();
+        assert_eq!(
+            verify_signed_by_all(&[withdrawer, identity], &[identity, withdrawer]),
+            Ok(())
+        );
+        assert_eq!(
+            verify_signed_by_all(&[withdrawer, identity], &[withdrawer]),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            verify_signed_by_all(&[], &[withdrawer]),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_verify_signed_by_any() {
+        let withdrawer = Pubkey::new_unique();
+        let voter = Pubkey::new_unique();
+        assert_eq!(verify_signed_by_any(&[withdrawer, voter], &[voter]), Ok(()));
+        assert_eq!(
+            verify_signed_by_any(&[], &[voter]),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+    }
+}
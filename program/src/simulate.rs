@@ -0,0 +1,384 @@
+//! Vote pre-simulation.
+
+use {
+    crate::delta::process_new_vote_state,
+    solana_program::{
+        clock::{Clock, Slot},
+        hash::Hash,
+        instruction::InstructionError,
+        vote::{
+            error::VoteError,
+            state::{LandedVote, Lockout, Vote, VoteState, VoteStateUpdate},
+        },
+    },
+    std::{cmp::Ordering, collections::VecDeque},
+};
+
+/// Runs `vote_state_update` against a copy of `vote_state` the way the
+/// program would when the vote lands at `clock.slot`, returning the resulting
+/// state.
+///
+/// `slot_hashes` must be ordered newest first, as in the `SlotHashes` sysvar.
+/// Signer verification is not simulated.
+pub fn simulate_vote(
+    vote_state: &VoteState,
+    vote_state_update: &VoteStateUpdate,
+    slot_hashes: &[(Slot, Hash)],
+    clock: &Clock,
+) -> Result<VoteState, InstructionError> {
+    let mut vote_state = vote_state.clone();
+    vote_state.get_and_update_authorized_voter(clock.epoch)?;
+
+    let mut lockouts = vote_state_update.lockouts.clone();
+    let mut root = vote_state_update.root;
+    check_and_filter_proposed_vote_state(
+        &vote_state,
+        &mut lockouts,
+        &mut root,
+        vote_state_update.hash,
+        slot_hashes,
+    )?;
+    process_new_vote_state(
+        &mut vote_state,
+        lockouts.into_iter().map(LandedVote::from).collect(),
+        root,
+        vote_state_update.timestamp,
+        clock.epoch,
+        clock.slot,
+    )?;
+
+    Ok(vote_state)
+}
+
+/// Drops the slots of a legacy `vote` older than the oldest entry in
+/// `slot_hashes`, as the program does before applying it.
+pub(crate) fn filter_vote_slots(
+    vote: &Vote,
+    slot_hashes: &[(Slot, Hash)],
+) -> Result<Vote, VoteError> {
+    let earliest_slot_hash_in_history = slot_hashes
+        .last()
+        .map(|(slot, _)| *slot)
+        .unwrap_or_default();
+    let mut vote = vote.clone();
+    vote.slots
+        .retain(|slot| *slot >= earliest_slot_hash_in_history);
+    if vote.slots.is_empty() {
+        return Err(VoteError::VotesTooOldAllFiltered);
+    }
+    Ok(vote)
+}
+
+/// Checks a proposed tower against `SlotHashes`, dropping proposed slots too
+/// old to verify that the vote state has never seen, and pulling an
+/// unverifiable root back to the newest known vote at or below it.
+pub(crate) fn check_and_filter_proposed_vote_state(
+    vote_state: &VoteState,
+    proposed_lockouts: &mut VecDeque<Lockout>,
+    proposed_root: &mut Option<Slot>,
+    proposed_hash: Hash,
+    slot_hashes: &[(Slot, Hash)],
+) -> Result<(), VoteError> {
+    let last_proposed_slot = proposed_lockouts
+        .back()
+        .map(Lockout::slot)
+        .ok_or(VoteError::EmptySlots)?;
+
+    if let Some(last_vote_slot) = vote_state.last_voted_slot() {
+        if last_proposed_slot <= last_vote_slot {
+            return Err(VoteError::VoteTooOld);
+        }
+    }
+
+    let earliest_slot_hash_in_history = slot_hashes
+        .last()
+        .map(|(slot, _)| *slot)
+        .ok_or(VoteError::SlotsMismatch)?;
+
+    // The newest proposed slot must be verifiable against its hash.
+    if last_proposed_slot < earliest_slot_hash_in_history {
+        return Err(VoteError::VoteTooOld);
+    }
+
+    if let Some(root) = *proposed_root {
+        if root < earliest_slot_hash_in_history {
+            *proposed_root = vote_state
+                .votes
+                .iter()
+                .rev()
+                .map(LandedVote::slot)
+                .find(|slot| *slot <= root)
+                .or(vote_state.root_slot);
+        }
+    }
+
+    // Walk the proposed slots oldest first, and `slot_hashes` from its oldest
+    // entry, checking the root first when there is one.
+    let mut root_to_check = *proposed_root;
+    let mut proposed_lockouts_index = 0;
+    let mut slot_hashes_index = slot_hashes.len();
+    let mut proposed_lockouts_indices_to_filter = vec![];
+
+    while proposed_lockouts_index < proposed_lockouts.len() && slot_hashes_index > 0 {
+        let proposed_vote_slot =
+            root_to_check.unwrap_or_else(|| proposed_lockouts[proposed_lockouts_index].slot());
+        if root_to_check.is_none()
+            && proposed_lockouts_index > 0
+            && proposed_vote_slot <= proposed_lockouts[proposed_lockouts_index - 1].slot()
+        {
+            return Err(VoteError::SlotsNotOrdered);
+        }
+        let ancestor_slot = slot_hashes[slot_hashes_index - 1].0;
+
+        match proposed_vote_slot.cmp(&ancestor_slot) {
+            Ordering::Less => {
+                if slot_hashes_index != slot_hashes.len() {
+                    // Recent enough to be in the history but missing from it,
+                    // so it belongs to another fork.
+                    return Err(if root_to_check.is_some() {
+                        VoteError::RootOnDifferentFork
+                    } else {
+                        VoteError::SlotsMismatch
+                    });
+                }
+                // Older than the history: keep it only if already voted on.
+                if root_to_check.take().is_none() {
+                    if !vote_state.contains_slot(proposed_vote_slot) {
+                        proposed_lockouts_indices_to_filter.push(proposed_lockouts_index);
+                    }
+                    proposed_lockouts_index += 1;
+                }
+            }
+            Ordering::Greater => slot_hashes_index -= 1,
+            Ordering::Equal => {
+                if root_to_check.take().is_none() {
+                    proposed_lockouts_index += 1;
+                    slot_hashes_index -= 1;
+                }
+            }
+        }
+    }
+
+    if proposed_lockouts_index != proposed_lockouts.len() {
+        return Err(VoteError::SlotsMismatch);
+    }
+
+    // The newest proposed slot was matched against `slot_hashes_index`.
+    if slot_hashes[slot_hashes_index].1 != proposed_hash {
+        return Err(VoteError::SlotHashMismatch);
+    }
+
+    let mut index = 0;
+    proposed_lockouts.retain(|_| {
+        let retain = proposed_lockouts_indices_to_filter
+            .binary_search(&index)
+            .is_err();
+        index += 1;
+        retain
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            delta::{DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING, TIMELY_VOTE_CREDITS},
+            test_support::{fixture_bank_hash, fixture_slot_hashes, VoteStateBuilder},
+        },
+    };
+
+    /// Lands every vote this many slots after the last proposed slot.
+    const LATENCY: u64 = 1;
+
+    /// A tower that voted on every even slot from 2 through 80, rooted at 18.
+    fn even_slot_tower() -> VoteState {
+        (1..=40)
+            .fold(VoteStateBuilder::new(), |builder, n| {
+                builder.vote(2 * n, LATENCY as u8)
+            })
+            .build()
+    }
+
+    fn clock(slot: Slot) -> Clock {
+        Clock {
+            slot,
+            ..Clock::default()
+        }
+    }
+
+    /// `vote_state` after the SDK votes on `slots` one at a time, and the
+    /// update proposing the resulting tower.
+    fn sdk_vote(vote_state: &VoteState, slots: &[Slot]) -> (VoteState, VoteStateUpdate) {
+        let mut expected = vote_state.clone();
+        let last_slot = *slots.last().unwrap();
+        for &slot in slots {
+            expected.process_next_vote_slot(
+                slot,
+                0,
+                last_slot + LATENCY,
+                TIMELY_VOTE_CREDITS,
+                DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+            );
+        }
+        let vote_state_update = VoteStateUpdate::new(
+            expected.votes.iter().map(|vote| vote.lockout).collect(),
+            expected.root_slot,
+            fixture_bank_hash(last_slot),
+        );
+        (expected, vote_state_update)
+    }
+
+    fn assert_matches_sdk(
+        vote_state: &VoteState,
+        vote_state_update: &VoteStateUpdate,
+        slot_hashes: &[(Slot, Hash)],
+        expected: &VoteState,
+    ) {
+        let last_slot = vote_state_update.last_voted_slot().unwrap();
+        let simulated = simulate_vote(
+            vote_state,
+            vote_state_update,
+            slot_hashes,
+            &clock(last_slot + LATENCY),
+        )
+        .unwrap();
+        assert_eq!(simulated.votes, expected.votes);
+        assert_eq!(simulated.root_slot, expected.root_slot);
+        assert_eq!(simulated.epoch_credits, expected.epoch_credits);
+    }
+
+    #[test]
+    fn test_success_matches_sdk() {
+        let vote_state = VoteStateBuilder::new()
+            .vote(1, 1)
+            .vote(2, 1)
+            .vote(3, 1)
+            .build();
+        let (expected, vote_state_update) = sdk_vote(&vote_state, &[4, 5]);
+        assert_matches_sdk(
+            &vote_state,
+            &vote_state_update,
+            &fixture_slot_hashes(0..=5),
+            &expected,
+        );
+    }
+
+    #[test]
+    fn test_success_rooting_matches_sdk() {
+        let vote_state = even_slot_tower();
+        let (expected, vote_state_update) = sdk_vote(&vote_state, &[82, 84]);
+        assert_eq!(expected.root_slot, Some(22));
+        assert_matches_sdk(
+            &vote_state,
+            &vote_state_update,
+            &fixture_slot_hashes(0..=84),
+            &expected,
+        );
+    }
+
+    #[test]
+    fn test_root_kept_within_slot_hashes() {
+        let vote_state = even_slot_tower();
+        let (_, vote_state_update) = sdk_vote(&vote_state, &[82]);
+        let mut lockouts = vote_state_update.lockouts.clone();
+        let mut root = Some(19);
+        check_and_filter_proposed_vote_state(
+            &vote_state,
+            &mut lockouts,
+            &mut root,
+            vote_state_update.hash,
+            &fixture_slot_hashes(18..=82),
+        )
+        .unwrap();
+        assert_eq!(root, Some(19));
+        assert_eq!(lockouts, vote_state_update.lockouts);
+    }
+
+    #[test]
+    fn test_root_reset_before_slot_hashes() {
+        let vote_state = even_slot_tower();
+        let (expected, mut vote_state_update) = sdk_vote(&vote_state, &[82]);
+        assert_eq!(expected.root_slot, Some(20));
+        // Slot 21 was never voted on, so the root falls back to 20. The
+        // proposed slots 22 through 28 predate the history but stay, as the
+        // tower already holds them.
+        vote_state_update.root = Some(21);
+        let slot_hashes = fixture_slot_hashes(30..=82);
+        assert_matches_sdk(&vote_state, &vote_state_update, &slot_hashes, &expected);
+
+        // With no vote at or below it in the tower, the stored root is kept.
+        vote_state_update.root = Some(19);
+        let mut lockouts = vote_state_update.lockouts.clone();
+        let mut root = vote_state_update.root;
+        check_and_filter_proposed_vote_state(
+            &vote_state,
+            &mut lockouts,
+            &mut root,
+            vote_state_update.hash,
+            &slot_hashes,
+        )
+        .unwrap();
+        assert_eq!(root, Some(18));
+    }
+
+    #[test]
+    fn test_filters_unseen_slots_before_slot_hashes() {
+        let vote_state = even_slot_tower();
+        let (_, vote_state_update) = sdk_vote(&vote_state, &[82]);
+        let mut lockouts = vote_state_update.lockouts.clone();
+        lockouts.insert(2, Lockout::new(25));
+        let mut root = vote_state_update.root;
+        check_and_filter_proposed_vote_state(
+            &vote_state,
+            &mut lockouts,
+            &mut root,
+            vote_state_update.hash,
+            &fixture_slot_hashes(30..=82),
+        )
+        .unwrap();
+        assert_eq!(lockouts, vote_state_update.lockouts);
+        assert_eq!(root, Some(20));
+    }
+
+    #[test]
+    fn test_rejections() {
+        let vote_state = even_slot_tower();
+        let (_, next) = sdk_vote(&vote_state, &[82]);
+        let (_, stale) = sdk_vote(&VoteStateBuilder::new().vote(2, 1).build(), &[4]);
+        let mut wrong_hash = next.clone();
+        wrong_hash.hash = fixture_bank_hash(0);
+        let mut other_fork_root = next.clone();
+        other_fork_root.root = Some(21);
+        let mut empty = next.clone();
+        empty.lockouts.clear();
+
+        let without =
+            |skipped: Slot| fixture_slot_hashes((0..=82).filter(move |slot| *slot != skipped));
+        for (vote_state_update, slot_hashes, expected) in [
+            (&stale, fixture_slot_hashes(0..=82), VoteError::VoteTooOld),
+            (&next, fixture_slot_hashes(83..=90), VoteError::VoteTooOld),
+            (&empty, fixture_slot_hashes(0..=82), VoteError::EmptySlots),
+            (&next, vec![], VoteError::SlotsMismatch),
+            (&next, without(50), VoteError::SlotsMismatch),
+            (
+                &wrong_hash,
+                fixture_slot_hashes(0..=82),
+                VoteError::SlotHashMismatch,
+            ),
+            (
+                &other_fork_root,
+                without(21),
+                VoteError::RootOnDifferentFork,
+            ),
+        ] {
+            assert_eq!(
+                simulate_vote(&vote_state, vote_state_update, &slot_hashes, &clock(83)),
+                Err(expected.clone().into()),
+                "{expected:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Bulk vote account snapshot files.
+//!
+//! A snapshot is a header followed by entries until end of file:
+//!
+//! - header: the magic `VSNP`, then the format version as a `u32`
+//! - entry: vote pubkey, the slot the state was observed at as a `u64`,
+//!   the byte length of the state as a `u32`, then the state as
+//!   `VoteStateVersions::Current` without the account's zero padding
+//!
+//! Integers are little endian. Entries are read back through
+//! [`sanitize_vote_account_data`], so damaged files cannot force large
+//! allocations.
+
+use {
+    crate::{
+        sanitize::{sanitize_vote_account_data, SanitizeError},
+        wire,
+    },
+    solana_program::{
+        clock::Slot,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+        vote::state::{VoteState, VoteStateVersions},
+    },
+    std::io::{self, Read, Write},
+    thiserror::Error,
+};
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"VSNP";
+/// Format version written by [`SnapshotWriter`].
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// One vote account in a snapshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub vote_pubkey: Pubkey,
+    /// Slot the vote state was observed at.
+    pub slot: Slot,
+    pub vote_state: VoteState,
+}
+
+/// Reasons a snapshot cannot be written or read.
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a vote account snapshot")]
+    BadMagic,
+    #[error("unsupported snapshot version {0}")]
+    UnsupportedVersion(u32),
+    #[error("snapshot ends inside an entry")]
+    Truncated,
+    #[error("entry of {0} bytes exceeds the vote state size")]
+    EntryTooLarge(u32),
+    #[error("invalid vote state for {vote_pubkey}: {err}")]
+    InvalidVoteState {
+        vote_pubkey: Pubkey,
+        err: SanitizeError,
+    },
+    #[error("vote state could not be serialized")]
+    Serialize,
+}
+
+/// Writes snapshot entries to `W`.
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Writes the header.
+    pub fn new(mut writer: W) -> Result<Self, SnapshotError> {
+        writer.write_all(&SNAPSHOT_MAGIC)?;
+        writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, entry: &SnapshotEntry) -> Result<(), SnapshotError> {
+        let versioned = VoteStateVersions::new_current(entry.vote_state.clone());
+        let data = wire::serialize(&versioned, VoteState::size_of() as u64)
+            .map_err(|_| SnapshotError::Serialize)?;
+        let len = u32::try_from(data.len()).map_err(|_| SnapshotError::Serialize)?;
+        self.writer.write_all(entry.vote_pubkey.as_ref())?;
+        self.writer.write_all(&entry.slot.to_le_bytes())?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&data)?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, SnapshotError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads snapshot entries from `R`, in file order.
+pub struct SnapshotReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Reads and checks the header.
+    pub fn new(mut reader: R) -> Result<Self, SnapshotError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        Ok(Self { reader })
+    }
+
+    /// Reads the next entry, or `None` at the end of the snapshot.
+    pub fn read(&mut self) -> Result<Option<SnapshotEntry>, SnapshotError> {
+        let mut vote_pubkey = [0; PUBKEY_BYTES];
+        if !self.read_first(&mut vote_pubkey)? {
+            return Ok(None);
+        }
+        let vote_pubkey = Pubkey::new_from_array(vote_pubkey);
+        let mut slot = [0; 8];
+        self.read_rest(&mut slot)?;
+        let mut len = [0; 4];
+        self.read_rest(&mut len)?;
+        let len = u32::from_le_bytes(len);
+        if len as usize > VoteState::size_of() {
+            return Err(SnapshotError::EntryTooLarge(len));
+        }
+        let mut data = vec![0; len as usize];
+        self.read_rest(&mut data)?;
+        let vote_state = sanitize_vote_account_data(&data)
+            .map_err(|err| SnapshotError::InvalidVoteState { vote_pubkey, err })?
+            .into_inner();
+        Ok(Some(SnapshotEntry {
+            vote_pubkey,
+            slot: Slot::from_le_bytes(slot),
+            vote_state,
+        }))
+    }
+
+    /// Fills `buf`, returning `false` if the input ended before any byte.
+    fn read_first(&mut self, buf: &mut [u8]) -> Result<bool, SnapshotError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => return Err(SnapshotError::Truncated),
+                Ok(read) => filled += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_rest(&mut self, buf: &mut [u8]) -> Result<(), SnapshotError> {
+        self.reader.read_exact(buf).map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => SnapshotError::Truncated,
+            _ => err.into(),
+        })
+    }
+}
+
+impl<R: Read> Iterator for SnapshotReader<R> {
+    type Item = Result<SnapshotEntry, SnapshotError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_pubkey, initialized_vote_state, populated_vote_state},
+        solana_program::vote::state::MAX_LOCKOUT_HISTORY,
+    };
+
+    fn entries() -> Vec<SnapshotEntry> {
+        [
+            initialized_vote_state(),
+            populated_vote_state(4, 2, 1),
+            populated_vote_state(MAX_LOCKOUT_HISTORY, 64, 40),
+        ]
+        .into_iter()
+        .zip(1..)
+        .map(|(vote_state, n)| SnapshotEntry {
+            vote_pubkey: fixture_pubkey(n),
+            slot: u64::from(n) * 100,
+            vote_state,
+        })
+        .collect()
+    }
+
+    fn write(entries: &[SnapshotEntry]) -> Vec<u8> {
+        let mut writer = SnapshotWriter::new(vec![]).unwrap();
+        for entry in entries {
+            writer.write(entry).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn read(data: &[u8]) -> Result<Vec<SnapshotEntry>, SnapshotError> {
+        SnapshotReader::new(data)?.collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let entries = entries();
+        assert_eq!(read(&write(&entries)).unwrap(), entries);
+        assert_eq!(read(&write(&[])).unwrap(), []);
+    }
+
+    #[test]
+    fn test_bad_magic() {
+        let mut data = write(&entries());
+        data[0] = b'X';
+        assert!(matches!(read(&data), Err(SnapshotError::BadMagic)));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut data = write(&entries());
+        data[4..8].copy_from_slice(&2u32.to_le_bytes());
+        assert!(matches!(
+            read(&data),
+            Err(SnapshotError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let entries = entries();
+        let one_entry = write(&entries[..1]).len();
+        let data = write(&entries);
+        // Cutting at an entry boundary is a clean end; anywhere inside the
+        // entry after it is truncation.
+        assert_eq!(read(&data[..one_entry]).unwrap(), entries[..1]);
+        for len in [
+            one_entry + 1,
+            one_entry + PUBKEY_BYTES,
+            one_entry + 45,
+            data.len() - 1,
+        ] {
+            assert!(
+                matches!(read(&data[..len]), Err(SnapshotError::Truncated)),
+                "{len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_entry_too_large() {
+        let mut data = write(&entries()[..1]);
+        let len_offset = 8 + PUBKEY_BYTES + 8;
+        let len = VoteState::size_of() as u32 + 1;
+        data[len_offset..len_offset + 4].copy_from_slice(&len.to_le_bytes());
+        assert!(matches!(
+            read(&data),
+            Err(SnapshotError::EntryTooLarge(too_large)) if too_large == len
+        ));
+    }
+}
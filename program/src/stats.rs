@@ -0,0 +1,120 @@
+//! Counts and serialized sizes of vote instructions, per variant.
+
+use {
+    crate::{instruction::InstructionMeta, wire},
+    solana_program::vote::instruction::VoteInstruction,
+    std::collections::BTreeMap,
+};
+
+/// Width of one [`VariantStats::size_histogram`] bucket, in bytes.
+pub const SIZE_BUCKET_BYTES: usize = 32;
+
+/// Statistics for one instruction variant.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VariantStats {
+    pub count: u64,
+    /// Sum of serialized sizes, in bytes.
+    pub total_bytes: u64,
+    pub min_bytes: usize,
+    pub max_bytes: usize,
+    /// Instruction count by size, keyed by the bucket's lowest size.
+    pub size_histogram: BTreeMap<usize, u64>,
+}
+
+impl VariantStats {
+    fn record(&mut self, size: usize) {
+        self.min_bytes = if self.count == 0 {
+            size
+        } else {
+            self.min_bytes.min(size)
+        };
+        self.max_bytes = self.max_bytes.max(size);
+        self.count = self.count.saturating_add(1);
+        self.total_bytes = self.total_bytes.saturating_add(size as u64);
+        let bucket = size / SIZE_BUCKET_BYTES * SIZE_BUCKET_BYTES;
+        *self.size_histogram.entry(bucket).or_default() += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        self.min_bytes = if self.count == 0 {
+            other.min_bytes
+        } else {
+            self.min_bytes.min(other.min_bytes)
+        };
+        self.max_bytes = self.max_bytes.max(other.max_bytes);
+        self.count = self.count.saturating_add(other.count);
+        self.total_bytes = self.total_bytes.saturating_add(other.total_bytes);
+        for (bucket, count) in &other.size_histogram {
+            *self.size_histogram.entry(*bucket).or_default() += count;
+        }
+    }
+}
+
+/// Accumulates [`VariantStats`] keyed by instruction name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VoteInstructionStats {
+    variants: BTreeMap<&'static str, VariantStats>,
+}
+
+impl VoteInstructionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `instruction` at its bincode-serialized size.
+    pub fn record(&mut self, instruction: &VoteInstruction) {
+        let size = wire::serialized_size(instruction).unwrap_or_default() as usize;
+        self.record_with_size(instruction, size);
+    }
+
+    /// Records `instruction` at `size` bytes, for sizes measured under
+    /// another encoding.
+    pub fn record_with_size(&mut self, instruction: &VoteInstruction, size: usize) {
+        self.variants
+            .entry(InstructionMeta::of(instruction).name)
+            .or_default()
+            .record(size);
+    }
+
+    /// Records raw instruction data, keyed by its tag. Returns `false`
+    /// without recording if the tag is unknown.
+    pub fn record_data(&mut self, instruction_data: &[u8]) -> bool {
+        let Some(meta) = InstructionMeta::from_data(instruction_data) else {
+            return false;
+        };
+        self.variants
+            .entry(meta.name)
+            .or_default()
+            .record(instruction_data.len());
+        true
+    }
+
+    /// Adds the statistics gathered by `other`.
+    pub fn merge(&mut self, other: &Self) {
+        for (name, stats) in &other.variants {
+            self.variants.entry(name).or_default().merge(stats);
+        }
+    }
+
+    /// Statistics for the variant called `name`, as in
+    /// [`InstructionMeta::name`].
+    pub fn variant(&self, name: &str) -> Option<&VariantStats> {
+        self.variants.get(name)
+    }
+
+    /// Statistics for every recorded variant, by name.
+    pub fn variants(&self) -> impl Iterator<Item = (&'static str, &VariantStats)> {
+        self.variants.iter().map(|(name, stats)| (*name, stats))
+    }
+
+    pub fn total_count(&self) -> u64 {
+        self.variants.values().map(|stats| stats.count).sum()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.variants.values().map(|stats| stats.total_bytes).sum()
+    }
+}
@@ -0,0 +1,406 @@
+//! Storing a vote state into its account.
+//!
+//! Storage is split into deciding the layout, growing the account if needed
+//! and serializing, with the account behind [`VoteAccountStorage`] so each
+//! step can run against an in-memory account.
+
+use {
+    crate::layout::{serialize_as, VoteStateTargetVersion},
+    solana_program::{
+        account_info::AccountInfo,
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        vote::{instruction as vote_instruction, state::VoteState},
+    },
+};
+
+/// Account operations needed to store a vote state.
+pub trait VoteAccountStorage {
+    fn data_len(&self) -> usize;
+
+    fn lamports(&self) -> u64;
+
+    /// Resizes the account data to `new_len` bytes.
+    fn realloc(&mut self, new_len: usize) -> Result<(), InstructionError>;
+
+    /// Runs `f` over the mutable account data.
+    fn with_data_mut<R>(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> Result<R, InstructionError>,
+    ) -> Result<R, InstructionError>;
+}
+
+impl VoteAccountStorage for AccountInfo<'_> {
+    fn data_len(&self) -> usize {
+        AccountInfo::data_len(self)
+    }
+
+    fn lamports(&self) -> u64 {
+        AccountInfo::lamports(self)
+    }
+
+    fn realloc(&mut self, new_len: usize) -> Result<(), InstructionError> {
+        AccountInfo::realloc(self, new_len, true).map_err(|err| u64::from(err).into())
+    }
+
+    fn with_data_mut<R>(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> Result<R, InstructionError>,
+    ) -> Result<R, InstructionError> {
+        let mut data = self
+            .try_borrow_mut_data()
+            .map_err(|_| InstructionError::AccountBorrowFailed)?;
+        f(&mut data)
+    }
+}
+
+/// How `set_vote_account_state` stores a vote state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreAction {
+    /// The account already fits the current layout.
+    Current,
+    /// The account is grown to the current layout size first.
+    ReallocThenCurrent,
+    /// The account is too small and cannot stay rent exempt if grown, so the
+    /// 1.14.11 layout is written instead.
+    FallbackV1_14_11,
+}
+
+impl StoreAction {
+    /// Layout this action writes.
+    pub fn target_version(self) -> VoteStateTargetVersion {
+        match self {
+            Self::Current | Self::ReallocThenCurrent => VoteStateTargetVersion::Current,
+            Self::FallbackV1_14_11 => VoteStateTargetVersion::V1_14_11,
+        }
+    }
+}
+
+/// Decides how a vote account of `current_len` bytes holding `lamports`
+/// stores its vote state.
+///
+/// The program also falls back to the 1.14.11 layout if the realloc itself
+/// fails, which cannot be predicted here.
+pub fn required_action(current_len: usize, lamports: u64, rent: &Rent) -> StoreAction {
+    let size = VoteState::size_of();
+    if current_len >= size {
+        StoreAction::Current
+    } else if rent.is_exempt(lamports, size) {
+        StoreAction::ReallocThenCurrent
+    } else {
+        StoreAction::FallbackV1_14_11
+    }
+}
+
+/// Performs the realloc `action` calls for and returns the layout to write.
+///
+/// A failed realloc falls back to the 1.14.11 layout rather than failing.
+pub fn maybe_realloc<S: VoteAccountStorage>(
+    account: &mut S,
+    action: StoreAction,
+) -> VoteStateTargetVersion {
+    if action == StoreAction::ReallocThenCurrent && account.realloc(VoteState::size_of()).is_err() {
+        return VoteStateTargetVersion::V1_14_11;
+    }
+    action.target_version()
+}
+
+/// Stores `vote_state` into `account`, growing it to the current layout when
+/// it stays rent exempt under `rent`. Returns the layout written.
+pub fn set_vote_account_state<S: VoteAccountStorage>(
+    account: &mut S,
+    vote_state: &mut VoteState,
+    rent: &Rent,
+) -> Result<VoteStateTargetVersion, InstructionError> {
+    let action = required_action(account.data_len(), account.lamports(), rent);
+    let version = maybe_realloc(account, action);
+    account.with_data_mut(|data| serialize_as(version, vote_state, data))?;
+    Ok(version)
+}
+
+/// Instructions that let a 1.14.11-sized account grow to the current
+/// layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LegacyTopUp {
+    /// Lamports transferred in; zero if the account already holds enough.
+    pub lamports: u64,
+    /// The transfer, if any, then an `UpdateCommission` to the unchanged
+    /// commission, signed by the withdrawer, so the program rewrites the
+    /// account.
+    pub instructions: Vec<Instruction>,
+}
+
+/// Builds a [`LegacyTopUp`] for an account of `current_len` bytes holding
+/// `lamports`, funded by `funder`. Returns `None` unless the account has
+/// exactly the 1.14.11 layout size.
+pub fn legacy_top_up(
+    vote_pubkey: &Pubkey,
+    vote_state: &VoteState,
+    current_len: usize,
+    lamports: u64,
+    rent: &Rent,
+    funder: &Pubkey,
+) -> Option<LegacyTopUp> {
+    if current_len != VoteStateTargetVersion::V1_14_11.size_of() {
+        return None;
+    }
+    let top_up = rent
+        .minimum_balance(VoteState::size_of())
+        .saturating_sub(lamports);
+    let mut instructions = vec![];
+    if top_up > 0 {
+        instructions.push(system_instruction::transfer(funder, vote_pubkey, top_up));
+    }
+    instructions.push(vote_instruction::update_commission(
+        vote_pubkey,
+        &vote_state.authorized_withdrawer,
+        vote_state.commission,
+    ));
+    Some(LegacyTopUp {
+        lamports: top_up,
+        instructions,
+    })
+}
+
+/// What one vote account lacks to hold a layout of a given size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Headroom {
+    /// Bytes the account must grow by; zero if it is already large enough.
+    pub missing_bytes: usize,
+    /// Lamports needed to stay rent exempt at the larger size.
+    pub missing_lamports: u64,
+}
+
+impl Headroom {
+    pub fn needs_realloc(&self) -> bool {
+        self.missing_bytes > 0
+    }
+}
+
+/// Returns what an account of `current_len` bytes holding `lamports` lacks
+/// to hold `target_len` bytes.
+///
+/// `target_len` need not be a layout this crate knows, so tooling can plan
+/// for a layout before it ships; [`max_serialized_size`] and
+/// [`VoteStateTargetVersion::size_of`] give the known ones.
+///
+/// [`max_serialized_size`]: crate::layout::max_serialized_size
+pub fn headroom(current_len: usize, lamports: u64, target_len: usize, rent: &Rent) -> Headroom {
+    Headroom {
+        missing_bytes: target_len.saturating_sub(current_len),
+        missing_lamports: rent
+            .minimum_balance(target_len.max(current_len))
+            .saturating_sub(lamports),
+    }
+}
+
+/// [`Headroom`] summed over many vote accounts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FleetHeadroom {
+    pub accounts: usize,
+    /// Accounts that must grow to hold the target size.
+    pub needing_realloc: usize,
+    pub missing_bytes: u64,
+    pub missing_lamports: u64,
+}
+
+/// Sums [`headroom`] over `(data_len, lamports)` pairs, such as a
+/// `getProgramAccounts` response.
+pub fn fleet_headroom(
+    accounts: impl IntoIterator<Item = (usize, u64)>,
+    target_len: usize,
+    rent: &Rent,
+) -> FleetHeadroom {
+    accounts.into_iter().fold(
+        FleetHeadroom::default(),
+        |mut fleet, (current_len, lamports)| {
+            let headroom = headroom(current_len, lamports, target_len, rent);
+            fleet.accounts += 1;
+            fleet.needing_realloc += usize::from(headroom.needs_realloc());
+            fleet.missing_bytes = fleet
+                .missing_bytes
+                .saturating_add(headroom.missing_bytes as u64);
+            fleet.missing_lamports = fleet
+                .missing_lamports
+                .saturating_add(headroom.missing_lamports);
+            fleet
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{populated_vote_state, restore},
+        solana_program::vote::state::MAX_LOCKOUT_HISTORY,
+    };
+
+    struct MemoryAccount {
+        data: Vec<u8>,
+        lamports: u64,
+        realloc_fails: bool,
+        reallocs: usize,
+    }
+
+    impl MemoryAccount {
+        fn new(version: VoteStateTargetVersion, lamports: u64) -> Self {
+            Self {
+                data: vec![0; version.size_of()],
+                lamports,
+                realloc_fails: false,
+                reallocs: 0,
+            }
+        }
+    }
+
+    impl VoteAccountStorage for MemoryAccount {
+        fn data_len(&self) -> usize {
+            self.data.len()
+        }
+
+        fn lamports(&self) -> u64 {
+            self.lamports
+        }
+
+        fn realloc(&mut self, new_len: usize) -> Result<(), InstructionError> {
+            self.reallocs += 1;
+            if self.realloc_fails {
+                return Err(InstructionError::InvalidRealloc);
+            }
+            self.data.resize(new_len, 0);
+            Ok(())
+        }
+
+        fn with_data_mut<R>(
+            &mut self,
+            f: impl FnOnce(&mut [u8]) -> Result<R, InstructionError>,
+        ) -> Result<R, InstructionError> {
+            f(&mut self.data)
+        }
+    }
+
+    fn current_exempt(rent: &Rent) -> u64 {
+        rent.minimum_balance(VoteState::size_of())
+    }
+
+    #[test]
+    fn test_store_into_current_size() {
+        let rent = Rent::default();
+        let mut vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 4);
+        let mut account = MemoryAccount::new(VoteStateTargetVersion::Current, 0);
+        assert_eq!(
+            set_vote_account_state(&mut account, &mut vote_state, &rent),
+            Ok(VoteStateTargetVersion::Current)
+        );
+        assert_eq!(account.reallocs, 0);
+        assert_eq!(restore(&account.data).unwrap(), vote_state);
+    }
+
+    #[test]
+    fn test_store_grows_legacy_account() {
+        let rent = Rent::default();
+        let mut vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 4);
+        let mut account =
+            MemoryAccount::new(VoteStateTargetVersion::V1_14_11, current_exempt(&rent));
+        assert_eq!(
+            set_vote_account_state(&mut account, &mut vote_state, &rent),
+            Ok(VoteStateTargetVersion::Current)
+        );
+        assert_eq!(account.data.len(), VoteState::size_of());
+        assert_eq!(restore(&account.data).unwrap(), vote_state);
+    }
+
+    #[test]
+    fn test_store_falls_back_on_rent_shortfall() {
+        let rent = Rent::default();
+        let mut vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 4);
+        let mut account =
+            MemoryAccount::new(VoteStateTargetVersion::V1_14_11, current_exempt(&rent) - 1);
+        assert_eq!(
+            set_vote_account_state(&mut account, &mut vote_state, &rent),
+            Ok(VoteStateTargetVersion::V1_14_11)
+        );
+        assert_eq!(account.reallocs, 0);
+        assert_eq!(
+            account.data.len(),
+            VoteStateTargetVersion::V1_14_11.size_of()
+        );
+        assert_eq!(
+            restore(&account.data).unwrap().votes.len(),
+            vote_state.votes.len()
+        );
+    }
+
+    #[test]
+    fn test_store_falls_back_on_realloc_failure() {
+        let rent = Rent::default();
+        let mut vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 4);
+        let mut account =
+            MemoryAccount::new(VoteStateTargetVersion::V1_14_11, current_exempt(&rent));
+        account.realloc_fails = true;
+        assert_eq!(
+            set_vote_account_state(&mut account, &mut vote_state, &rent),
+            Ok(VoteStateTargetVersion::V1_14_11)
+        );
+        assert_eq!(account.reallocs, 1);
+        assert_eq!(
+            account.data.len(),
+            VoteStateTargetVersion::V1_14_11.size_of()
+        );
+        assert_eq!(
+            restore(&account.data).unwrap().votes.len(),
+            vote_state.votes.len()
+        );
+    }
+
+    #[test]
+    fn test_legacy_top_up() {
+        let rent = Rent::default();
+        let vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 4);
+        let vote_pubkey = Pubkey::new_unique();
+        let funder = Pubkey::new_unique();
+        let legacy_len = VoteStateTargetVersion::V1_14_11.size_of();
+        let lamports = rent.minimum_balance(legacy_len);
+
+        let top_up = legacy_top_up(
+            &vote_pubkey,
+            &vote_state,
+            legacy_len,
+            lamports,
+            &rent,
+            &funder,
+        )
+        .unwrap();
+        assert_eq!(top_up.lamports, current_exempt(&rent) - lamports);
+        assert_eq!(top_up.instructions.len(), 2);
+
+        let funded = legacy_top_up(
+            &vote_pubkey,
+            &vote_state,
+            legacy_len,
+            current_exempt(&rent),
+            &rent,
+            &funder,
+        )
+        .unwrap();
+        assert_eq!(funded.lamports, 0);
+        assert_eq!(funded.instructions.len(), 1);
+
+        for current_len in [0, legacy_len - 1, legacy_len + 1, VoteState::size_of()] {
+            assert_eq!(
+                legacy_top_up(
+                    &vote_pubkey,
+                    &vote_state,
+                    current_len,
+                    lamports,
+                    &rent,
+                    &funder
+                ),
+                None
+            );
+        }
+    }
+}
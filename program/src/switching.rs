@@ -0,0 +1,192 @@
+//! Switch threshold evaluation for fork switching.
+
+use {
+    solana_program::{clock::Slot, vote::state::VoteState},
+    std::collections::{HashMap, HashSet},
+};
+
+/// Fraction of total stake that must be locked out on other forks before a
+/// validator may switch away from its last voted fork.
+pub const SWITCH_FORK_THRESHOLD: f64 = 0.38;
+
+/// Outcome of [`check_switch_threshold`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwitchForkDecision {
+    /// The candidate descends from the last vote; a plain vote suffices.
+    SameFork,
+    /// Enough stake is on other forks; the vote must use a `*Switch` variant
+    /// carrying the proof.
+    SwitchProof { switch_stake: u64, total_stake: u64 },
+    /// Not enough stake is on other forks to switch.
+    FailedSwitchThreshold { switch_stake: u64, total_stake: u64 },
+    /// The candidate is an ancestor of the last vote. Voting on it would roll
+    /// the tower back, which no amount of stake allows.
+    Rollback,
+}
+
+impl SwitchForkDecision {
+    /// Returns whether the validator may vote on the candidate slot.
+    pub fn can_vote(&self) -> bool {
+        matches!(self, Self::SameFork | Self::SwitchProof { .. })
+    }
+
+    /// Returns whether the vote must use a `*Switch` instruction variant.
+    pub fn needs_switch_proof(&self) -> bool {
+        matches!(self, Self::SwitchProof { .. })
+    }
+}
+
+/// Evaluates whether `vote_state` may switch to `switch_slot`.
+///
+/// `ancestors` maps each slot in the fork graph to all of its ancestors, as
+/// bank forks do; a slot missing from it has none. `fork_stakes` maps voted
+/// slots to the stake locked out on them. Stake on the last vote's own fork,
+/// meaning its ancestors, itself and its descendants, does not count toward
+/// the switch.
+pub fn check_switch_threshold(
+    vote_state: &VoteState,
+    switch_slot: Slot,
+    ancestors: &HashMap<Slot, HashSet<Slot>>,
+    fork_stakes: &HashMap<Slot, u64>,
+    total_stake: u64,
+) -> SwitchForkDecision {
+    let Some(last_voted_slot) = vote_state.last_voted_slot() else {
+        return SwitchForkDecision::SameFork;
+    };
+    let is_ancestor = |ancestor: Slot, slot: Slot| {
+        ancestors
+            .get(&slot)
+            .is_some_and(|slot_ancestors| slot_ancestors.contains(&ancestor))
+    };
+    if switch_slot == last_voted_slot || is_ancestor(last_voted_slot, switch_slot) {
+        return SwitchForkDecision::SameFork;
+    }
+    if is_ancestor(switch_slot, last_voted_slot) {
+        return SwitchForkDecision::Rollback;
+    }
+    let switch_stake = fork_stakes
+        .iter()
+        .filter(|(slot, _)| {
+            **slot != last_voted_slot
+                && !is_ancestor(**slot, last_voted_slot)
+                && !is_ancestor(last_voted_slot, **slot)
+        })
+        .fold(0u64, |sum, (_, stake)| sum.saturating_add(*stake));
+    if total_stake > 0 && switch_stake as f64 / total_stake as f64 > SWITCH_FORK_THRESHOLD {
+        SwitchForkDecision::SwitchProof {
+            switch_stake,
+            total_stake,
+        }
+    } else {
+        SwitchForkDecision::FailedSwitchThreshold {
+            switch_stake,
+            total_stake,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test_support::VoteStateBuilder};
+
+    /// Slot 0 forks into 1-2-3 and 1-4-5, then 3 into 6:
+    ///
+    /// ```text
+    /// 0 - 1 - 2 - 3 - 6
+    ///      \
+    ///       4 - 5
+    /// ```
+    fn ancestors() -> HashMap<Slot, HashSet<Slot>> {
+        [
+            (1, vec![0]),
+            (2, vec![0, 1]),
+            (3, vec![0, 1, 2]),
+            (6, vec![0, 1, 2, 3]),
+            (4, vec![0, 1]),
+            (5, vec![0, 1, 4]),
+        ]
+        .into_iter()
+        .map(|(slot, ancestors)| (slot, ancestors.into_iter().collect()))
+        .collect()
+    }
+
+    /// Voted last on slot 2.
+    fn vote_state() -> VoteState {
+        VoteStateBuilder::new().vote(1, 1).vote(2, 1).build()
+    }
+
+    fn check(switch_slot: Slot, fork_stakes: &[(Slot, u64)]) -> SwitchForkDecision {
+        check_switch_threshold(
+            &vote_state(),
+            switch_slot,
+            &ancestors(),
+            &fork_stakes.iter().copied().collect(),
+            100,
+        )
+    }
+
+    #[test]
+    fn test_same_fork() {
+        assert_eq!(check(2, &[(5, 100)]), SwitchForkDecision::SameFork);
+        assert_eq!(check(6, &[(5, 100)]), SwitchForkDecision::SameFork);
+        assert!(check_switch_threshold(
+            &VoteState::default(),
+            5,
+            &ancestors(),
+            &HashMap::new(),
+            100
+        )
+        .can_vote());
+    }
+
+    #[test]
+    fn test_rollback() {
+        let decision = check(1, &[(5, 100)]);
+        assert_eq!(decision, SwitchForkDecision::Rollback);
+        assert!(!decision.can_vote());
+    }
+
+    #[test]
+    fn test_threshold_boundary() {
+        assert_eq!(
+            check(5, &[(5, 38)]),
+            SwitchForkDecision::FailedSwitchThreshold {
+                switch_stake: 38,
+                total_stake: 100,
+            }
+        );
+        let decision = check(5, &[(4, 20), (5, 19)]);
+        assert_eq!(
+            decision,
+            SwitchForkDecision::SwitchProof {
+                switch_stake: 39,
+                total_stake: 100,
+            }
+        );
+        assert!(decision.can_vote() && decision.needs_switch_proof());
+    }
+
+    #[test]
+    fn test_last_vote_fork_excluded() {
+        // Stake on the last vote, its ancestors and its descendants does not
+        // count; only slots 4 and 5 do.
+        assert_eq!(
+            check(5, &[(0, 50), (1, 50), (2, 50), (3, 50), (6, 50), (4, 38)]),
+            SwitchForkDecision::FailedSwitchThreshold {
+                switch_stake: 38,
+                total_stake: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_stake() {
+        assert_eq!(
+            check_switch_threshold(&vote_state(), 5, &ancestors(), &HashMap::new(), 0),
+            SwitchForkDecision::FailedSwitchThreshold {
+                switch_stake: 0,
+                total_stake: 0,
+            }
+        );
+    }
+}
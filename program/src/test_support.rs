@@ -0,0 +1,569 @@
+//! Deterministic vote account fixtures for tests and fuzzers.
+
+use {
+    crate::{
+        delta::{DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING, TIMELY_VOTE_CREDITS},
+        layout::{serialize_as, VoteStateTargetVersion},
+        wire,
+    },
+    solana_program::{
+        clock::{Clock, Epoch, Slot},
+        hash::{hashv, Hash},
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        vote::{
+            error::VoteError,
+            instruction as vote_instruction,
+            state::{
+                LandedVote, Lockout, VoteAuthorize, VoteInit, VoteState, VoteStateVersions,
+                MAX_EPOCH_CREDITS_HISTORY, MAX_LOCKOUT_HISTORY,
+            },
+        },
+    },
+    std::collections::VecDeque,
+};
+
+/// Credits earned per epoch in fixture credit histories.
+pub const FIXTURE_CREDITS_PER_EPOCH: u64 = 1_000;
+
+/// Returns a stable pubkey for fixture slot `n`.
+pub fn fixture_pubkey(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+/// Returns an initialized vote state with fixed node, voter and withdrawer
+/// keys and a 10% commission, as of epoch 0.
+pub fn initialized_vote_state() -> VoteState {
+    VoteState::new(&fixture_vote_init(), &Clock::default())
+}
+
+fn fixture_vote_init() -> VoteInit {
+    VoteInit {
+        node_pubkey: fixture_pubkey(1),
+        authorized_voter: fixture_pubkey(2),
+        authorized_withdrawer: fixture_pubkey(3),
+        commission: 10,
+    }
+}
+
+/// Returns a full tower of `depth` votes above `root_slot`, oldest first,
+/// each landed one slot after the slot it votes on.
+///
+/// `depth` is capped at `MAX_LOCKOUT_HISTORY`.
+pub fn tower(root_slot: Slot, depth: usize) -> VecDeque<LandedVote> {
+    let depth = depth.min(MAX_LOCKOUT_HISTORY);
+    (1..=depth)
+        .map(|offset| LandedVote {
+            latency: 1,
+            lockout: Lockout::new_with_confirmation_count(
+                root_slot.saturating_add(offset as u64),
+                (depth - offset + 1) as u32,
+            ),
+        })
+        .collect()
+}
+
+/// Returns a credits history of `len` epochs ending at `last_epoch`, with
+/// `FIXTURE_CREDITS_PER_EPOCH` credits earned in each.
+///
+/// `len` is capped at `MAX_EPOCH_CREDITS_HISTORY` and at the number of epochs
+/// up to `last_epoch`.
+pub fn epoch_credits(last_epoch: Epoch, len: usize) -> Vec<(Epoch, u64, u64)> {
+    let len = (len as u64)
+        .min(MAX_EPOCH_CREDITS_HISTORY as u64)
+        .min(last_epoch.saturating_add(1));
+    (last_epoch.saturating_add(1).saturating_sub(len)..=last_epoch)
+        .map(|epoch| {
+            let prev_credits = epoch.saturating_mul(FIXTURE_CREDITS_PER_EPOCH);
+            (
+                epoch,
+                prev_credits.saturating_add(FIXTURE_CREDITS_PER_EPOCH),
+                prev_credits,
+            )
+        })
+        .collect()
+}
+
+/// Rotates the authorized voter once per epoch, `count` times, starting from
+/// the current authorized voter. Each rotation records a prior voter.
+///
+/// New voters use fixture pubkeys counting up from 100. Returns the epoch of
+/// the last rotation.
+pub fn rotate_voters(vote_state: &mut VoteState, count: u8) -> Result<Epoch, InstructionError> {
+    let mut epoch = vote_state
+        .authorized_voters()
+        .last()
+        .map(|(epoch, _)| *epoch)
+        .unwrap_or_default();
+    for n in 0..count {
+        let target_epoch = epoch.saturating_add(1);
+        vote_state.set_new_authorized_voter(
+            &fixture_pubkey(n.saturating_add(100)),
+            epoch,
+            target_epoch,
+            |_| Ok(()),
+        )?;
+        epoch = target_epoch;
+    }
+    Ok(epoch)
+}
+
+/// Returns a vote state with every field populated: a tower of
+/// `tower_depth` votes, `epoch_credits_len` epochs of credits and
+/// `prior_voters_len` prior authorized voters.
+pub fn populated_vote_state(
+    tower_depth: usize,
+    epoch_credits_len: usize,
+    prior_voters_len: u8,
+) -> VoteState {
+    let mut vote_state = initialized_vote_state();
+    let epoch = rotate_voters(&mut vote_state, prior_voters_len)
+        .expect("fixture voter rotations target increasing epochs");
+    let root_slot = epoch.saturating_mul(MAX_LOCKOUT_HISTORY as u64 + 1);
+    vote_state.votes = tower(root_slot, tower_depth);
+    vote_state.root_slot = Some(root_slot);
+    vote_state.epoch_credits = epoch_credits(epoch, epoch_credits_len);
+    vote_state.last_timestamp.slot = vote_state.last_voted_slot().unwrap_or(root_slot);
+    vote_state
+}
+
+/// Fluent construction of vote states that the program would accept.
+///
+/// Starts from the keys and commission of [`initialized_vote_state`].
+#[derive(Clone, Debug)]
+pub struct VoteStateBuilder {
+    vote_init: VoteInit,
+    votes: Vec<(Slot, u8)>,
+    epoch_credits: Option<Vec<(Epoch, u64, u64)>>,
+    prior_voters: u8,
+}
+
+impl Default for VoteStateBuilder {
+    fn default() -> Self {
+        Self {
+            vote_init: fixture_vote_init(),
+            votes: vec![],
+            epoch_credits: None,
+            prior_voters: 0,
+        }
+    }
+}
+
+impl VoteStateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node_pubkey(mut self, node_pubkey: Pubkey) -> Self {
+        self.vote_init.node_pubkey = node_pubkey;
+        self
+    }
+
+    pub fn authorized_voter(mut self, authorized_voter: Pubkey) -> Self {
+        self.vote_init.authorized_voter = authorized_voter;
+        self
+    }
+
+    pub fn authorized_withdrawer(mut self, authorized_withdrawer: Pubkey) -> Self {
+        self.vote_init.authorized_withdrawer = authorized_withdrawer;
+        self
+    }
+
+    pub fn commission(mut self, commission: u8) -> Self {
+        self.vote_init.commission = commission;
+        self
+    }
+
+    /// Votes on `slot`, landing `latency` slots later. Votes are applied in
+    /// order through the tower, so lockouts double and roots advance as on
+    /// chain; a slot at or below the last voted slot is ignored.
+    pub fn vote(mut self, slot: Slot, latency: u8) -> Self {
+        self.votes.push((slot, latency));
+        self
+    }
+
+    /// Replaces the credits history the votes would have produced.
+    pub fn epoch_credits(mut self, epoch_credits: Vec<(Epoch, u64, u64)>) -> Self {
+        self.epoch_credits = Some(epoch_credits);
+        self
+    }
+
+    /// Rotates the authorized voter `count` times, as [`rotate_voters`]
+    /// does, before any votes are applied.
+    pub fn prior_voters(mut self, count: u8) -> Self {
+        self.prior_voters = count;
+        self
+    }
+
+    pub fn build(&self) -> VoteState {
+        let mut vote_state = VoteState::new(&self.vote_init, &Clock::default());
+        let epoch = rotate_voters(&mut vote_state, self.prior_voters)
+            .expect("fixture voter rotations target increasing epochs");
+        for (slot, latency) in &self.votes {
+            vote_state.process_next_vote_slot(
+                *slot,
+                epoch,
+                slot.saturating_add(u64::from(*latency)),
+                TIMELY_VOTE_CREDITS,
+                DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+            );
+        }
+        if let Some(epoch_credits) = &self.epoch_credits {
+            vote_state.epoch_credits.clone_from(epoch_credits);
+        }
+        vote_state
+    }
+
+    /// Builds the vote state and serializes it in the `version` layout into
+    /// a buffer of exactly that layout's size.
+    pub fn build_serialized(
+        &self,
+        version: VoteStateTargetVersion,
+    ) -> Result<Vec<u8>, InstructionError> {
+        serialize_fixture(version, &self.build())
+    }
+}
+
+/// Serializes `vote_state` in the current layout into a buffer of exactly
+/// `VoteState::size_of()` bytes.
+pub fn serialize_current(vote_state: &VoteState) -> Result<Vec<u8>, InstructionError> {
+    serialize_fixture(VoteStateTargetVersion::Current, vote_state)
+}
+
+/// Serializes `vote_state` in the 1.14.11 layout into a buffer of exactly
+/// `VoteState1_14_11::size_of()` bytes. Vote latencies are dropped.
+pub fn serialize_1_14_11(vote_state: &VoteState) -> Result<Vec<u8>, InstructionError> {
+    serialize_fixture(VoteStateTargetVersion::V1_14_11, vote_state)
+}
+
+fn serialize_fixture(
+    version: VoteStateTargetVersion,
+    vote_state: &VoteState,
+) -> Result<Vec<u8>, InstructionError> {
+    let mut data = vec![0; version.size_of()];
+    serialize_as(version, &mut vote_state.clone(), &mut data)?;
+    Ok(data)
+}
+
+/// Restores a vote state from a fixture buffer of any layout.
+pub fn restore(data: &[u8]) -> Result<VoteState, InstructionError> {
+    wire::deserialize::<VoteStateVersions>(data, data.len() as u64)
+        .map(VoteStateVersions::convert_to_current)
+        .map_err(|_| InstructionError::InvalidAccountData)
+}
+
+/// Slots per epoch in [`fixture_clock`].
+pub const FIXTURE_SLOTS_PER_EPOCH: u64 = 32;
+
+/// Returns a clock at the first slot of `epoch`, with the leader schedule
+/// computed one epoch ahead as on a live cluster.
+pub fn fixture_clock(epoch: Epoch) -> Clock {
+    Clock {
+        slot: epoch.saturating_mul(FIXTURE_SLOTS_PER_EPOCH),
+        epoch,
+        leader_schedule_epoch: epoch.saturating_add(1),
+        ..Clock::default()
+    }
+}
+
+/// Returns a stable bank hash for fixture slot `slot`.
+pub fn fixture_bank_hash(slot: Slot) -> Hash {
+    hashv(&[&slot.to_le_bytes()])
+}
+
+/// Returns `SlotHashes` entries for `slots`, newest first as in the sysvar,
+/// each with its [`fixture_bank_hash`].
+pub fn fixture_slot_hashes(slots: impl IntoIterator<Item = Slot>) -> Vec<(Slot, Hash)> {
+    let mut slot_hashes: Vec<_> = slots
+        .into_iter()
+        .map(|slot| (slot, fixture_bank_hash(slot)))
+        .collect();
+    slot_hashes.sort_by_key(|(slot, _)| std::cmp::Reverse(*slot));
+    slot_hashes
+}
+
+/// One instruction in an [`AuthorityScenario`] and its expected outcome.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthorityStep {
+    pub clock: Clock,
+    /// Signers are the instruction's signer account metas.
+    pub instruction: Instruction,
+    pub expected: Result<(), InstructionError>,
+}
+
+/// A sequence of authority changes against [`initialized_vote_state`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthorityScenario {
+    pub name: &'static str,
+    pub steps: Vec<AuthorityStep>,
+    /// Authorized voter expected for each listed epoch after all steps.
+    pub expected_authorized_voters: Vec<(Epoch, Pubkey)>,
+    pub expected_authorized_withdrawer: Pubkey,
+}
+
+/// Voter and withdrawer rotation sequences across epoch boundaries, each
+/// starting from [`initialized_vote_state`].
+///
+/// Failed steps leave the state unchanged. The vote account is
+/// `fixture_pubkey(0)`, the voter `fixture_pubkey(2)` and the withdrawer
+/// `fixture_pubkey(3)`; new authorities count up from `fixture_pubkey(10)`.
+pub fn authority_scenarios() -> Vec<AuthorityScenario> {
+    let vote = fixture_pubkey(0);
+    let voter = fixture_pubkey(2);
+    let withdrawer = fixture_pubkey(3);
+    let new_voter = fixture_pubkey(10);
+    let newer_voter = fixture_pubkey(11);
+    let new_withdrawer = fixture_pubkey(20);
+    let authorize = |signer: &Pubkey, new_authority: &Pubkey, vote_authorize| {
+        vote_instruction::authorize(&vote, signer, new_authority, vote_authorize)
+    };
+    let step = |epoch, instruction, expected| AuthorityStep {
+        clock: fixture_clock(epoch),
+        instruction,
+        expected,
+    };
+    let too_soon = Err(InstructionError::Custom(
+        VoteError::TooSoonToReauthorize as u32,
+    ));
+    vec![
+        AuthorityScenario {
+            name: "pending voter activates after the leader schedule epoch",
+            steps: vec![step(
+                0,
+                authorize(&voter, &new_voter, VoteAuthorize::Voter),
+                Ok(()),
+            )],
+            expected_authorized_voters: vec![(0, voter), (1, voter), (2, new_voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "second voter change in the same epoch is rejected",
+            steps: vec![
+                step(
+                    0,
+                    authorize(&voter, &new_voter, VoteAuthorize::Voter),
+                    Ok(()),
+                ),
+                step(
+                    0,
+                    authorize(&voter, &newer_voter, VoteAuthorize::Voter),
+                    too_soon.clone(),
+                ),
+            ],
+            expected_authorized_voters: vec![(1, voter), (2, new_voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "voter change in the next epoch queues behind the pending voter",
+            steps: vec![
+                step(
+                    0,
+                    authorize(&voter, &new_voter, VoteAuthorize::Voter),
+                    Ok(()),
+                ),
+                step(
+                    1,
+                    authorize(&voter, &newer_voter, VoteAuthorize::Voter),
+                    Ok(()),
+                ),
+            ],
+            expected_authorized_voters: vec![(1, voter), (2, new_voter), (3, newer_voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "pending voter cannot sign before it activates",
+            steps: vec![
+                step(
+                    0,
+                    authorize(&voter, &new_voter, VoteAuthorize::Voter),
+                    Ok(()),
+                ),
+                step(
+                    1,
+                    authorize(&new_voter, &newer_voter, VoteAuthorize::Voter),
+                    Err(InstructionError::MissingRequiredSignature),
+                ),
+            ],
+            expected_authorized_voters: vec![(1, voter), (2, new_voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "withdrawer overrides the voter",
+            steps: vec![step(
+                0,
+                authorize(&withdrawer, &new_voter, VoteAuthorize::Voter),
+                Ok(()),
+            )],
+            expected_authorized_voters: vec![(1, voter), (2, new_voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "withdrawer override is still limited to one change per epoch",
+            steps: vec![
+                step(
+                    0,
+                    authorize(&voter, &new_voter, VoteAuthorize::Voter),
+                    Ok(()),
+                ),
+                step(
+                    0,
+                    authorize(&withdrawer, &newer_voter, VoteAuthorize::Voter),
+                    too_soon,
+                ),
+            ],
+            expected_authorized_voters: vec![(1, voter), (2, new_voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "voter cannot change the withdrawer",
+            steps: vec![step(
+                0,
+                authorize(&voter, &new_withdrawer, VoteAuthorize::Withdrawer),
+                Err(InstructionError::MissingRequiredSignature),
+            )],
+            expected_authorized_voters: vec![(1, voter)],
+            expected_authorized_withdrawer: withdrawer,
+        },
+        AuthorityScenario {
+            name: "withdrawer change takes effect immediately",
+            steps: vec![
+                step(
+                    0,
+                    authorize(&withdrawer, &new_withdrawer, VoteAuthorize::Withdrawer),
+                    Ok(()),
+                ),
+                step(
+                    0,
+                    authorize(&withdrawer, &new_voter, VoteAuthorize::Voter),
+                    Err(InstructionError::MissingRequiredSignature),
+                ),
+                step(
+                    0,
+                    authorize(&new_withdrawer, &new_voter, VoteAuthorize::Voter),
+                    Ok(()),
+                ),
+            ],
+            expected_authorized_voters: vec![(1, voter), (2, new_voter)],
+            expected_authorized_withdrawer: new_withdrawer,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::predict::{predict, PreAccount},
+        solana_program::{epoch_schedule::EpochSchedule, rent::Rent},
+    };
+
+    fn fixtures() -> Vec<VoteState> {
+        vec![
+            VoteState::default(),
+            initialized_vote_state(),
+            populated_vote_state(0, 0, 0),
+            populated_vote_state(MAX_LOCKOUT_HISTORY, MAX_EPOCH_CREDITS_HISTORY, 0),
+            populated_vote_state(MAX_LOCKOUT_HISTORY, MAX_EPOCH_CREDITS_HISTORY, 40),
+            VoteStateBuilder::new()
+                .commission(100)
+                .prior_voters(3)
+                .vote(1, 1)
+                .vote(2, 3)
+                .vote(5, 1)
+                .build(),
+            VoteStateBuilder::new()
+                .epoch_credits(epoch_credits(10, 5))
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_fixtures_round_trip_current() {
+        for vote_state in fixtures() {
+            let data = serialize_current(&vote_state).unwrap();
+            assert_eq!(data.len(), VoteState::size_of());
+            assert_eq!(restore(&data).unwrap(), vote_state);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_round_trip_1_14_11() {
+        for mut vote_state in fixtures() {
+            let data = serialize_1_14_11(&vote_state).unwrap();
+            for landed_vote in vote_state.votes.iter_mut() {
+                landed_vote.latency = 0;
+            }
+            assert_eq!(restore(&data).unwrap(), vote_state);
+        }
+    }
+
+    #[test]
+    fn test_build_serialized() {
+        let builder = VoteStateBuilder::new().vote(7, 2);
+        let data = builder
+            .build_serialized(VoteStateTargetVersion::Current)
+            .unwrap();
+        assert_eq!(restore(&data).unwrap(), builder.build());
+    }
+
+    #[test]
+    fn test_builder_votes_through_tower() {
+        let vote_state = (1..=MAX_LOCKOUT_HISTORY as u64 + 1)
+            .fold(VoteStateBuilder::new(), |builder, slot| {
+                builder.vote(slot, 1)
+            })
+            .build();
+        assert_eq!(vote_state.root_slot, Some(1));
+        assert_eq!(vote_state.votes.len(), MAX_LOCKOUT_HISTORY);
+    }
+
+    #[test]
+    fn test_tower_and_epoch_credits_caps() {
+        assert_eq!(tower(0, 100).len(), MAX_LOCKOUT_HISTORY);
+        assert_eq!(epoch_credits(3, 10).len(), 4);
+        assert_eq!(epoch_credits(1_000, 1_000).len(), MAX_EPOCH_CREDITS_HISTORY);
+    }
+
+    #[test]
+    fn test_authority_scenarios() {
+        let pre_account = PreAccount {
+            lamports: 1_000_000_000,
+            data_len: VoteState::size_of(),
+        };
+        for scenario in authority_scenarios() {
+            let mut vote_state = initialized_vote_state();
+            for step in &scenario.steps {
+                let result = predict(
+                    &step.instruction,
+                    &vote_state,
+                    pre_account,
+                    &step.clock,
+                    &Rent::default(),
+                    &EpochSchedule::default(),
+                );
+                assert_eq!(
+                    result.as_ref().map(|_| ()).map_err(Clone::clone),
+                    step.expected,
+                    "{}",
+                    scenario.name
+                );
+                if let Ok(effects) = result {
+                    vote_state = effects.vote_state.unwrap();
+                }
+            }
+            for (epoch, authorized_voter) in &scenario.expected_authorized_voters {
+                assert_eq!(
+                    vote_state.authorized_voters().get_authorized_voter(*epoch),
+                    Some(*authorized_voter),
+                    "{}: epoch {epoch}",
+                    scenario.name
+                );
+            }
+            assert_eq!(
+                vote_state.authorized_withdrawer, scenario.expected_authorized_withdrawer,
+                "{}",
+                scenario.name
+            );
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Cluster time estimation from vote timestamps.
+//!
+//! Follows the runtime's stake-weighted timestamp: each sample is projected
+//! forward to the target slot, the stake-weighted median is taken, and the
+//! result is bounded by the allowed drift from the PoH estimate.
+
+use {
+    solana_program::{
+        clock::{Slot, UnixTimestamp},
+        vote::state::BlockTimestamp,
+    },
+    std::{collections::BTreeMap, time::Duration},
+};
+
+/// Percentage PoH may run fast, i.e. the estimate may lag the PoH estimate.
+pub const MAX_ALLOWABLE_DRIFT_PERCENTAGE_FAST: u32 = 25;
+/// Percentage PoH may run slow, i.e. the estimate may lead the PoH estimate.
+pub const MAX_ALLOWABLE_DRIFT_PERCENTAGE_SLOW: u32 = 150;
+
+/// A vote account's latest timestamp and its stake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimestampSample {
+    pub stake: u64,
+    pub timestamp: BlockTimestamp,
+}
+
+/// Allowed drift from the PoH estimate, in percent of the time elapsed
+/// since the epoch started.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaxAllowableDrift {
+    pub fast: u32,
+    pub slow: u32,
+}
+
+impl Default for MaxAllowableDrift {
+    fn default() -> Self {
+        Self {
+            fast: MAX_ALLOWABLE_DRIFT_PERCENTAGE_FAST,
+            slow: MAX_ALLOWABLE_DRIFT_PERCENTAGE_SLOW,
+        }
+    }
+}
+
+/// Stake-weighted median of `samples` projected to `slot`, or `None` if no
+/// sample carries stake.
+///
+/// As in the runtime, a sample for a slot after `slot` counts with its own
+/// timestamp rather than being dropped.
+pub fn stake_weighted_timestamp(
+    samples: impl IntoIterator<Item = TimestampSample>,
+    slot: Slot,
+    slot_duration: Duration,
+) -> Option<UnixTimestamp> {
+    let mut stake_per_timestamp: BTreeMap<UnixTimestamp, u128> = BTreeMap::new();
+    let mut total_stake: u128 = 0;
+    for sample in samples {
+        let BlockTimestamp {
+            slot: vote_slot,
+            timestamp,
+        } = sample.timestamp;
+        let elapsed_slots = slot.saturating_sub(vote_slot);
+        let offset = slot_duration.saturating_mul(u32::try_from(elapsed_slots).unwrap_or(u32::MAX));
+        let estimate = timestamp.saturating_add(offset.as_secs() as i64);
+        let stake = u128::from(sample.stake);
+        *stake_per_timestamp.entry(estimate).or_default() += stake;
+        total_stake += stake;
+    }
+    if total_stake == 0 {
+        return None;
+    }
+    let mut stake_accumulator: u128 = 0;
+    stake_per_timestamp
+        .into_iter()
+        .find(|(_, stake)| {
+            stake_accumulator += stake;
+            stake_accumulator > total_stake / 2
+        })
+        .map(|(timestamp, _)| timestamp)
+}
+
+/// Clamps `estimate` for `slot` to within `max_allowable_drift` of the PoH
+/// estimate measured from `epoch_start`.
+pub fn bound_timestamp_drift(
+    estimate: UnixTimestamp,
+    slot: Slot,
+    epoch_start: &BlockTimestamp,
+    slot_duration: Duration,
+    max_allowable_drift: MaxAllowableDrift,
+) -> UnixTimestamp {
+    let elapsed_slots = slot.saturating_sub(epoch_start.slot);
+    let poh_estimate_offset =
+        slot_duration.saturating_mul(u32::try_from(elapsed_slots).unwrap_or(u32::MAX));
+    let estimate_offset =
+        Duration::from_secs((estimate as u64).saturating_sub(epoch_start.timestamp as u64));
+    let max_drift_fast = poh_estimate_offset.saturating_mul(max_allowable_drift.fast) / 100;
+    let max_drift_slow = poh_estimate_offset.saturating_mul(max_allowable_drift.slow) / 100;
+    let poh_estimate = epoch_start
+        .timestamp
+        .saturating_add(poh_estimate_offset.as_secs() as i64);
+    if estimate_offset > poh_estimate_offset
+        && estimate_offset - poh_estimate_offset > max_drift_slow
+    {
+        poh_estimate.saturating_add(max_drift_slow.as_secs() as i64)
+    } else if estimate_offset < poh_estimate_offset
+        && poh_estimate_offset - estimate_offset > max_drift_fast
+    {
+        poh_estimate.saturating_sub(max_drift_fast.as_secs() as i64)
+    } else {
+        estimate
+    }
+}
+
+/// [`stake_weighted_timestamp`] bounded by [`bound_timestamp_drift`], as the
+/// runtime computes a bank's timestamp.
+pub fn estimate_cluster_timestamp(
+    samples: impl IntoIterator<Item = TimestampSample>,
+    slot: Slot,
+    epoch_start: &BlockTimestamp,
+    slot_duration: Duration,
+    max_allowable_drift: MaxAllowableDrift,
+) -> Option<UnixTimestamp> {
+    stake_weighted_timestamp(samples, slot, slot_duration).map(|estimate| {
+        bound_timestamp_drift(
+            estimate,
+            slot,
+            epoch_start,
+            slot_duration,
+            max_allowable_drift,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOT_DURATION: Duration = Duration::from_millis(400);
+    const RECENT_TIMESTAMP: UnixTimestamp = 1_578_909_061;
+
+    fn sample(stake: u64, slot: Slot, timestamp: UnixTimestamp) -> TimestampSample {
+        TimestampSample {
+            stake,
+            timestamp: BlockTimestamp { slot, timestamp },
+        }
+    }
+
+    #[test]
+    fn test_stake_weighted_timestamp_uses_median() {
+        // Samples from slot 0 are projected two seconds forward to slot 5.
+        // The light outlier does not move the median off the middle of the
+        // heavily staked samples.
+        let samples = [
+            sample(1, 0, RECENT_TIMESTAMP),
+            sample(1, 0, RECENT_TIMESTAMP + 100),
+            sample(1_000_000, 0, RECENT_TIMESTAMP + 1),
+            sample(1_000_000, 0, RECENT_TIMESTAMP + 2),
+            sample(1_000_000, 0, RECENT_TIMESTAMP + 3),
+        ];
+        assert_eq!(
+            stake_weighted_timestamp(samples, 5, SLOT_DURATION),
+            Some(RECENT_TIMESTAMP + 2 + 2)
+        );
+    }
+
+    #[test]
+    fn test_stake_weighted_timestamp_counts_future_samples() {
+        // The majority voted past slot 5 and is counted without projection.
+        let samples = [
+            sample(1, 0, RECENT_TIMESTAMP),
+            sample(2, 10, RECENT_TIMESTAMP + 10),
+        ];
+        assert_eq!(
+            stake_weighted_timestamp(samples, 5, SLOT_DURATION),
+            Some(RECENT_TIMESTAMP + 10)
+        );
+        assert_eq!(
+            stake_weighted_timestamp([sample(1, 10, RECENT_TIMESTAMP)], 5, SLOT_DURATION),
+            Some(RECENT_TIMESTAMP)
+        );
+    }
+
+    #[test]
+    fn test_stake_weighted_timestamp_without_stake() {
+        assert_eq!(stake_weighted_timestamp([], 5, SLOT_DURATION), None);
+        assert_eq!(
+            stake_weighted_timestamp([sample(0, 0, RECENT_TIMESTAMP)], 5, SLOT_DURATION),
+            None
+        );
+    }
+}
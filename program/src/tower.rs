@@ -0,0 +1,275 @@
+//! A vote tower that keeps its invariants on every mutation.
+//!
+//! `VoteState::votes` is a plain `VecDeque` defined upstream, so [`Tower`]
+//! is used alongside it: load with [`Tower::from_vote_state`], mutate, and
+//! store back with [`Tower::store`].
+
+use {
+    solana_program::{
+        clock::Slot,
+        vote::{
+            error::VoteError,
+            state::{LandedVote, Lockout, VoteState, MAX_LOCKOUT_HISTORY},
+        },
+    },
+    std::collections::VecDeque,
+};
+
+/// At most `MAX_LOCKOUT_HISTORY` votes, slots strictly increasing and
+/// confirmation counts strictly decreasing from oldest to newest, each
+/// between 1 and `MAX_LOCKOUT_HISTORY`, and every vote within the lockout of
+/// the vote before it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tower(VecDeque<LandedVote>);
+
+impl Tower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the tower of `vote_state`.
+    pub fn from_vote_state(vote_state: &VoteState) -> Result<Self, VoteError> {
+        Self::try_from(vote_state.votes.clone())
+    }
+
+    /// Replaces the tower of `vote_state` with this one.
+    pub fn store(self, vote_state: &mut VoteState) {
+        vote_state.votes = self.0;
+    }
+
+    /// Votes on `slot` from a vote landed in `current_slot`, as the program
+    /// does: expired votes are popped, the oldest vote roots if the tower is
+    /// full, and lockouts double where enough votes stack on them.
+    ///
+    /// Returns the vote that rooted, if any.
+    pub fn record_vote(
+        &mut self,
+        slot: Slot,
+        current_slot: Slot,
+    ) -> Result<Option<LandedVote>, VoteError> {
+        if self.newest().is_some_and(|newest| slot <= newest.slot()) {
+            return Err(VoteError::VoteTooOld);
+        }
+        while self
+            .newest()
+            .is_some_and(|newest| !newest.lockout.is_locked_out_at_slot(slot))
+        {
+            self.0.pop_back();
+        }
+        let rooted = if self.0.len() == MAX_LOCKOUT_HISTORY {
+            self.0.pop_front()
+        } else {
+            None
+        };
+        self.0.push_back(LandedVote {
+            latency: VoteState::compute_vote_latency(slot, current_slot),
+            lockout: Lockout::new(slot),
+        });
+        let depth = self.0.len();
+        for (index, vote) in self.0.iter_mut().enumerate() {
+            if depth > index + vote.confirmation_count() as usize {
+                vote.lockout.increase_confirmation_count(1);
+            }
+        }
+        Ok(rooted)
+    }
+
+    /// Appends `vote` as the newest vote, with its confirmation count as
+    /// already computed; [`Tower::record_vote`] computes it.
+    pub fn push(&mut self, vote: LandedVote) -> Result<(), VoteError> {
+        if self.0.len() >= MAX_LOCKOUT_HISTORY {
+            return Err(VoteError::TooManyVotes);
+        }
+        check_confirmation_count(&vote)?;
+        if let Some(newest) = self.0.back() {
+            check_order(newest, &vote)?;
+        }
+        self.0.push_back(vote);
+        Ok(())
+    }
+
+    /// Removes the newest vote.
+    pub fn pop_newest(&mut self) -> Option<LandedVote> {
+        self.0.pop_back()
+    }
+
+    /// Removes the oldest vote, as when it roots.
+    pub fn pop_oldest(&mut self) -> Option<LandedVote> {
+        self.0.pop_front()
+    }
+
+    pub fn newest(&self) -> Option<&LandedVote> {
+        self.0.back()
+    }
+
+    pub fn oldest(&self) -> Option<&LandedVote> {
+        self.0.front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Votes, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &LandedVote> {
+        self.0.iter()
+    }
+
+    pub fn into_inner(self) -> VecDeque<LandedVote> {
+        self.0
+    }
+}
+
+impl TryFrom<VecDeque<LandedVote>> for Tower {
+    type Error = VoteError;
+
+    fn try_from(votes: VecDeque<LandedVote>) -> Result<Self, VoteError> {
+        if votes.len() > MAX_LOCKOUT_HISTORY {
+            return Err(VoteError::TooManyVotes);
+        }
+        for vote in &votes {
+            check_confirmation_count(vote)?;
+        }
+        for (older, newer) in votes.iter().zip(votes.iter().skip(1)) {
+            check_order(older, newer)?;
+        }
+        Ok(Self(votes))
+    }
+}
+
+impl From<Tower> for VecDeque<LandedVote> {
+    fn from(tower: Tower) -> Self {
+        tower.0
+    }
+}
+
+fn check_confirmation_count(vote: &LandedVote) -> Result<(), VoteError> {
+    match vote.confirmation_count() {
+        0 => Err(VoteError::ZeroConfirmations),
+        count if count as usize > MAX_LOCKOUT_HISTORY => Err(VoteError::ConfirmationTooLarge),
+        _ => Ok(()),
+    }
+}
+
+fn check_order(older: &LandedVote, newer: &LandedVote) -> Result<(), VoteError> {
+    if older.slot() >= newer.slot() {
+        return Err(VoteError::SlotsNotOrdered);
+    }
+    if older.confirmation_count() <= newer.confirmation_count() {
+        return Err(VoteError::ConfirmationsNotOrdered);
+    }
+    // A vote past the older vote's lockout would have expired it.
+    if newer.slot() > older.lockout.last_locked_out_slot() {
+        return Err(VoteError::NewVoteStateLockoutMismatch);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            delta::{DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING, TIMELY_VOTE_CREDITS},
+            test_support::{populated_vote_state, tower},
+        },
+    };
+
+    fn landed(slot: Slot, confirmation_count: u32) -> LandedVote {
+        LandedVote {
+            latency: 1,
+            lockout: Lockout::new_with_confirmation_count(slot, confirmation_count),
+        }
+    }
+
+    #[test]
+    fn test_record_vote_matches_sdk() {
+        // Consecutive votes, skips that expire lockouts, and enough votes to
+        // root.
+        let slots = (1..=40).chain([45, 46, 60, 61, 62, 200]).chain(201..=240);
+        let mut tower = Tower::new();
+        let mut vote_state = VoteState::default();
+        for slot in slots {
+            let root_slot = vote_state.root_slot;
+            vote_state.process_next_vote_slot(
+                slot,
+                0,
+                slot + 1,
+                TIMELY_VOTE_CREDITS,
+                DEPRECATE_UNUSED_LEGACY_VOTE_PLUMBING,
+            );
+            let rooted = tower.record_vote(slot, slot + 1).unwrap();
+            assert_eq!(
+                rooted.map(|vote| vote.slot()),
+                vote_state
+                    .root_slot
+                    .filter(|_| vote_state.root_slot != root_slot)
+            );
+            assert_eq!(
+                tower.iter().collect::<Vec<_>>(),
+                vote_state.votes.iter().collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(Tower::from_vote_state(&vote_state), Ok(tower));
+    }
+
+    #[test]
+    fn test_record_vote_rejects_old_slot() {
+        let mut tower = Tower::new();
+        tower.record_vote(5, 6).unwrap();
+        assert_eq!(tower.record_vote(5, 7), Err(VoteError::VoteTooOld));
+        assert_eq!(tower.record_vote(4, 7), Err(VoteError::VoteTooOld));
+    }
+
+    #[test]
+    fn test_from_vote_state() {
+        let vote_state = populated_vote_state(MAX_LOCKOUT_HISTORY, 4, 0);
+        let tower = Tower::from_vote_state(&vote_state).unwrap();
+        let mut stored = VoteState::default();
+        tower.store(&mut stored);
+        assert_eq!(stored.votes, vote_state.votes);
+    }
+
+    #[test]
+    fn test_invariants() {
+        for (votes, err) in [
+            (vec![landed(1, 0)], VoteError::ZeroConfirmations),
+            (
+                vec![landed(1, MAX_LOCKOUT_HISTORY as u32 + 1)],
+                VoteError::ConfirmationTooLarge,
+            ),
+            (vec![landed(2, 2), landed(1, 1)], VoteError::SlotsNotOrdered),
+            (
+                vec![landed(1, 1), landed(2, 1)],
+                VoteError::ConfirmationsNotOrdered,
+            ),
+            // Slot 1 at two confirmations is locked out through slot 5.
+            (
+                vec![landed(1, 2), landed(6, 1)],
+                VoteError::NewVoteStateLockoutMismatch,
+            ),
+            (
+                tower(0, MAX_LOCKOUT_HISTORY)
+                    .into_iter()
+                    .chain([landed(100, 1)])
+                    .collect(),
+                VoteError::TooManyVotes,
+            ),
+        ] {
+            assert_eq!(
+                Tower::try_from(VecDeque::from(votes.clone())),
+                Err(err.clone())
+            );
+            let mut tower = Tower::new();
+            let pushed = votes.into_iter().try_for_each(|vote| tower.push(vote));
+            assert_eq!(pushed, Err(err));
+        }
+        let mut tower = Tower::new();
+        tower.push(landed(1, 2)).unwrap();
+        tower.push(landed(5, 1)).unwrap();
+    }
+}
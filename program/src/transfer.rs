@@ -0,0 +1,261 @@
+//! Ordered transaction plan for handing a validator to a new operator.
+
+use {
+    solana_program::{
+        clock::{Clock, Epoch},
+        instruction::Instruction,
+        pubkey::Pubkey,
+        vote::{
+            instruction as vote_instruction,
+            state::{VoteAuthorize, VoteState},
+        },
+    },
+    thiserror::Error,
+};
+
+/// Keys the new operator takes over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NewOperator {
+    pub node_pubkey: Pubkey,
+    pub authorized_voter: Pubkey,
+    pub authorized_withdrawer: Pubkey,
+}
+
+/// Which part of the transfer a step performs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferStepKind {
+    RotateVoter,
+    RotateWithdrawer,
+    UpdateIdentity,
+}
+
+/// One transaction of the transfer. Every step must land before the next is
+/// sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferStep {
+    pub kind: TransferStepKind,
+    pub instruction: Instruction,
+}
+
+/// Reasons the transfer would fail against the current vote state.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum TransferError {
+    #[error("vote account withdrawer is {actual}, not {expected}")]
+    WithdrawerMismatch { expected: Pubkey, actual: Pubkey },
+    #[error("a voter change is already pending for epoch {0}")]
+    VoterChangePending(Epoch),
+}
+
+/// Plans the transfer of `vote_pubkey` from `authorized_withdrawer` to
+/// `new_operator`, checking each step against `vote_state` as of `clock`.
+///
+/// Steps already satisfied by `vote_state` are left out. The steps run in
+/// order:
+/// 1. the current withdrawer rotates the voter;
+/// 2. the current withdrawer rotates the withdrawer;
+/// 3. the new withdrawer and new identity update the validator identity.
+///
+/// Rotations use the checked variants, so the new keys must sign too.
+///
+/// The voter rotation counts as done once the newest authorized voter entry
+/// is the new voter, even while that entry is pending for a future epoch;
+/// the new voter takes over when that epoch starts.
+pub fn plan_operator_transfer(
+    vote_pubkey: &Pubkey,
+    vote_state: &VoteState,
+    clock: &Clock,
+    authorized_withdrawer: &Pubkey,
+    new_operator: &NewOperator,
+) -> Result<Vec<TransferStep>, TransferError> {
+    if vote_state.authorized_withdrawer != *authorized_withdrawer {
+        return Err(TransferError::WithdrawerMismatch {
+            expected: *authorized_withdrawer,
+            actual: vote_state.authorized_withdrawer,
+        });
+    }
+
+    let mut steps = vec![];
+    let target_epoch = clock.leader_schedule_epoch.saturating_add(1);
+    let latest_voter = vote_state
+        .authorized_voters()
+        .last()
+        .map(|(_, authorized_voter)| *authorized_voter);
+    if latest_voter != Some(new_operator.authorized_voter) {
+        if vote_state.authorized_voters().contains(target_epoch) {
+            return Err(TransferError::VoterChangePending(target_epoch));
+        }
+        steps.push(TransferStep {
+            kind: TransferStepKind::RotateVoter,
+            instruction: vote_instruction::authorize_checked(
+                vote_pubkey,
+                authorized_withdrawer,
+                &new_operator.authorized_voter,
+                VoteAuthorize::Voter,
+            ),
+        });
+    }
+    if *authorized_withdrawer != new_operator.authorized_withdrawer {
+        steps.push(TransferStep {
+            kind: TransferStepKind::RotateWithdrawer,
+            instruction: vote_instruction::authorize_checked(
+                vote_pubkey,
+                authorized_withdrawer,
+                &new_operator.authorized_withdrawer,
+                VoteAuthorize::Withdrawer,
+            ),
+        });
+    }
+    if vote_state.node_pubkey != new_operator.node_pubkey {
+        steps.push(TransferStep {
+            kind: TransferStepKind::UpdateIdentity,
+            instruction: vote_instruction::update_validator_identity(
+                vote_pubkey,
+                &new_operator.authorized_withdrawer,
+                &new_operator.node_pubkey,
+            ),
+        });
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_clock, fixture_pubkey, initialized_vote_state},
+    };
+
+    const VOTE_ACCOUNT: u8 = 0;
+    /// Withdrawer of [`initialized_vote_state`].
+    const WITHDRAWER: u8 = 3;
+
+    fn new_operator() -> NewOperator {
+        NewOperator {
+            node_pubkey: fixture_pubkey(11),
+            authorized_voter: fixture_pubkey(12),
+            authorized_withdrawer: fixture_pubkey(13),
+        }
+    }
+
+    fn plan(
+        vote_state: &VoteState,
+        new_operator: &NewOperator,
+    ) -> Result<Vec<TransferStepKind>, TransferError> {
+        plan_operator_transfer(
+            &fixture_pubkey(VOTE_ACCOUNT),
+            vote_state,
+            &fixture_clock(0),
+            &fixture_pubkey(WITHDRAWER),
+            new_operator,
+        )
+        .map(|steps| steps.into_iter().map(|step| step.kind).collect())
+    }
+
+    #[test]
+    fn test_full_plan_in_order() {
+        let new_operator = new_operator();
+        let steps = plan_operator_transfer(
+            &fixture_pubkey(VOTE_ACCOUNT),
+            &initialized_vote_state(),
+            &fixture_clock(0),
+            &fixture_pubkey(WITHDRAWER),
+            &new_operator,
+        )
+        .unwrap();
+        let vote_pubkey = fixture_pubkey(VOTE_ACCOUNT);
+        let withdrawer = fixture_pubkey(WITHDRAWER);
+        assert_eq!(
+            steps,
+            [
+                TransferStep {
+                    kind: TransferStepKind::RotateVoter,
+                    instruction: vote_instruction::authorize_checked(
+                        &vote_pubkey,
+                        &withdrawer,
+                        &new_operator.authorized_voter,
+                        VoteAuthorize::Voter,
+                    ),
+                },
+                TransferStep {
+                    kind: TransferStepKind::RotateWithdrawer,
+                    instruction: vote_instruction::authorize_checked(
+                        &vote_pubkey,
+                        &withdrawer,
+                        &new_operator.authorized_withdrawer,
+                        VoteAuthorize::Withdrawer,
+                    ),
+                },
+                TransferStep {
+                    kind: TransferStepKind::UpdateIdentity,
+                    instruction: vote_instruction::update_validator_identity(
+                        &vote_pubkey,
+                        &new_operator.authorized_withdrawer,
+                        &new_operator.node_pubkey,
+                    ),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_satisfied_steps_skipped() {
+        let vote_state = initialized_vote_state();
+        let unchanged = NewOperator {
+            node_pubkey: vote_state.node_pubkey,
+            authorized_voter: fixture_pubkey(2),
+            authorized_withdrawer: fixture_pubkey(WITHDRAWER),
+        };
+        assert_eq!(plan(&vote_state, &unchanged), Ok(vec![]));
+        let new_identity = NewOperator {
+            node_pubkey: fixture_pubkey(11),
+            ..unchanged
+        };
+        assert_eq!(
+            plan(&vote_state, &new_identity),
+            Ok(vec![TransferStepKind::UpdateIdentity])
+        );
+    }
+
+    #[test]
+    fn test_pending_new_voter_skips_rotation() {
+        let new_operator = new_operator();
+        let mut vote_state = initialized_vote_state();
+        vote_state
+            .set_new_authorized_voter(&new_operator.authorized_voter, 0, 2, |_| Ok(()))
+            .unwrap();
+        // The new voter is not yet current, but is the newest entry.
+        assert_eq!(vote_state.get_authorized_voter(0), Some(fixture_pubkey(2)));
+        assert_eq!(
+            plan(&vote_state, &new_operator),
+            Ok(vec![
+                TransferStepKind::RotateWithdrawer,
+                TransferStepKind::UpdateIdentity,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_voter_change_pending() {
+        let mut vote_state = initialized_vote_state();
+        vote_state
+            .set_new_authorized_voter(&fixture_pubkey(20), 0, 2, |_| Ok(()))
+            .unwrap();
+        assert_eq!(
+            plan(&vote_state, &new_operator()),
+            Err(TransferError::VoterChangePending(2))
+        );
+    }
+
+    #[test]
+    fn test_withdrawer_mismatch() {
+        let mut vote_state = initialized_vote_state();
+        vote_state.authorized_withdrawer = fixture_pubkey(20);
+        assert_eq!(
+            plan(&vote_state, &new_operator()),
+            Err(TransferError::WithdrawerMismatch {
+                expected: fixture_pubkey(WITHDRAWER),
+                actual: fixture_pubkey(20),
+            })
+        );
+    }
+}
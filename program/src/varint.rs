@@ -0,0 +1,246 @@
+//! LEB128 and `short_vec` length encoding shared by the compact codec and
+//! the sanitizer, so both accept exactly the encodings bincode does.
+
+/// Longest LEB128 encoding of a `u64`.
+const MAX_VARINT_U64_BYTES: usize = 10;
+/// Longest `short_vec` length prefix.
+const MAX_SHORT_U16_BYTES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum VarintError {
+    /// Input ended inside the encoding.
+    Truncated,
+    /// Overlong, overflowing or otherwise non-canonical encoding.
+    Invalid,
+    /// Decoded length is above the caller's maximum.
+    LengthExceeded(usize),
+}
+
+/// Decodes a LEB128 `u64` from the start of `data`, rejecting the same
+/// encodings as `serde_varint`. Returns the value and bytes consumed.
+pub(crate) fn decode_u64(data: &[u8]) -> Result<(u64, usize), VarintError> {
+    let mut value = 0u64;
+    for index in 0..MAX_VARINT_U64_BYTES {
+        let byte = *data.get(index).ok_or(VarintError::Truncated)?;
+        let shift = index * 7;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            // Bits lost to the shift, or a trailing zero byte.
+            if (value >> shift) as u8 != byte || (byte == 0 && index != 0) {
+                return Err(VarintError::Invalid);
+            }
+            return Ok((value, index + 1));
+        }
+    }
+    Err(VarintError::Invalid)
+}
+
+/// Decodes a `short_vec` length prefix from the start of `data`, rejecting
+/// lengths above `max`. Returns the length and bytes consumed.
+pub(crate) fn decode_short_u16_len(data: &[u8], max: usize) -> Result<(usize, usize), VarintError> {
+    let mut len = 0usize;
+    for index in 0..MAX_SHORT_U16_BYTES {
+        let byte = *data.get(index).ok_or(VarintError::Truncated)?;
+        if byte == 0 && index != 0 {
+            return Err(VarintError::Invalid);
+        }
+        len |= usize::from(byte & 0x7f) << (index * 7);
+        if byte & 0x80 == 0 {
+            if len > usize::from(u16::MAX) {
+                return Err(VarintError::Invalid);
+            }
+            if len > max {
+                return Err(VarintError::LengthExceeded(len));
+            }
+            return Ok((len, index + 1));
+        }
+    }
+    Err(VarintError::Invalid)
+}
+
+/// Appends `value` as LEB128.
+pub(crate) fn encode_u64(mut value: u64, data: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            data.push(byte);
+            return;
+        }
+        data.push(byte | 0x80);
+    }
+}
+
+/// Appends `len` as a `short_vec` length prefix.
+///
+/// Panics if `len` does not fit in a `u16`.
+pub(crate) fn encode_short_u16_len(len: usize, data: &mut Vec<u8>) {
+    let len = u16::try_from(len).expect("short_vec length fits in u16");
+    encode_u64(u64::from(len), data);
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        serde::{Deserialize, Serialize},
+        solana_program::{
+            serde_varint,
+            short_vec::{decode_shortu16_len, ShortU16},
+        },
+    };
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Varint(#[serde(with = "serde_varint")] u64);
+
+    /// Deterministic byte strings of `len` bytes.
+    fn samples(len: usize, count: usize) -> impl Iterator<Item = Vec<u8>> {
+        let mut state = len as u64;
+        (0..count).map(move |_| {
+            (0..len)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    (state >> 56) as u8
+                })
+                .collect()
+        })
+    }
+
+    fn check_u64(data: &[u8]) {
+        let expected = bincode::deserialize::<Varint>(data)
+            .ok()
+            .map(|varint| varint.0);
+        match decode_u64(data) {
+            Ok((value, consumed)) => {
+                assert_eq!(Some(value), expected, "{data:?}");
+                assert_eq!(
+                    bincode::serialize(&Varint(value)).unwrap(),
+                    data[..consumed],
+                    "{data:?}"
+                );
+            }
+            Err(_) => assert_eq!(expected, None, "{data:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_u64_matches_serde_varint() {
+        check_u64(&[]);
+        for byte in 0..=u8::MAX {
+            check_u64(&[byte]);
+            for second in 0..=u8::MAX {
+                check_u64(&[byte, second]);
+            }
+        }
+        for len in 3..=MAX_VARINT_U64_BYTES + 1 {
+            samples(len, 20_000).for_each(|data| check_u64(&data));
+        }
+    }
+
+    #[test]
+    fn test_decode_u64_edge_cases() {
+        let max = bincode::serialize(&Varint(u64::MAX)).unwrap();
+        assert_eq!(max.len(), MAX_VARINT_U64_BYTES);
+        assert_eq!(decode_u64(&max), Ok((u64::MAX, MAX_VARINT_U64_BYTES)));
+        // Bits past the 64th.
+        let mut overflow = max.clone();
+        overflow[MAX_VARINT_U64_BYTES - 1] = 0x03;
+        assert_eq!(decode_u64(&overflow), Err(VarintError::Invalid));
+        // An eleventh byte.
+        let mut too_long = vec![0x80; MAX_VARINT_U64_BYTES];
+        too_long.push(0x01);
+        assert_eq!(decode_u64(&too_long), Err(VarintError::Invalid));
+        // A trailing zero byte.
+        assert_eq!(decode_u64(&[0x81, 0x00]), Err(VarintError::Invalid));
+        // Every proper prefix of a valid encoding.
+        for len in 0..max.len() {
+            assert_eq!(decode_u64(&max[..len]), Err(VarintError::Truncated));
+        }
+        for data in [overflow, too_long] {
+            check_u64(&data);
+        }
+    }
+
+    #[test]
+    fn test_encode_u64_matches_serde_varint() {
+        // Shifted so samples cover every encoded length.
+        let values = samples(8, 10_000)
+            .map(|bytes| {
+                let shift = bytes[0] % 64;
+                u64::from_le_bytes(bytes.try_into().unwrap()) >> shift
+            })
+            .chain([0, 0x7f, 0x80, u64::from(u32::MAX), u64::MAX]);
+        for value in values {
+            let mut data = vec![];
+            encode_u64(value, &mut data);
+            assert_eq!(data, bincode::serialize(&Varint(value)).unwrap());
+            assert_eq!(decode_u64(&data), Ok((value, data.len())));
+        }
+    }
+
+    #[test]
+    fn test_decode_short_u16_len_matches_short_vec() {
+        let check = |data: &[u8]| {
+            assert_eq!(
+                decode_short_u16_len(data, usize::MAX).ok(),
+                decode_shortu16_len(data).ok(),
+                "{data:?}"
+            );
+        };
+        check(&[]);
+        for first in 0..=u8::MAX {
+            check(&[first]);
+            for second in 0..=u8::MAX {
+                check(&[first, second]);
+                // A third byte is only read after two continuation bits.
+                if first & second & 0x80 != 0 {
+                    for third in 0..=u8::MAX {
+                        check(&[first, second, third]);
+                    }
+                }
+            }
+        }
+        samples(MAX_SHORT_U16_BYTES + 1, 20_000).for_each(|data| check(&data));
+    }
+
+    #[test]
+    fn test_decode_short_u16_len_errors() {
+        // Past `u16::MAX`, and a fourth byte.
+        assert_eq!(
+            decode_short_u16_len(&[0x80, 0x80, 0x04], usize::MAX),
+            Err(VarintError::Invalid)
+        );
+        assert_eq!(
+            decode_short_u16_len(&[0x80, 0x80, 0x80, 0x01], usize::MAX),
+            Err(VarintError::Invalid)
+        );
+        // Non-canonical zero continuation.
+        assert_eq!(
+            decode_short_u16_len(&[0x81, 0x00], usize::MAX),
+            Err(VarintError::Invalid)
+        );
+        assert_eq!(
+            decode_short_u16_len(&[0xff, 0xff], usize::MAX),
+            Err(VarintError::Truncated)
+        );
+        assert_eq!(
+            decode_short_u16_len(&[0x80, 0x01], 127),
+            Err(VarintError::LengthExceeded(128))
+        );
+    }
+
+    #[test]
+    fn test_encode_short_u16_len_matches_short_vec() {
+        for len in 0..=u16::MAX {
+            let mut data = vec![];
+            encode_short_u16_len(usize::from(len), &mut data);
+            assert_eq!(data, bincode::serialize(&ShortU16(len)).unwrap());
+            assert_eq!(
+                decode_short_u16_len(&data, usize::MAX),
+                Ok((usize::from(len), data.len()))
+            );
+        }
+    }
+}
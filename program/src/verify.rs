@@ -0,0 +1,344 @@
+//! Off-chain checks for vote transactions received over gossip or TPU.
+//!
+//! These helpers confirm that each vote in a transaction is signed by the
+//! vote account's authorized voter for the epoch. They do not check the
+//! ed25519 signatures themselves, which callers are expected to have done
+//! already (for example during sigverify).
+
+use {
+    crate::{
+        sanitize::{sanitize_vote_instruction_data, SanitizeError},
+        wire,
+    },
+    solana_program::{
+        clock::Epoch,
+        message::VersionedMessage,
+        pubkey::Pubkey,
+        short_vec::decode_shortu16_len,
+        vote::{self, instruction::VoteInstruction, state::VoteState},
+    },
+    thiserror::Error,
+};
+
+pub(crate) const SIGNATURE_BYTES: usize = 64;
+/// Maximum transaction size, as in `solana_sdk::packet::PACKET_DATA_SIZE`.
+pub(crate) const PACKET_DATA_SIZE: u64 = 1280 - 40 - 8;
+
+/// Reasons a vote transaction fails verification.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum VoteVerificationError {
+    #[error("transaction could not be decoded")]
+    InvalidTransaction,
+    #[error("transaction contains no vote instructions")]
+    NoVoteInstruction,
+    #[error("vote instruction {index} is invalid: {err}")]
+    InvalidVoteInstruction { index: usize, err: SanitizeError },
+    #[error("messages that load accounts from address lookup tables are not supported")]
+    AddressLookupTablesUnsupported,
+    #[error("vote instruction references an account not in the message")]
+    MissingAccount,
+    #[error("vote account {0} is unknown")]
+    UnknownVoteAccount(Pubkey),
+    #[error("vote account {vote_pubkey} has no authorized voter for epoch {epoch}")]
+    NoAuthorizedVoter { vote_pubkey: Pubkey, epoch: Epoch },
+    #[error("vote for {vote_pubkey} is not signed by authorized voter {authorized_voter}")]
+    UnauthorizedSigner {
+        vote_pubkey: Pubkey,
+        authorized_voter: Pubkey,
+    },
+}
+
+/// A vote instruction whose signer matches the authorized voter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedVote {
+    pub vote_pubkey: Pubkey,
+    pub authorized_voter: Pubkey,
+    pub instruction: VoteInstruction,
+}
+
+/// Splits a wire-format transaction into its signature count and message.
+pub fn decode_transaction(
+    transaction: &[u8],
+) -> Result<(usize, VersionedMessage), VoteVerificationError> {
+    let (num_signatures, offset) =
+        decode_shortu16_len(transaction).map_err(|_| VoteVerificationError::InvalidTransaction)?;
+    let message_offset = num_signatures
+        .checked_mul(SIGNATURE_BYTES)
+        .and_then(|len| len.checked_add(offset))
+        .filter(|message_offset| *message_offset <= transaction.len())
+        .ok_or(VoteVerificationError::InvalidTransaction)?;
+    let message: VersionedMessage =
+        wire::deserialize(&transaction[message_offset..], PACKET_DATA_SIZE)
+            .map_err(|_| VoteVerificationError::InvalidTransaction)?;
+    message
+        .sanitize()
+        .map_err(|_| VoteVerificationError::InvalidTransaction)?;
+    if num_signatures != usize::from(message.header().num_required_signatures) {
+        return Err(VoteVerificationError::InvalidTransaction);
+    }
+    Ok((num_signatures, message))
+}
+
+/// Extracts every vote instruction from `transaction` and checks that each
+/// is signed by the vote account's authorized voter for `epoch`.
+///
+/// Vote program instructions that fail sanitization are reported rather than
+/// skipped. `get_vote_state` looks up the current state of a vote account.
+pub fn verify_vote_transaction<'a, F>(
+    transaction: &[u8],
+    epoch: Epoch,
+    get_vote_state: F,
+) -> Result<Vec<VerifiedVote>, VoteVerificationError>
+where
+    F: FnMut(&Pubkey) -> Option<&'a VoteState>,
+{
+    let (_, message) = decode_transaction(transaction)?;
+    verify_vote_message(&message, epoch, get_vote_state)
+}
+
+/// Like [`verify_vote_transaction`], for an already decoded message.
+///
+/// Only static account keys are resolved, so version 0 messages that load
+/// accounts from address lookup tables are rejected.
+pub fn verify_vote_message<'a, F>(
+    message: &VersionedMessage,
+    epoch: Epoch,
+    mut get_vote_state: F,
+) -> Result<Vec<VerifiedVote>, VoteVerificationError>
+where
+    F: FnMut(&Pubkey) -> Option<&'a VoteState>,
+{
+    if message
+        .address_table_lookups()
+        .is_some_and(|lookups| !lookups.is_empty())
+    {
+        return Err(VoteVerificationError::AddressLookupTablesUnsupported);
+    }
+    let account_keys = message.static_account_keys();
+    let mut verified_votes = vec![];
+    for (index, compiled_instruction) in message.instructions().iter().enumerate() {
+        if !account_keys
+            .get(usize::from(compiled_instruction.program_id_index))
+            .is_some_and(vote::program::check_id)
+        {
+            continue;
+        }
+        let instruction = sanitize_vote_instruction_data(&compiled_instruction.data)
+            .map_err(|err| VoteVerificationError::InvalidVoteInstruction { index, err })?;
+        if !instruction.is_simple_vote() {
+            continue;
+        }
+
+        let vote_pubkey = compiled_instruction
+            .accounts
+            .first()
+            .and_then(|index| account_keys.get(usize::from(*index)))
+            .ok_or(VoteVerificationError::MissingAccount)?;
+        let vote_state = get_vote_state(vote_pubkey)
+            .ok_or(VoteVerificationError::UnknownVoteAccount(*vote_pubkey))?;
+        let authorized_voter = vote_state.get_authorized_voter(epoch).ok_or(
+            VoteVerificationError::NoAuthorizedVoter {
+                vote_pubkey: *vote_pubkey,
+                epoch,
+            },
+        )?;
+
+        let signed_by_authorized_voter = compiled_instruction.accounts.iter().any(|index| {
+            let index = usize::from(*index);
+            message.is_signer(index) && account_keys.get(index) == Some(&authorized_voter)
+        });
+        if !signed_by_authorized_voter {
+            return Err(VoteVerificationError::UnauthorizedSigner {
+                vote_pubkey: *vote_pubkey,
+                authorized_voter,
+            });
+        }
+
+        verified_votes.push(VerifiedVote {
+            vote_pubkey: *vote_pubkey,
+            authorized_voter,
+            instruction,
+        });
+    }
+
+    if verified_votes.is_empty() {
+        return Err(VoteVerificationError::NoVoteInstruction);
+    }
+    Ok(verified_votes)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{fixture_bank_hash, fixture_pubkey, VoteStateBuilder},
+        solana_program::{
+            address_lookup_table::AddressLookupTableAccount,
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            message::{v0, Message},
+            vote::{instruction as vote_instruction, state::Vote},
+        },
+    };
+
+    const VOTE_ACCOUNT: u8 = 0;
+    const VOTER: u8 = 2;
+    const NEXT_VOTER: u8 = 3;
+    const PAYER: u8 = 4;
+
+    /// A vote account whose voter is `VOTER` until epoch 2, then
+    /// `NEXT_VOTER`.
+    fn vote_state() -> VoteState {
+        let mut vote_state = VoteStateBuilder::new()
+            .authorized_voter(fixture_pubkey(VOTER))
+            .build();
+        vote_state
+            .set_new_authorized_voter(&fixture_pubkey(NEXT_VOTER), 0, 2, |_| Ok(()))
+            .unwrap();
+        vote_state
+    }
+
+    fn vote_instruction(voter: u8) -> Instruction {
+        vote_instruction::vote(
+            &fixture_pubkey(VOTE_ACCOUNT),
+            &fixture_pubkey(voter),
+            Vote::new(vec![1], fixture_bank_hash(1)),
+        )
+    }
+
+    /// `message` in wire format, with zeroed signatures.
+    fn wire_transaction(message: &VersionedMessage) -> Vec<u8> {
+        let num_signatures = message.header().num_required_signatures;
+        let mut transaction = vec![num_signatures];
+        transaction.resize(1 + usize::from(num_signatures) * SIGNATURE_BYTES, 0);
+        transaction.extend(bincode::serialize(message).unwrap());
+        transaction
+    }
+
+    fn verify(
+        message: VersionedMessage,
+        epoch: Epoch,
+    ) -> Result<Vec<VerifiedVote>, VoteVerificationError> {
+        let vote_state = vote_state();
+        verify_vote_transaction(&wire_transaction(&message), epoch, |vote_pubkey| {
+            (*vote_pubkey == fixture_pubkey(VOTE_ACCOUNT)).then_some(&vote_state)
+        })
+    }
+
+    fn legacy(instruction: Instruction) -> VersionedMessage {
+        VersionedMessage::Legacy(Message::new(&[instruction], Some(&fixture_pubkey(PAYER))))
+    }
+
+    fn unauthorized(authorized_voter: u8) -> Result<Vec<VerifiedVote>, VoteVerificationError> {
+        Err(VoteVerificationError::UnauthorizedSigner {
+            vote_pubkey: fixture_pubkey(VOTE_ACCOUNT),
+            authorized_voter: fixture_pubkey(authorized_voter),
+        })
+    }
+
+    #[test]
+    fn test_signed_by_authorized_voter() {
+        let instruction = vote_instruction(VOTER);
+        let expected = VerifiedVote {
+            vote_pubkey: fixture_pubkey(VOTE_ACCOUNT),
+            authorized_voter: fixture_pubkey(VOTER),
+            instruction: bincode::deserialize(&instruction.data).unwrap(),
+        };
+        assert_eq!(verify(legacy(instruction), 1), Ok(vec![expected]));
+        let instruction = vote_instruction(NEXT_VOTER);
+        assert_eq!(verify(legacy(instruction), 2).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_voter_not_signer() {
+        let mut instruction = vote_instruction(VOTER);
+        instruction.accounts[3].is_signer = false;
+        assert_eq!(verify(legacy(instruction), 1), unauthorized(VOTER));
+    }
+
+    #[test]
+    fn test_voter_signature_missing() {
+        // Signed only by the payer, which the instruction does not reference.
+        let mut instruction = vote_instruction(VOTER);
+        instruction.accounts.pop();
+        assert_eq!(verify(legacy(instruction), 1), unauthorized(VOTER));
+    }
+
+    #[test]
+    fn test_wrong_epoch_voter() {
+        assert_eq!(
+            verify(legacy(vote_instruction(VOTER)), 2),
+            unauthorized(NEXT_VOTER)
+        );
+        assert_eq!(
+            verify(legacy(vote_instruction(NEXT_VOTER)), 1),
+            unauthorized(VOTER)
+        );
+    }
+
+    #[test]
+    fn test_unknown_vote_account() {
+        let mut instruction = vote_instruction(VOTER);
+        instruction.accounts[0].pubkey = fixture_pubkey(9);
+        assert_eq!(
+            verify(legacy(instruction), 1),
+            Err(VoteVerificationError::UnknownVoteAccount(fixture_pubkey(9)))
+        );
+    }
+
+    #[test]
+    fn test_no_vote_instruction() {
+        let instruction = Instruction::new_with_bytes(fixture_pubkey(9), &[], vec![]);
+        assert_eq!(
+            verify(legacy(instruction), 1),
+            Err(VoteVerificationError::NoVoteInstruction)
+        );
+    }
+
+    #[test]
+    fn test_v0_message() {
+        let compile = |lookup_tables: &[AddressLookupTableAccount]| {
+            VersionedMessage::V0(
+                v0::Message::try_compile(
+                    &fixture_pubkey(PAYER),
+                    &[vote_instruction(VOTER)],
+                    lookup_tables,
+                    Hash::default(),
+                )
+                .unwrap(),
+            )
+        };
+        assert_eq!(verify(compile(&[]), 1).unwrap().len(), 1);
+
+        let lookup_table = AddressLookupTableAccount {
+            key: fixture_pubkey(8),
+            addresses: vec![fixture_pubkey(VOTE_ACCOUNT)],
+        };
+        assert_eq!(
+            verify(compile(&[lookup_table]), 1),
+            Err(VoteVerificationError::AddressLookupTablesUnsupported)
+        );
+    }
+
+    #[test]
+    fn test_invalid_vote_instruction_reported() {
+        let vote_pubkey = Pubkey::new_unique();
+        let voter = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            vote::program::id(),
+            &[2, 0, 0, 0, 0xff],
+            vec![
+                AccountMeta::new(vote_pubkey, false),
+                AccountMeta::new_readonly(voter, true),
+            ],
+        );
+        let message = VersionedMessage::Legacy(Message::new(&[instruction], Some(&voter)));
+        assert_eq!(
+            verify_vote_message(&message, 0, |_| None),
+            Err(VoteVerificationError::InvalidVoteInstruction {
+                index: 0,
+                err: SanitizeError::Truncated(4),
+            })
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Decoding of types stored as a tagged enum of layout versions.
+//!
+//! A versioned type serializes as a `u32` variant tag followed by one
+//! layout, the newest of which is its current type. [`Versioned`] names
+//! the pieces every such type has, and [`decode_current`] decodes any of
+//! them the same way, taking the newest layout without conversion.
+//!
+//! `VoteStateVersions` is the only versioned vote type in this SDK;
+//! `AuthorizedVoters` has a single layout.
+
+use {
+    crate::wire,
+    serde::de::DeserializeOwned,
+    solana_program::vote::state::{VoteState, VoteState1_14_11, VoteStateVersions},
+};
+
+/// A tagged enum over the layouts of [`Self::Current`].
+pub trait Versioned: DeserializeOwned {
+    type Current: DeserializeOwned;
+
+    /// Variant tag of the current layout.
+    const CURRENT_TAG: u32;
+
+    /// Variant tag of this value's layout.
+    fn tag(&self) -> u32;
+
+    /// Allocated size of data in the `tag` layout, or `None` if the program
+    /// no longer writes that layout or the tag is unknown.
+    fn size_of(tag: u32) -> Option<usize>;
+
+    fn is_uninitialized(&self) -> bool;
+
+    /// Like [`Versioned::is_uninitialized`], for a value decoded directly in
+    /// the current layout.
+    fn is_current_uninitialized(current: &Self::Current) -> bool;
+
+    fn convert_to_current(self) -> Self::Current;
+}
+
+impl Versioned for VoteStateVersions {
+    type Current = VoteState;
+
+    const CURRENT_TAG: u32 = 2;
+
+    fn tag(&self) -> u32 {
+        match self {
+            Self::V0_23_5(_) => 0,
+            Self::V1_14_11(_) => 1,
+            Self::Current(_) => Self::CURRENT_TAG,
+        }
+    }
+
+    fn size_of(tag: u32) -> Option<usize> {
+        match tag {
+            1 => Some(VoteState1_14_11::size_of()),
+            Self::CURRENT_TAG => Some(VoteState::size_of()),
+            _ => None,
+        }
+    }
+
+    fn is_uninitialized(&self) -> bool {
+        VoteStateVersions::is_uninitialized(self)
+    }
+
+    fn is_current_uninitialized(current: &VoteState) -> bool {
+        current.authorized_voters().is_empty()
+    }
+
+    fn convert_to_current(self) -> VoteState {
+        VoteStateVersions::convert_to_current(self)
+    }
+}
+
+/// Decodes `data` into `V`'s current type, or `None` if it is
+/// uninitialized.
+///
+/// Data already in the current layout is decoded straight into the current
+/// type, skipping the enum and its conversion.
+pub(crate) fn decode_current<V: Versioned>(data: &[u8]) -> bincode::Result<Option<V::Current>> {
+    let limit = data.len() as u64;
+    if let Some(current) = data.strip_prefix(&V::CURRENT_TAG.to_le_bytes()) {
+        let current: V::Current = wire::deserialize(current, limit)?;
+        return Ok((!V::is_current_uninitialized(&current)).then_some(current));
+    }
+    let versioned: V = wire::deserialize(data, limit)?;
+    Ok((!versioned.is_uninitialized()).then(|| versioned.convert_to_current()))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::test_support::{
+            fixture_pubkey, initialized_vote_state, populated_vote_state, serialize_1_14_11,
+            serialize_current,
+        },
+        solana_program::{pubkey::Pubkey, vote::state::MAX_LOCKOUT_HISTORY},
+    };
+
+    /// What the SDK decodes `data` to.
+    fn sdk_decode(data: &[u8]) -> Option<VoteState> {
+        let versions: VoteStateVersions = bincode::deserialize(data).unwrap();
+        (!versions.is_uninitialized()).then(|| versions.convert_to_current())
+    }
+
+    fn assert_decodes_as_sdk(data: &[u8]) {
+        assert_eq!(
+            decode_current::<VoteStateVersions>(data).unwrap(),
+            sdk_decode(data)
+        );
+    }
+
+    /// A 0.23.5 account with `votes` lockouts and one prior voter.
+    fn serialize_0_23_5(authorized_voter: Pubkey, votes: u64) -> Vec<u8> {
+        let mut data = 0u32.to_le_bytes().to_vec();
+        data.extend_from_slice(fixture_pubkey(1).as_ref());
+        data.extend_from_slice(authorized_voter.as_ref());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        for n in 0..32u8 {
+            let prior_voter = if n == 0 {
+                fixture_pubkey(9)
+            } else {
+                Pubkey::default()
+            };
+            data.extend_from_slice(prior_voter.as_ref());
+            for value in [u64::from(n == 0) * 3, u64::from(n == 0) * 6, 0] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(fixture_pubkey(3).as_ref());
+        data.push(10);
+        data.extend_from_slice(&votes.to_le_bytes());
+        for slot in 0..votes {
+            data.extend_from_slice(&(100 + slot).to_le_bytes());
+            data.extend_from_slice(&((votes - slot) as u32).to_le_bytes());
+        }
+        data.extend_from_slice(&[1]);
+        data.extend_from_slice(&99u64.to_le_bytes());
+        data.extend_from_slice(&1u64.to_le_bytes());
+        for value in [7u64, 2_000, 1_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&(100 + votes).to_le_bytes());
+        data.extend_from_slice(&1_600_000_000i64.to_le_bytes());
+        data.resize(VoteState::size_of(), 0);
+        data
+    }
+
+    #[test]
+    fn test_decode_0_23_5() {
+        let data = serialize_0_23_5(fixture_pubkey(2), 5);
+        assert!(sdk_decode(&data).is_some());
+        assert_decodes_as_sdk(&data);
+        assert_decodes_as_sdk(&serialize_0_23_5(fixture_pubkey(2), 0));
+    }
+
+    #[test]
+    fn test_decode_uninitialized_0_23_5() {
+        let data = serialize_0_23_5(Pubkey::default(), 0);
+        assert_eq!(sdk_decode(&data), None);
+        assert_decodes_as_sdk(&data);
+    }
+
+    #[test]
+    fn test_decode_1_14_11() {
+        for vote_state in [
+            initialized_vote_state(),
+            populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 40),
+        ] {
+            assert_decodes_as_sdk(&serialize_1_14_11(&vote_state).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_decode_current() {
+        for vote_state in [
+            initialized_vote_state(),
+            populated_vote_state(MAX_LOCKOUT_HISTORY, 8, 40),
+        ] {
+            assert_decodes_as_sdk(&serialize_current(&vote_state).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_decode_zeroed() {
+        for len in [VoteState1_14_11::size_of(), VoteState::size_of()] {
+            let data = vec![0; len];
+            assert_eq!(decode_current::<VoteStateVersions>(&data).unwrap(), None);
+            assert_decodes_as_sdk(&data);
+        }
+        for tag in [1u32, VoteStateVersions::CURRENT_TAG] {
+            let mut data = vec![0; VoteState::size_of()];
+            data[..4].copy_from_slice(&tag.to_le_bytes());
+            assert_eq!(decode_current::<VoteStateVersions>(&data).unwrap(), None);
+            assert_decodes_as_sdk(&data);
+        }
+    }
+}
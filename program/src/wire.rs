@@ -0,0 +1,34 @@
+//! The crate's only bincode configuration.
+//!
+//! Vote accounts, instructions and transactions use the encoding of
+//! bincode 1.x `serialize`/`deserialize`: little-endian fixed-width
+//! integers, with trailing input bytes allowed as in the runtime's
+//! `limited_deserialize`. Every byte limit is explicit.
+
+use {
+    bincode::{DefaultOptions, Options},
+    serde::{de::DeserializeOwned, Serialize},
+};
+
+fn options(limit: u64) -> impl Options {
+    DefaultOptions::new()
+        .with_little_endian()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+}
+
+/// Decodes a `T` from `data`, reading at most `limit` bytes.
+pub(crate) fn deserialize<T: DeserializeOwned>(data: &[u8], limit: u64) -> bincode::Result<T> {
+    options(limit).deserialize(data)
+}
+
+/// Encodes `value`, failing with `SizeLimit` past `limit` bytes.
+pub(crate) fn serialize<T: Serialize>(value: &T, limit: u64) -> bincode::Result<Vec<u8>> {
+    options(limit).serialize(value)
+}
+
+/// Encoded size of `value`.
+pub(crate) fn serialized_size<T: Serialize>(value: &T) -> bincode::Result<u64> {
+    options(u64::MAX).serialized_size(value)
+}
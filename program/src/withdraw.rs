@@ -0,0 +1,221 @@
+//! Destination checks for `Withdraw`.
+
+use {
+    solana_program::{
+        instruction::Instruction, pubkey, pubkey::Pubkey, rent::Rent, system_program,
+        vote::instruction as vote_instruction,
+    },
+    thiserror::Error,
+};
+
+/// SPL Token program id.
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+/// SPL Token-2022 program id.
+const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// What is known about a withdraw destination before sending.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DestinationAccount {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data_len: usize,
+}
+
+/// Reasons a withdraw destination is likely a mistake.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum DestinationWarning {
+    #[error("destination is a token account owned by {0}")]
+    TokenAccount(Pubkey),
+    #[error("destination is owned by program {0}")]
+    ProgramOwned(Pubkey),
+    #[error(
+        "destination would hold {balance} lamports, below the {required} needed for rent exemption"
+    )]
+    BelowRentExemption { balance: u64, required: u64 },
+}
+
+/// Withdraw refused by [`withdraw_checked`].
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("unsafe withdraw destination: {warnings:?}")]
+pub struct UnsafeDestination {
+    pub warnings: Vec<DestinationWarning>,
+}
+
+/// Checks a destination receiving `lamports`. `destination` is `None` if
+/// the account does not exist yet.
+pub fn check_withdraw_destination(
+    destination: Option<&DestinationAccount>,
+    lamports: u64,
+    rent: &Rent,
+) -> Vec<DestinationWarning> {
+    let mut warnings = vec![];
+    let (balance, data_len) = match destination {
+        Some(destination) => {
+            if destination.owner == TOKEN_PROGRAM_ID || destination.owner == TOKEN_2022_PROGRAM_ID {
+                warnings.push(DestinationWarning::TokenAccount(destination.owner));
+            } else if destination.owner != system_program::id() {
+                warnings.push(DestinationWarning::ProgramOwned(destination.owner));
+            }
+            (
+                destination.lamports.saturating_add(lamports),
+                destination.data_len,
+            )
+        }
+        None => (lamports, 0),
+    };
+    if !rent.is_exempt(balance, data_len) {
+        warnings.push(DestinationWarning::BelowRentExemption {
+            balance,
+            required: rent.minimum_balance(data_len),
+        });
+    }
+    warnings
+}
+
+/// Builds `Withdraw`, refusing if [`check_withdraw_destination`] reports
+/// anything.
+pub fn withdraw_checked(
+    vote_pubkey: &Pubkey,
+    authorized_withdrawer: &Pubkey,
+    lamports: u64,
+    to_pubkey: &Pubkey,
+    destination: Option<&DestinationAccount>,
+    rent: &Rent,
+) -> Result<Instruction, UnsafeDestination> {
+    let warnings = check_withdraw_destination(destination, lamports, rent);
+    if !warnings.is_empty() {
+        return Err(UnsafeDestination { warnings });
+    }
+    Ok(vote_instruction::withdraw(
+        vote_pubkey,
+        authorized_withdrawer,
+        lamports,
+        to_pubkey,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::test_support::fixture_pubkey};
+
+    fn account(owner: Pubkey, lamports: u64, data_len: usize) -> DestinationAccount {
+        DestinationAccount {
+            owner,
+            lamports,
+            data_len,
+        }
+    }
+
+    #[test]
+    fn test_system_account() {
+        let rent = Rent::default();
+        let destination = account(system_program::id(), rent.minimum_balance(0), 0);
+        assert_eq!(check_withdraw_destination(Some(&destination), 1, &rent), []);
+    }
+
+    #[test]
+    fn test_token_account() {
+        let rent = Rent::default();
+        for owner in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+            let destination = account(owner, rent.minimum_balance(165), 165);
+            assert_eq!(
+                check_withdraw_destination(Some(&destination), 1, &rent),
+                [DestinationWarning::TokenAccount(owner)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_program_owned() {
+        let rent = Rent::default();
+        let owner = fixture_pubkey(7);
+        let destination = account(owner, rent.minimum_balance(10), 10);
+        assert_eq!(
+            check_withdraw_destination(Some(&destination), 1, &rent),
+            [DestinationWarning::ProgramOwned(owner)]
+        );
+    }
+
+    #[test]
+    fn test_new_account_below_rent_exemption() {
+        let rent = Rent::default();
+        let required = rent.minimum_balance(0);
+        assert_eq!(check_withdraw_destination(None, required, &rent), []);
+        assert_eq!(
+            check_withdraw_destination(None, required - 1, &rent),
+            [DestinationWarning::BelowRentExemption {
+                balance: required - 1,
+                required,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rent_depends_on_data_len() {
+        let rent = Rent::default();
+        let required = rent.minimum_balance(200);
+        // Exempt with no data, short with 200 bytes.
+        let balance = rent.minimum_balance(0);
+        assert_eq!(
+            check_withdraw_destination(
+                Some(&account(system_program::id(), balance - 1, 0)),
+                1,
+                &rent
+            ),
+            []
+        );
+        assert_eq!(
+            check_withdraw_destination(
+                Some(&account(system_program::id(), balance - 1, 200)),
+                1,
+                &rent
+            ),
+            [DestinationWarning::BelowRentExemption { balance, required }]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_checked() {
+        let rent = Rent::default();
+        let vote_pubkey = fixture_pubkey(1);
+        let withdrawer = fixture_pubkey(2);
+        let to_pubkey = fixture_pubkey(3);
+        let destination = account(system_program::id(), rent.minimum_balance(0), 0);
+        assert_eq!(
+            withdraw_checked(
+                &vote_pubkey,
+                &withdrawer,
+                5,
+                &to_pubkey,
+                Some(&destination),
+                &rent
+            ),
+            Ok(vote_instruction::withdraw(
+                &vote_pubkey,
+                &withdrawer,
+                5,
+                &to_pubkey
+            ))
+        );
+        let token_account = account(TOKEN_PROGRAM_ID, 0, 165);
+        assert_eq!(
+            withdraw_checked(
+                &vote_pubkey,
+                &withdrawer,
+                5,
+                &to_pubkey,
+                Some(&token_account),
+                &rent
+            ),
+            Err(UnsafeDestination {
+                warnings: vec![
+                    DestinationWarning::TokenAccount(TOKEN_PROGRAM_ID),
+                    DestinationWarning::BelowRentExemption {
+                        balance: 5,
+                        required: rent.minimum_balance(165),
+                    },
+                ],
+            })
+        );
+    }
+}